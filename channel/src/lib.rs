@@ -112,6 +112,19 @@ pub struct CapacityGuard {
     pub size: usize,
 }
 
+impl CapacityGuard {
+    /// Splits off a guard holding `size` of the permits, or all of them if this guard holds fewer.
+    /// The permits are released separately when each guard is dropped.
+    pub fn split(&mut self, size: usize) -> CapacityGuard {
+        let size = min(size, self.size);
+        self.size -= size;
+        CapacityGuard {
+            semaphore: self.semaphore.clone(),
+            size,
+        }
+    }
+}
+
 impl Drop for CapacityGuard {
     fn drop(&mut self) {
         self.semaphore.release(self.size);
@@ -133,6 +146,7 @@ mod tests {
         runtime.block_on(test_sender_close_first());
         runtime.block_on(test_receiver_close_first());
         runtime.block_on(test_guard_drop());
+        runtime.block_on(test_guard_split());
     }
 
     async fn test_simple_test() {
@@ -290,4 +304,24 @@ mod tests {
             assert_eq!(tx.remain(), cap);
         }
     }
+
+    async fn test_guard_split() {
+        let (tx, mut rx) = create_channel(100);
+        tx.send((60, 60)).await.expect("send message to channel");
+        assert_eq!(tx.remain(), 40);
+
+        let (_event, mut guard) = rx.recv().await.expect("receive message from channel");
+        let first = guard.split(10);
+        let second = guard.split(80);
+        assert_eq!(first.size, 10);
+        assert_eq!(second.size, 50);
+        assert_eq!(guard.size, 0);
+
+        drop(first);
+        assert_eq!(tx.remain(), 50);
+        drop(guard);
+        assert_eq!(tx.remain(), 50);
+        drop(second);
+        assert_eq!(tx.remain(), 100);
+    }
 }
@@ -103,6 +103,18 @@ pub struct ClientConfig {
     #[get_copy = "pub"]
     #[builder(default = "self.default_timeout()")]
     pub request_timeout: Duration,
+
+    /// The maximum number of bytes per second written by all the writers created from the same
+    /// client factory. There is no limit by default.
+    #[get_copy = "pub"]
+    #[builder(default)]
+    pub max_write_bytes_per_sec: Option<u64>,
+
+    /// The maximum number of events per second written by all the writers created from the same
+    /// client factory. There is no limit by default.
+    #[get_copy = "pub"]
+    #[builder(default)]
+    pub max_write_events_per_sec: Option<u64>,
 }
 
 impl ClientConfigBuilder {
@@ -191,6 +203,9 @@ impl ClientConfigBuilder {
     /// contains a scheme, then verify that the uri scheme matches the is_tls_enabled
     /// value.
     fn validate(&self) -> Result<(), String> {
+        if self.max_write_bytes_per_sec == Some(Some(0)) || self.max_write_events_per_sec == Some(Some(0)) {
+            return Err("write rate limit should be greater than 0".to_owned());
+        }
         if self.is_tls_enabled.is_none()    // is_tls_enabled not specified
             || self.controller_uri.is_none()    // controller_uri not specified
             || self
@@ -259,6 +274,14 @@ mod tests {
         assert_eq!(config.max_controller_connections(), 3u32);
         assert_eq!(config.connection_type(), ConnectionType::Tokio);
         assert_eq!(config.retry_policy(), RetryWithBackoff::default());
+        assert_eq!(config.max_write_bytes_per_sec(), None);
+        assert_eq!(config.max_write_events_per_sec(), None);
+
+        assert!(ClientConfigBuilder::default()
+            .controller_uri(MOCK_CONTROLLER_URI)
+            .max_write_events_per_sec(0)
+            .build()
+            .is_err());
     }
 
     #[test]
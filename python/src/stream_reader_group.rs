@@ -166,7 +166,8 @@ impl StreamReaderGroup {
         match res {
             Ok(_) => Ok(()),
             Err(e) => match e {
-                ReaderGroupStateError::SyncError { .. } => {
+                ReaderGroupStateError::SyncError { .. }
+                | ReaderGroupStateError::CheckpointTimeoutError { .. } => {
                     error!("Failed to mark the reader {:?} offline {:?} ", reader_name, e);
                     Err(exceptions::PyValueError::new_err(format!(
                         " Failed to mark reader offline {:?}",
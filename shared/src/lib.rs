@@ -637,6 +637,11 @@ impl StreamSegments {
     }
 
     pub fn get_segment_for_string(&self, str: &str) -> &ScopedSegment {
+        self.get_segment(StreamSegments::hash_routing_key(str))
+    }
+
+    /// Hashes the routing key to a key in the range [0, 1) the same way as the Java client.
+    pub fn hash_routing_key(str: &str) -> f64 {
         let mut buffer_u16 = vec![0; str.len()];
 
         // convert uft-8 encoded Rust string to utf-16.
@@ -650,8 +655,7 @@ impl StreamSegments {
         let (upper, _lower) = murmurhash3_x64_128(buffer_u8, StreamSegments::SEED);
 
         // takes the first 64 bit as Java client uses asLong method.
-        let key = u64_to_f64_fraction(upper);
-        self.get_segment(key)
+        u64_to_f64_fraction(upper)
     }
 
     pub fn get_segments(&self) -> Vec<ScopedSegment> {
@@ -14,9 +14,14 @@ use crate::segment::reader::PrefetchingAsyncSegmentReader;
 
 use pravega_client_shared::{ScopedSegment, ScopedStream};
 
+use futures::future::{self, BoxFuture};
+use futures::ready;
 use std::convert::TryInto;
 use std::io::{Error, ErrorKind, SeekFrom};
+use std::pin::Pin;
 use std::sync::Arc;
+use std::task::{Context, Poll};
+use tokio::io::{AsyncRead, AsyncSeek, ReadBuf};
 use uuid::Uuid;
 
 /// A ByteReader enables reading raw bytes from a segment.
@@ -32,9 +37,15 @@ use uuid::Uuid;
 ///
 /// You can also wrap ByteReader with [`BufReader`], but doing so will not increase performance further.
 ///
+/// ByteReader implements tokio's [`AsyncRead`] and [`AsyncSeek`], so it can be used with the
+/// utilities built on top of them such as [`tokio::io::copy`] or [`tokio_util::codec`].
+///
 /// [`Read`]: https://doc.rust-lang.org/std/io/trait.Read.html
 /// [`Seek`]: https://doc.rust-lang.org/stable/std/io/trait.Seek.html
 /// [`BufReader`]: https://doc.rust-lang.org/std/io/struct.BufReader.html
+/// [`AsyncRead`]: tokio::io::AsyncRead
+/// [`AsyncSeek`]: tokio::io::AsyncSeek
+/// [`tokio_util::codec`]: https://docs.rs/tokio-util/latest/tokio_util/codec/index.html
 ///
 /// # Examples
 /// ```no_run
@@ -68,8 +79,10 @@ pub struct ByteReader {
     pub segment: ScopedSegment,
     reader: Option<PrefetchingAsyncSegmentReader>,
     reader_buffer_size: usize,
-    metadata_client: SegmentMetadataClient,
+    metadata_client: Arc<SegmentMetadataClient>,
     factory: ClientFactoryAsync,
+    // the offset that a seek started by AsyncSeek::start_seek resolves to.
+    pending_seek: Option<BoxFuture<'static, std::io::Result<i64>>>,
 }
 
 impl ByteReader {
@@ -105,8 +118,9 @@ impl ByteReader {
             segment: scoped_segment,
             reader: Some(async_reader_wrapper),
             reader_buffer_size: buffer_size,
-            metadata_client,
+            metadata_client: Arc::new(metadata_client),
             factory,
+            pending_seek: None,
         }
     }
 
@@ -182,49 +196,49 @@ impl ByteReader {
     /// of the stream or a byte offset relative to the current position in the stream.
     /// If the stream has been truncated, the byte offset will be relative to the original beginning of the stream.
     pub async fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        let offset = self.seek_offset(pos).await?;
+        self.recreate_reader_wrapper(offset);
+        Ok(offset as u64)
+    }
+
+    // Resolves the offset to seek to. The returned future does not borrow the ByteReader
+    // so that it can be kept across the calls of AsyncSeek.
+    fn seek_offset(&self, pos: SeekFrom) -> BoxFuture<'static, std::io::Result<i64>> {
         match pos {
-            SeekFrom::Start(offset) => {
-                let offset = offset.try_into().map_err(|e| {
-                    Error::new(
-                        ErrorKind::InvalidInput,
-                        format!("Overflowed when converting offset to i64: {:?}", e),
-                    )
-                })?;
-                self.recreate_reader_wrapper(offset);
-                Ok(offset as u64)
-            }
+            SeekFrom::Start(offset) => Box::pin(future::ready(offset.try_into().map_err(|e| {
+                Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("Overflowed when converting offset to i64: {:?}", e),
+                )
+            }))),
             SeekFrom::Current(offset) => {
                 let new_offset = self.reader.as_ref().unwrap().offset + offset;
-                if new_offset < 0 {
-                    Err(Error::new(
-                        ErrorKind::InvalidInput,
-                        "Cannot seek to a negative offset",
-                    ))
-                } else {
-                    self.recreate_reader_wrapper(new_offset);
-                    Ok(new_offset as u64)
-                }
+                Box::pin(future::ready(ByteReader::non_negative(new_offset)))
             }
             SeekFrom::End(offset) => {
-                let tail = self
-                    .metadata_client
-                    .fetch_current_segment_length()
-                    .await
-                    .map_err(|e| Error::new(ErrorKind::Other, format!("{:?}", e)))?;
-                if tail + offset < 0 {
-                    Err(Error::new(
-                        ErrorKind::InvalidInput,
-                        "Cannot seek to a negative offset",
-                    ))
-                } else {
-                    let new_offset = tail + offset;
-                    self.recreate_reader_wrapper(new_offset);
-                    Ok(new_offset as u64)
-                }
+                let metadata_client = self.metadata_client.clone();
+                Box::pin(async move {
+                    let tail = metadata_client
+                        .fetch_current_segment_length()
+                        .await
+                        .map_err(|e| Error::new(ErrorKind::Other, format!("{:?}", e)))?;
+                    ByteReader::non_negative(tail + offset)
+                })
             }
         }
     }
 
+    fn non_negative(offset: i64) -> std::io::Result<i64> {
+        if offset < 0 {
+            Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Cannot seek to a negative offset",
+            ))
+        } else {
+            Ok(offset)
+        }
+    }
+
     fn recreate_reader_wrapper(&mut self, offset: i64) {
         let internal_reader = self.reader.take().unwrap().extract_reader();
         let new_reader_wrapper = PrefetchingAsyncSegmentReader::new(
@@ -237,6 +251,50 @@ impl ByteReader {
     }
 }
 
+impl AsyncRead for ByteReader {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        let read = ready!(self
+            .get_mut()
+            .reader
+            .as_mut()
+            .unwrap()
+            .poll_read(cx, buf.initialize_unfilled()))
+        .map_err(|e| Error::new(ErrorKind::Other, format!("Error: {:?}", e)))?;
+        buf.advance(read);
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl AsyncSeek for ByteReader {
+    fn start_seek(self: Pin<&mut Self>, position: SeekFrom) -> std::io::Result<()> {
+        let reader = self.get_mut();
+        if reader.pending_seek.is_some() {
+            return Err(Error::new(
+                ErrorKind::Other,
+                "Cannot start a seek while another seek is in progress",
+            ));
+        }
+        reader.pending_seek = Some(reader.seek_offset(position));
+        Ok(())
+    }
+
+    fn poll_complete(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<u64>> {
+        let reader = self.get_mut();
+        let offset = match reader.pending_seek.as_mut() {
+            Some(seek) => ready!(seek.as_mut().poll(cx)),
+            None => return Poll::Ready(Ok(reader.current_offset())),
+        };
+        reader.pending_seek = None;
+        let offset = offset?;
+        reader.recreate_reader_wrapper(offset);
+        Poll::Ready(Ok(offset as u64))
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -246,6 +304,7 @@ mod test {
     use pravega_client_config::connection_type::{ConnectionType, MockType};
     use pravega_client_config::ClientConfigBuilder;
     use pravega_client_shared::PravegaNodeUri;
+    use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
     use tokio::runtime::Runtime;
 
     #[test]
@@ -306,6 +365,51 @@ mod test {
         assert!(rt.block_on(reader.seek(SeekFrom::End(-300))).is_err());
     }
 
+    #[test]
+    fn test_byte_async_io() {
+        let (mut writer, mut reader, factory) = create_reader_and_writer(Runtime::new().unwrap());
+        let rt = factory.runtime();
+        rt.block_on(async {
+            // copy 200 bytes into the writer
+            let payload = vec![1; 200];
+            let copied = tokio::io::copy(&mut payload.as_slice(), &mut writer)
+                .await
+                .expect("copy to byte writer");
+            assert_eq!(copied, 200);
+            writer.flush().await.expect("flush");
+            assert_eq!(writer.current_offset(), 200);
+
+            // read them back
+            let mut buf = vec![0; 200];
+            reader.read_exact(&mut buf).await.expect("read exact");
+            assert_eq!(buf, payload);
+
+            // seek relative to the end and to the current offset
+            let offset = AsyncSeekExt::seek(&mut reader, SeekFrom::End(-50))
+                .await
+                .expect("seek to end");
+            assert_eq!(offset, 150);
+            let offset = AsyncSeekExt::seek(&mut reader, SeekFrom::Current(-100))
+                .await
+                .expect("seek to current");
+            assert_eq!(offset, 50);
+            let mut buf = vec![0; 10];
+            reader.read_exact(&mut buf).await.expect("read exact");
+            assert_eq!(buf, vec![1; 10]);
+            assert_eq!(reader.current_offset(), 60);
+            assert!(AsyncSeekExt::seek(&mut reader, SeekFrom::End(-300))
+                .await
+                .is_err());
+
+            // shutting down flushes the writer without sealing the segment
+            writer.write_all(&payload).await.expect("write all");
+            writer.shutdown().await.expect("shutdown");
+            writer.write_all(&payload).await.expect("write all");
+            writer.flush().await.expect("flush");
+            assert_eq!(reader.current_tail().await.expect("get current tail"), 600);
+        });
+    }
+
     #[test]
     fn test_byte_stream_truncate() {
         let (mut writer, mut reader, factory) = create_reader_and_writer(Runtime::new().unwrap());
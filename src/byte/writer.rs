@@ -10,16 +10,26 @@
 
 use crate::client_factory::ClientFactoryAsync;
 use crate::error::Error;
+use crate::event::routing::HashRoutingStrategy;
 use crate::event::writer::EventWriter;
 use crate::segment::event::{Incoming, PendingEvent, RoutingInfo};
 use crate::segment::metadata::SegmentMetadataClient;
 use crate::segment::reactor::Reactor;
+use crate::segment::writer::SegmentWriterConfig;
 use crate::util::get_random_u128;
 
 use pravega_client_channel::{create_channel, ChannelSender};
 use pravega_client_shared::{ScopedSegment, ScopedStream, WriterId};
 
+use futures::future::{poll_fn, BoxFuture};
+use futures::ready;
 use std::collections::VecDeque;
+use std::future::Future;
+use std::io::ErrorKind;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use tokio::io::AsyncWrite;
 use tokio::sync::oneshot;
 use tracing::info_span;
 use tracing_futures::Instrument;
@@ -53,8 +63,17 @@ type EventHandle = oneshot::Receiver<Result<(), Error>>;
 /// failures. Internal retries will not violate the exactly once semantic so it is better to rely on them
 /// than to wrap this with custom retry logic.
 ///
+/// ## AsyncWrite
+/// ByteWriter implements tokio's [`AsyncWrite`], so it can be used with the utilities built on top
+/// of it such as [`tokio::io::copy`] or async compression encoders. `poll_write` returns once
+/// the previous write is handed to the Reactor, `poll_flush` behaves like [`flush`] and
+/// `poll_shutdown` flushes the pending writes. Shutting down does not seal the segment,
+/// call [`seal`] explicitly to do so.
+///
 /// [`channel`]: pravega_client_channel
 /// [`capacity`]: ByteWriter::CHANNEL_CAPACITY
+/// [`AsyncWrite`]: tokio::io::AsyncWrite
+/// [`seal`]: ByteWriter::seal
 /// [`EventWriter`]: crate::event::writer::EventWriter
 /// [`ByteReader`]: crate::byte::reader::ByteReader
 /// [`flush`]: ByteWriter::flush
@@ -102,6 +121,8 @@ pub struct ByteWriter {
     factory: ClientFactoryAsync,
     event_handles: VecDeque<EventHandle>,
     write_offset: i64,
+    // the write accepted by AsyncWrite::poll_write that is not handed to the Reactor yet.
+    pending_write: Option<BoxFuture<'static, EventHandle>>,
 }
 
 impl ByteWriter {
@@ -133,7 +154,18 @@ impl ByteWriter {
         let span = info_span!("Reactor", byte_stream_writer = %writer_id);
         // spawn is tied to the factory runtime.
         let _h = factory.runtime_handle().enter();
-        tokio::spawn(Reactor::run(stream, sender.clone(), receiver, factory.clone(), None).instrument(span));
+        tokio::spawn(
+            Reactor::run(
+                stream,
+                sender.clone(),
+                receiver,
+                factory.clone(),
+                None,
+                SegmentWriterConfig::default(),
+                Arc::new(HashRoutingStrategy),
+            )
+            .instrument(span),
+        );
         ByteWriter {
             writer_id,
             scoped_segment,
@@ -142,6 +174,7 @@ impl ByteWriter {
             factory,
             event_handles: VecDeque::new(),
             write_offset: 0,
+            pending_write: None,
         }
     }
 
@@ -156,20 +189,13 @@ impl ByteWriter {
     /// let size = byte_writer.write(&payload).await;
     /// ```
     pub async fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
+        poll_fn(|cx| self.poll_pending_write(cx)).await;
         let bytes_to_write = std::cmp::min(buf.len(), EventWriter::MAX_EVENT_SIZE);
         let payload = buf[0..bytes_to_write].to_vec();
-        let oneshot_receiver = self.write_internal(self.sender.clone(), payload).await;
+        let oneshot_receiver = self.write_internal(payload).await;
         self.write_offset += bytes_to_write as i64;
         self.event_handles.push_back(oneshot_receiver);
-
-        while let Some(handle) = self.event_handles.front_mut() {
-            if let Ok(res) = handle.try_recv() {
-                res?;
-                self.event_handles.pop_front().expect("remove successful handle");
-            } else {
-                break;
-            }
-        }
+        self.check_acked_writes()?;
         Ok(bytes_to_write)
     }
 
@@ -185,14 +211,7 @@ impl ByteWriter {
     /// byte_writer.flush().await;
     /// ```
     pub async fn flush(&mut self) -> Result<(), Error> {
-        while self.event_handles.front().is_some() {
-            let handle = self.event_handles.pop_front().expect("get first handle");
-            let event_result = handle.await.map_err(|e| Error::InternalFailure {
-                msg: format!("oneshot error {:?}", e),
-            })?;
-            event_result?;
-        }
-        Ok(())
+        poll_fn(|cx| self.poll_flush_internal(cx)).await
     }
 
     /// Seal the segment and no further writes are allowed.
@@ -271,6 +290,7 @@ impl ByteWriter {
     /// byte_writer.write(&payload).await.expect("write");
     /// ```
     pub async fn reset(&mut self) -> Result<(), Error> {
+        self.pending_write = None;
         self.event_handles.clear();
         // send reset signal to reactor
         self.sender
@@ -283,35 +303,103 @@ impl ByteWriter {
         Ok(())
     }
 
-    async fn write_internal(
-        &self,
-        sender: ChannelSender<Incoming>,
-        event: Vec<u8>,
-    ) -> oneshot::Receiver<Result<(), Error>> {
+    // Returns the future that hands the write to the Reactor. The future does not borrow the ByteWriter
+    // so that it can be kept across the calls of AsyncWrite.
+    #[allow(clippy::async_yields_async)] // the handle is awaited by flush, not by the caller
+    fn write_internal(&self, event: Vec<u8>) -> BoxFuture<'static, EventHandle> {
         let size = event.len();
         let (tx, rx) = oneshot::channel();
         let routing_info = RoutingInfo::Segment(self.scoped_segment.clone());
-        if let Some(pending_event) =
-            PendingEvent::without_header(routing_info, event, Some(self.write_offset), tx)
-        {
-            let append_event = Incoming::AppendEvent(pending_event);
-            if let Err(_e) = sender.send((append_event, size)).await {
-                let (tx_error, rx_error) = oneshot::channel();
-                tx_error
-                    .send(Err(Error::InternalFailure {
-                        msg: "failed to send request to reactor".to_string(),
-                    }))
-                    .expect("send error");
-                return rx_error;
+        let pending_event = PendingEvent::without_header(routing_info, event, Some(self.write_offset), tx);
+        let sender = self.sender.clone();
+        let factory = self.factory.clone();
+        let stream = ScopedStream::from(&self.scoped_segment);
+        Box::pin(async move {
+            if let Some(pending_event) = pending_event {
+                if let Some(limiter) = factory.write_rate_limiter() {
+                    limiter.acquire(&stream, size, 1).await;
+                }
+                let append_event = Incoming::AppendEvent(pending_event);
+                if let Err(_e) = sender.send((append_event, size)).await {
+                    let (tx_error, rx_error) = oneshot::channel();
+                    tx_error
+                        .send(Err(Error::InternalFailure {
+                            msg: "failed to send request to reactor".to_string(),
+                        }))
+                        .expect("send error");
+                    return rx_error;
+                }
             }
+            rx
+        })
+    }
+
+    // Waits until the write accepted by poll_write is handed to the Reactor.
+    fn poll_pending_write(&mut self, cx: &mut Context<'_>) -> Poll<()> {
+        if let Some(write) = self.pending_write.as_mut() {
+            let handle = ready!(write.as_mut().poll(cx));
+            self.pending_write = None;
+            self.event_handles.push_back(handle);
         }
-        rx
+        Poll::Ready(())
     }
+
+    // Removes the handles of the writes that are acknowledged, returns the error of a failed write.
+    fn check_acked_writes(&mut self) -> Result<(), Error> {
+        while let Some(handle) = self.event_handles.front_mut() {
+            if let Ok(res) = handle.try_recv() {
+                // a received handle must not be polled again.
+                self.event_handles
+                    .pop_front()
+                    .expect("remove acknowledged handle");
+                res?;
+            } else {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    fn poll_flush_internal(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Error>> {
+        ready!(self.poll_pending_write(cx));
+        while let Some(handle) = self.event_handles.front_mut() {
+            let event_result = ready!(Pin::new(handle).poll(cx));
+            self.event_handles.pop_front().expect("get first handle");
+            event_result.map_err(|e| Error::InternalFailure {
+                msg: format!("oneshot error {:?}", e),
+            })??;
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl AsyncWrite for ByteWriter {
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<std::io::Result<usize>> {
+        let writer = self.get_mut();
+        ready!(writer.poll_pending_write(cx));
+        writer.check_acked_writes().map_err(to_io_error)?;
+        let bytes_to_write = std::cmp::min(buf.len(), EventWriter::MAX_EVENT_SIZE);
+        writer.pending_write = Some(writer.write_internal(buf[0..bytes_to_write].to_vec()));
+        writer.write_offset += bytes_to_write as i64;
+        Poll::Ready(Ok(bytes_to_write))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        self.get_mut().poll_flush_internal(cx).map_err(to_io_error)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        self.poll_flush(cx)
+    }
+}
+
+fn to_io_error(e: Error) -> std::io::Error {
+    std::io::Error::new(ErrorKind::Other, format!("{:?}", e))
 }
 
 impl Drop for ByteWriter {
     fn drop(&mut self) {
-        let _res = self.sender.send_without_bp(Incoming::Close());
+        let _res = self.sender.send_without_bp(Incoming::Close(None));
     }
 }
 
@@ -15,9 +15,12 @@
 //!
 use crate::byte::reader::ByteReader;
 use crate::byte::writer::ByteWriter;
+use crate::event::pipeline::{PipelineError, TransactionalPipeline};
 use crate::event::reader_group::{ReaderGroup, ReaderGroupConfig, ReaderGroupConfigBuilder};
+use crate::event::serializer::Serializer;
 use crate::event::transactional_writer::TransactionalEventWriter;
-use crate::event::writer::EventWriter;
+use crate::event::typed_writer::TypedEventWriter;
+use crate::event::writer::{EventWriter, EventWriterConfig};
 use crate::segment::metadata::SegmentMetadataClient;
 use crate::segment::raw_client::RawClientImpl;
 use crate::segment::reader::AsyncSegmentReaderImpl;
@@ -42,6 +45,7 @@ use pravega_wire_protocol::connection_factory::{
 
 use crate::index::{IndexReader, IndexWriter};
 use crate::util::meta::MetaClient;
+use crate::util::rate_limiter::WriteRateLimiter;
 use std::fmt;
 use std::fmt::Debug;
 use std::sync::Arc;
@@ -126,6 +130,27 @@ impl ClientFactory {
         self.client_factory_async.create_event_writer(stream)
     }
 
+    ///
+    /// Create an EventWriter tuned by the EventWriterConfig.
+    ///
+    pub fn create_event_writer_with_config(
+        &self,
+        stream: ScopedStream,
+        config: EventWriterConfig,
+    ) -> EventWriter {
+        self.client_factory_async
+            .create_event_writer_with_config(stream, config)
+    }
+
+    pub fn create_typed_event_writer<T, S: Serializer<T>>(
+        &self,
+        stream: ScopedStream,
+        serializer: S,
+    ) -> TypedEventWriter<T, S> {
+        self.client_factory_async
+            .create_typed_event_writer(stream, serializer)
+    }
+
     pub async fn create_reader_group(&self, reader_group_name: String, stream: ScopedStream) -> ReaderGroup {
         info!(
             "Creating reader group {:?} to read data from stream {:?}",
@@ -182,6 +207,18 @@ impl ClientFactory {
             .await
     }
 
+    pub async fn create_transactional_pipeline(
+        &self,
+        name: String,
+        input: ScopedStream,
+        output: ScopedStream,
+        writer_id: WriterId,
+    ) -> Result<TransactionalPipeline, PipelineError> {
+        self.client_factory_async
+            .create_transactional_pipeline(name, input, output, writer_id)
+            .await
+    }
+
     pub async fn create_byte_writer(&self, stream: ScopedStream) -> ByteWriter {
         self.client_factory_async.create_byte_writer(stream).await
     }
@@ -281,6 +318,7 @@ pub struct ClientFactoryAsync {
     controller_client: Arc<Box<dyn ControllerClient>>,
     config: Arc<ClientConfig>,
     runtime_handle: Handle,
+    write_rate_limiter: Option<Arc<WriteRateLimiter>>,
 }
 
 impl ClientFactoryAsync {
@@ -292,11 +330,13 @@ impl ClientFactoryAsync {
         } else {
             Box::new(ControllerClientImpl::new(config.clone(), &handle)) as Box<dyn ControllerClient>
         };
+        let write_rate_limiter = WriteRateLimiter::from_config(&config).map(Arc::new);
         ClientFactoryAsync {
             connection_pool: Arc::new(pool),
             controller_client: Arc::new(controller),
             config: Arc::new(config),
             runtime_handle: handle,
+            write_rate_limiter,
         }
     }
     pub fn config(&self) -> &ClientConfig {
@@ -304,7 +344,23 @@ impl ClientFactoryAsync {
     }
 
     pub fn create_event_writer(&self, stream: ScopedStream) -> EventWriter {
-        EventWriter::new(stream, self.clone())
+        self.create_event_writer_with_config(stream, EventWriterConfig::default())
+    }
+
+    pub fn create_event_writer_with_config(
+        &self,
+        stream: ScopedStream,
+        config: EventWriterConfig,
+    ) -> EventWriter {
+        EventWriter::new(stream, self.clone(), config)
+    }
+
+    pub fn create_typed_event_writer<T, S: Serializer<T>>(
+        &self,
+        stream: ScopedStream,
+        serializer: S,
+    ) -> TypedEventWriter<T, S> {
+        TypedEventWriter::new(self.create_event_writer(stream), serializer)
     }
 
     pub async fn create_stream_meta_client(&self, stream: ScopedStream) -> MetaClient {
@@ -352,6 +408,18 @@ impl ClientFactoryAsync {
         TransactionalEventWriter::new(stream, writer_id, self.clone()).await
     }
 
+    /// Create a TransactionalPipeline that reads the input stream and writes to the output stream
+    /// exactly once. A pipeline created with the same name resumes from the committed position.
+    pub async fn create_transactional_pipeline(
+        &self,
+        name: String,
+        input: ScopedStream,
+        output: ScopedStream,
+        writer_id: WriterId,
+    ) -> Result<TransactionalPipeline, PipelineError> {
+        TransactionalPipeline::new(name, input, output, writer_id, self.clone()).await
+    }
+
     pub async fn create_byte_writer(&self, stream: ScopedStream) -> ByteWriter {
         ByteWriter::new(stream, self.clone()).await
     }
@@ -431,6 +499,12 @@ impl ClientFactoryAsync {
         token_provider
     }
 
+    /// Returns the write rate limiter shared by all the writers created from this factory, if the
+    /// write rate is limited in the client config.
+    pub(crate) fn write_rate_limiter(&self) -> Option<&WriteRateLimiter> {
+        self.write_rate_limiter.as_deref()
+    }
+
     pub(crate) fn get_connection_pool(&self) -> &ConnectionPool<SegmentConnectionManager> {
         &self.connection_pool
     }
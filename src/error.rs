@@ -8,9 +8,10 @@
 //     http://www.apache.org/licenses/LICENSE-2.0
 //
 
+use crate::event::writer::EventWriterError;
 use snafu::Snafu;
 
-#[derive(Debug, Snafu)]
+#[derive(Debug, Clone, Snafu)]
 pub enum Error {
     #[snafu(display("Conditional check failed: {}", msg))]
     ConditionalCheckFailure { msg: String },
@@ -20,4 +21,17 @@ pub enum Error {
 
     #[snafu(display("Input is invalid: {}", msg))]
     InvalidInput { msg: String },
+
+    #[snafu(display("Writer is closed: {}", msg))]
+    WriterClosed { msg: String },
+
+    #[snafu(display("Writer failed: {}", source))]
+    WriterFailure { source: EventWriterError },
+
+    #[snafu(display("{} of {} events in the batch failed: {:?}", failures.len(), total, failures))]
+    BatchFailure {
+        total: usize,
+        /// The index of every failed event in the batch along with its error.
+        failures: Vec<(usize, Error)>,
+    },
 }
@@ -0,0 +1,250 @@
+//
+// Copyright (c) Dell Inc., or its subsidiaries. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+
+//! Client side compression of events.
+//!
+//! A compressed event is wrapped in an envelope made of a magic number, the version of the envelope
+//! and the codec used to compress the payload. Readers detect the envelope and decompress the event
+//! with the codec it names, so events written with different codecs or without compression can be
+//! read from the same stream.
+
+use byteorder::{ByteOrder, LittleEndian};
+use snafu::Snafu;
+use std::fmt;
+use std::io::Read;
+use tracing::warn;
+
+// The magic number starts with a zero byte so that it does not collide with text payloads.
+const MAGIC: [u8; 4] = [0x00, b'P', b'C', b'Z'];
+const VERSION: u8 = 1;
+const ENVELOPE_HEADER_SIZE: usize = MAGIC.len() + 2;
+const ZSTD_LEVEL: i32 = 3;
+
+/// The codec used to compress the events written by an [`EventWriter`].
+///
+/// [`EventWriter`]: crate::event::writer::EventWriter
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Compression {
+    #[default]
+    None,
+    Zstd,
+    Lz4,
+    Snappy,
+}
+
+impl Compression {
+    fn id(self) -> u8 {
+        match self {
+            Compression::None => 0,
+            Compression::Zstd => 1,
+            Compression::Lz4 => 2,
+            Compression::Snappy => 3,
+        }
+    }
+
+    fn from_id(id: u8) -> Option<Compression> {
+        match id {
+            0 => Some(Compression::None),
+            1 => Some(Compression::Zstd),
+            2 => Some(Compression::Lz4),
+            3 => Some(Compression::Snappy),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Compression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+#[derive(Debug, Snafu)]
+pub enum CompressionError {
+    #[snafu(display("Failed to compress event with {}: {}", codec, error_msg))]
+    Compress { codec: Compression, error_msg: String },
+
+    #[snafu(display("Failed to decompress event with {}: {}", codec, error_msg))]
+    Decompress { codec: Compression, error_msg: String },
+
+    #[snafu(display("Unsupported compressed event: {}", error_msg))]
+    UnsupportedEnvelope { error_msg: String },
+}
+
+/// Compress the event and wrap it in an envelope.
+///
+/// The event is returned unchanged if the codec is `Compression::None` or if compressing does not
+/// reduce its size.
+pub(crate) fn compress(codec: Compression, event: Vec<u8>) -> Result<Vec<u8>, CompressionError> {
+    let compressed = match codec {
+        Compression::None => return Ok(event),
+        Compression::Zstd => zstd::bulk::compress(&event, ZSTD_LEVEL).map_err(|e| e.to_string()),
+        Compression::Lz4 => Ok(lz4_flex::compress_prepend_size(&event)),
+        Compression::Snappy => snap::raw::Encoder::new()
+            .compress_vec(&event)
+            .map_err(|e| e.to_string()),
+    }
+    .map_err(|error_msg| CompressionError::Compress { codec, error_msg })?;
+    if compressed.len() + ENVELOPE_HEADER_SIZE >= event.len() {
+        return Ok(event);
+    }
+    let mut envelope = Vec::with_capacity(ENVELOPE_HEADER_SIZE + compressed.len());
+    envelope.extend_from_slice(&MAGIC);
+    envelope.push(VERSION);
+    envelope.push(codec.id());
+    envelope.extend_from_slice(&compressed);
+    Ok(envelope)
+}
+
+/// Returns true if the event is wrapped in a compression envelope.
+pub(crate) fn is_compressed(event: &[u8]) -> bool {
+    event.len() >= ENVELOPE_HEADER_SIZE && event[..MAGIC.len()] == MAGIC
+}
+
+/// Unwrap the envelope and decompress the event. Events without an envelope are returned unchanged.
+///
+/// The size of the decompressed event is bounded by `max_size` since the size recorded in the
+/// compressed data cannot be trusted.
+pub(crate) fn decompress(event: &[u8], max_size: usize) -> Result<Vec<u8>, CompressionError> {
+    if !is_compressed(event) {
+        return Ok(event.to_vec());
+    }
+    let version = event[MAGIC.len()];
+    if version != VERSION {
+        return Err(CompressionError::UnsupportedEnvelope {
+            error_msg: format!("unknown envelope version {}", version),
+        });
+    }
+    let id = event[MAGIC.len() + 1];
+    let codec = Compression::from_id(id).ok_or_else(|| CompressionError::UnsupportedEnvelope {
+        error_msg: format!("unknown codec {}", id),
+    })?;
+    let payload = &event[ENVELOPE_HEADER_SIZE..];
+    let too_large = |size: usize| format!("decompressed size {} exceeds the maximum of {}", size, max_size);
+    match codec {
+        // uncompressed events are never wrapped.
+        Compression::None => {
+            return Err(CompressionError::UnsupportedEnvelope {
+                error_msg: "envelope without codec".to_string(),
+            })
+        }
+        Compression::Zstd => {
+            let mut value = Vec::new();
+            // the frame may not record its size, read one byte past the maximum to detect larger ones.
+            let res = zstd::stream::read::Decoder::new(payload).and_then(|decoder| {
+                decoder
+                    .take((max_size as u64).saturating_add(1))
+                    .read_to_end(&mut value)
+            });
+            match res {
+                Ok(size) if size > max_size => Err(too_large(size)),
+                Ok(_) => Ok(value),
+                Err(e) => Err(e.to_string()),
+            }
+        }
+        Compression::Lz4 => match payload.get(..4).map(LittleEndian::read_u32) {
+            Some(size) if size as usize > max_size => Err(too_large(size as usize)),
+            _ => lz4_flex::decompress_size_prepended(payload).map_err(|e| e.to_string()),
+        },
+        Compression::Snappy => match snap::raw::decompress_len(payload) {
+            Ok(size) if size > max_size => Err(too_large(size)),
+            _ => snap::raw::Decoder::new()
+                .decompress_vec(payload)
+                .map_err(|e| e.to_string()),
+        },
+    }
+    .map_err(|error_msg| CompressionError::Decompress { codec, error_msg })
+}
+
+/// Decompress the event read from a segment. An event that fails to decompress is returned as it is
+/// so that the application can still inspect it.
+pub(crate) fn decompress_or_raw(
+    event: Vec<u8>,
+    max_size: usize,
+    segment: &str,
+    offset_in_segment: i64,
+) -> Vec<u8> {
+    if !is_compressed(&event) {
+        return event;
+    }
+    match decompress(&event, max_size) {
+        Ok(value) => value,
+        Err(e) => {
+            warn!(
+                "failed to decompress event at offset {} of segment {}: {}",
+                offset_in_segment, segment, e
+            );
+            event
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_compression_round_trip() {
+        let event = br#"{"sensor":"temperature","value":21.5}"#.repeat(100);
+        for codec in [Compression::Zstd, Compression::Lz4, Compression::Snappy].iter() {
+            let compressed = compress(*codec, event.clone()).expect("compress");
+            assert!(is_compressed(&compressed));
+            assert_eq!(compressed[MAGIC.len() + 1], codec.id());
+            assert!(compressed.len() < event.len());
+            assert_eq!(decompress(&compressed, event.len()).expect("decompress"), event);
+            // the decompressed size is bounded.
+            assert!(matches!(
+                decompress(&compressed, event.len() - 1),
+                Err(CompressionError::Decompress { .. })
+            ));
+        }
+        // uncompressed events pass through.
+        assert_eq!(
+            compress(Compression::None, event.clone()).expect("compress"),
+            event
+        );
+        assert_eq!(decompress(&event, 0).expect("decompress"), event);
+        // events that do not shrink are not wrapped.
+        let tiny = b"a".to_vec();
+        assert_eq!(compress(Compression::Zstd, tiny.clone()).expect("compress"), tiny);
+    }
+
+    #[test]
+    fn test_decompress_invalid_envelope() {
+        let mut unknown_codec = MAGIC.to_vec();
+        unknown_codec.extend_from_slice(&[VERSION, 42, 1, 2, 3]);
+        assert!(matches!(
+            decompress(&unknown_codec, usize::MAX),
+            Err(CompressionError::UnsupportedEnvelope { .. })
+        ));
+        assert_eq!(
+            decompress_or_raw(unknown_codec.clone(), usize::MAX, "segment", 0),
+            unknown_codec
+        );
+
+        // uncompressed events are never wrapped.
+        let mut no_codec = MAGIC.to_vec();
+        no_codec.extend_from_slice(&[VERSION, Compression::None.id(), 1, 2, 3]);
+        assert!(matches!(
+            decompress(&no_codec, usize::MAX),
+            Err(CompressionError::UnsupportedEnvelope { .. })
+        ));
+
+        let mut corrupted = MAGIC.to_vec();
+        corrupted.extend_from_slice(&[VERSION, Compression::Zstd.id(), 1, 2, 3]);
+        assert!(matches!(
+            decompress(&corrupted, usize::MAX),
+            Err(CompressionError::Decompress {
+                codec: Compression::Zstd,
+                ..
+            })
+        ));
+    }
+}
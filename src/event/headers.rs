@@ -0,0 +1,151 @@
+//
+// Copyright (c) Dell Inc., or its subsidiaries. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+
+//! The envelope carrying the headers of an event.
+//!
+//! An event written with headers starts with a magic number and the version of the envelope,
+//! followed by the number of headers, every header as a length-prefixed key and value, and the
+//! payload. All the integers are in big-endian byte order.
+
+use snafu::Snafu;
+use std::collections::HashMap;
+use std::convert::TryInto;
+
+/// The headers of an event, such as a tracing id, a content type or a schema version.
+pub type EventHeaders = HashMap<String, String>;
+
+// The magic number starts with a zero byte so that it does not collide with text payloads.
+const MAGIC: [u8; 4] = [0x00, b'P', b'H', b'D'];
+const VERSION: u8 = 1;
+
+#[derive(Debug, Snafu)]
+pub enum EventHeadersError {
+    #[snafu(display("Failed to encode event headers: {}", error_msg))]
+    Encode { error_msg: String },
+
+    #[snafu(display("Failed to decode event headers: {}", error_msg))]
+    Decode { error_msg: String },
+}
+
+/// Wrap the payload in an envelope carrying the headers.
+pub(crate) fn encode(headers: &EventHeaders, payload: &[u8]) -> Result<Vec<u8>, EventHeadersError> {
+    let count: u16 = headers.len().try_into().map_err(|_| EventHeadersError::Encode {
+        error_msg: format!("{} headers exceed the limit of {}", headers.len(), u16::MAX),
+    })?;
+    let mut envelope = Vec::with_capacity(MAGIC.len() + 3 + payload.len());
+    envelope.extend_from_slice(&MAGIC);
+    envelope.push(VERSION);
+    envelope.extend_from_slice(&count.to_be_bytes());
+    for (key, value) in headers {
+        let key_len: u16 = key.len().try_into().map_err(|_| EventHeadersError::Encode {
+            error_msg: format!(
+                "header key of {} bytes exceeds the limit of {}",
+                key.len(),
+                u16::MAX
+            ),
+        })?;
+        let value_len: u32 = value.len().try_into().map_err(|_| EventHeadersError::Encode {
+            error_msg: format!("value of header {} exceeds the limit of {} bytes", key, u32::MAX),
+        })?;
+        envelope.extend_from_slice(&key_len.to_be_bytes());
+        envelope.extend_from_slice(key.as_bytes());
+        envelope.extend_from_slice(&value_len.to_be_bytes());
+        envelope.extend_from_slice(value.as_bytes());
+    }
+    envelope.extend_from_slice(payload);
+    Ok(envelope)
+}
+
+/// Returns true if the event is wrapped in a headers envelope.
+pub(crate) fn has_headers(event: &[u8]) -> bool {
+    event.len() > MAGIC.len() && event[..MAGIC.len()] == MAGIC
+}
+
+/// Split the event into its headers and its payload. The event must be wrapped in a headers envelope.
+pub(crate) fn decode(event: &[u8]) -> Result<(EventHeaders, Vec<u8>), EventHeadersError> {
+    let mut decoder = Decoder {
+        data: event,
+        position: MAGIC.len(),
+    };
+    let version = decoder.take(1)?[0];
+    if version != VERSION {
+        return Err(EventHeadersError::Decode {
+            error_msg: format!("unknown envelope version {}", version),
+        });
+    }
+    let count = u16::from_be_bytes(decoder.take(2)?.try_into().expect("2 bytes"));
+    let mut headers = EventHeaders::with_capacity(count as usize);
+    for _ in 0..count {
+        let key_len = u16::from_be_bytes(decoder.take(2)?.try_into().expect("2 bytes"));
+        let key = decoder.take_string(key_len as usize)?;
+        let value_len = u32::from_be_bytes(decoder.take(4)?.try_into().expect("4 bytes"));
+        let value = decoder.take_string(value_len as usize)?;
+        headers.insert(key, value);
+    }
+    Ok((headers, event[decoder.position..].to_vec()))
+}
+
+struct Decoder<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Decoder<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], EventHeadersError> {
+        if self.data.len() < self.position + len {
+            return Err(EventHeadersError::Decode {
+                error_msg: format!("envelope truncated at {} bytes", self.data.len()),
+            });
+        }
+        let bytes = &self.data[self.position..self.position + len];
+        self.position += len;
+        Ok(bytes)
+    }
+
+    fn take_string(&mut self, len: usize) -> Result<String, EventHeadersError> {
+        let bytes = self.take(len)?;
+        String::from_utf8(bytes.to_vec()).map_err(|e| EventHeadersError::Decode {
+            error_msg: e.to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_headers_round_trip() {
+        let mut headers = EventHeaders::new();
+        headers.insert("trace-id".to_string(), "4bf92f3577b34da6".to_string());
+        headers.insert("content-type".to_string(), "application/json".to_string());
+        let payload = br#"{"value":1}"#.to_vec();
+        let event = encode(&headers, &payload).expect("encode");
+        assert!(has_headers(&event));
+        assert_eq!(decode(&event).expect("decode"), (headers, payload.clone()));
+
+        // an event without headers.
+        assert!(!has_headers(&payload));
+        let event = encode(&EventHeaders::new(), &payload).expect("encode");
+        assert_eq!(decode(&event).expect("decode"), (EventHeaders::new(), payload));
+    }
+
+    #[test]
+    fn test_decode_invalid_envelope() {
+        let mut headers = EventHeaders::new();
+        headers.insert("schema-version".to_string(), "3".to_string());
+        let event = encode(&headers, b"payload").expect("encode");
+        // truncated in the middle of the header value.
+        assert!(decode(&event[..event.len() - 8]).is_err());
+        let mut unknown_version = event;
+        unknown_version[MAGIC.len()] = VERSION + 1;
+        assert!(decode(&unknown_version).is_err());
+    }
+}
@@ -0,0 +1,182 @@
+//
+// Copyright (c) Dell Inc., or its subsidiaries. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+
+//! The framing of events larger than the maximum event size.
+//!
+//! A large event is split into chunks which are written as consecutive events of the same segment.
+//! Every chunk starts with a header made of a magic number, the id of the large event, the index of
+//! the chunk and the number of chunks, all in big-endian byte order.
+
+use pravega_wire_protocol::commands::TYPE_PLUS_LENGTH_SIZE;
+use std::convert::TryInto;
+
+const MAGIC: u64 = 0x5052_4156_4c47_4556;
+pub(crate) const CHUNK_HEADER_SIZE: usize = 8 + 16 + 4 + 4;
+
+#[derive(Debug, PartialEq)]
+struct ChunkHeader {
+    id: u128,
+    index: u32,
+    count: u32,
+}
+
+impl ChunkHeader {
+    fn write(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(&MAGIC.to_be_bytes());
+        buf.extend_from_slice(&self.id.to_be_bytes());
+        buf.extend_from_slice(&self.index.to_be_bytes());
+        buf.extend_from_slice(&self.count.to_be_bytes());
+    }
+
+    fn read(payload: &[u8]) -> Option<ChunkHeader> {
+        if payload.len() < CHUNK_HEADER_SIZE || payload[0..8] != MAGIC.to_be_bytes() {
+            return None;
+        }
+        Some(ChunkHeader {
+            id: u128::from_be_bytes(payload[8..24].try_into().expect("16 bytes")),
+            index: u32::from_be_bytes(payload[24..28].try_into().expect("4 bytes")),
+            count: u32::from_be_bytes(payload[28..32].try_into().expect("4 bytes")),
+        })
+    }
+}
+
+/// Split the event into chunks whose size, header included, is at most `max_chunk_size`.
+pub(crate) fn split_into_chunks(id: u128, event: &[u8], max_chunk_size: usize) -> Vec<Vec<u8>> {
+    assert!(
+        max_chunk_size > CHUNK_HEADER_SIZE,
+        "chunk too small to hold its header"
+    );
+    let data_size = max_chunk_size - CHUNK_HEADER_SIZE;
+    let count = event.len().div_ceil(data_size) as u32;
+    event
+        .chunks(data_size)
+        .enumerate()
+        .map(|(index, data)| {
+            let mut chunk = Vec::with_capacity(CHUNK_HEADER_SIZE + data.len());
+            ChunkHeader {
+                id,
+                index: index as u32,
+                count,
+            }
+            .write(&mut chunk);
+            chunk.extend_from_slice(data);
+            chunk
+        })
+        .collect()
+}
+
+/// Returns the data of a chunk without its header.
+pub(crate) fn chunk_data(chunk: &[u8]) -> &[u8] {
+    &chunk[CHUNK_HEADER_SIZE..]
+}
+
+/// What the next event of the segment data is made of.
+#[derive(Debug, PartialEq)]
+pub(crate) enum NextEvent {
+    /// A regular event, which may not be completely read yet.
+    Regular,
+    /// A large event whose chunks are all read.
+    Chunked { chunks: usize },
+    /// A large event whose chunks are not all read yet.
+    Incomplete,
+    /// A chunk which is not followed by the rest of its large event.
+    Orphan,
+}
+
+/// Inspect the segment data, which starts with the type and length of an event, to find out how
+/// many events should be read to get the next event.
+pub(crate) fn next_event(data: &[u8]) -> NextEvent {
+    let header = match read_event(data, 0) {
+        Some(payload) => match ChunkHeader::read(payload) {
+            Some(header) => header,
+            None => return NextEvent::Regular,
+        },
+        None => return NextEvent::Regular,
+    };
+    if header.index != 0 {
+        return NextEvent::Orphan;
+    }
+    let mut position = TYPE_PLUS_LENGTH_SIZE as usize + payload_len(data, 0);
+    for index in 1..header.count {
+        let payload = match read_event(data, position) {
+            Some(payload) => payload,
+            None => return NextEvent::Incomplete,
+        };
+        match ChunkHeader::read(payload) {
+            Some(chunk) if chunk.id == header.id && chunk.index == index => {}
+            _ => return NextEvent::Orphan,
+        }
+        position += TYPE_PLUS_LENGTH_SIZE as usize + payload.len();
+    }
+    NextEvent::Chunked {
+        chunks: header.count as usize,
+    }
+}
+
+fn payload_len(data: &[u8], position: usize) -> usize {
+    i32::from_be_bytes(data[position + 4..position + 8].try_into().expect("4 bytes")) as usize
+}
+
+// Returns the payload of the event at the position if it is completely present in the data.
+fn read_event(data: &[u8], position: usize) -> Option<&[u8]> {
+    let start = position + TYPE_PLUS_LENGTH_SIZE as usize;
+    if data.len() < start {
+        return None;
+    }
+    let end = start + payload_len(data, position);
+    if data.len() < end {
+        return None;
+    }
+    Some(&data[start..end])
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use bytes::{BufMut, BytesMut};
+    use pravega_wire_protocol::commands::{Command, EventCommand};
+
+    fn to_segment_data(events: &[Vec<u8>]) -> BytesMut {
+        let mut buf = BytesMut::new();
+        for event in events {
+            buf.put_i32(EventCommand::TYPE_CODE);
+            buf.put_i32(event.len() as i32);
+            buf.put(event.as_slice());
+        }
+        buf
+    }
+
+    #[test]
+    fn test_split_and_scan_chunks() {
+        let event: Vec<u8> = (0..100u8).collect();
+        let chunks = split_into_chunks(7, &event, CHUNK_HEADER_SIZE + 30);
+        assert_eq!(chunks.len(), 4);
+        assert!(chunks.iter().all(|chunk| chunk.len() <= CHUNK_HEADER_SIZE + 30));
+        let joined: Vec<u8> = chunks
+            .iter()
+            .flat_map(|chunk| chunk_data(chunk).to_vec())
+            .collect();
+        assert_eq!(joined, event);
+
+        let data = to_segment_data(&chunks);
+        assert_eq!(next_event(&data), NextEvent::Chunked { chunks: 4 });
+        // the last chunk is only partially read.
+        assert_eq!(next_event(&data[..data.len() - 1]), NextEvent::Incomplete);
+        // the first chunk is missing.
+        assert_eq!(next_event(&to_segment_data(&chunks[1..])), NextEvent::Orphan);
+        // a chunk of another large event is in between.
+        let other = split_into_chunks(8, &event, CHUNK_HEADER_SIZE + 30);
+        let mixed = vec![chunks[0].clone(), other[1].clone()];
+        assert_eq!(next_event(&to_segment_data(&mixed)), NextEvent::Orphan);
+        // a regular event.
+        assert_eq!(next_event(&to_segment_data(&[event])), NextEvent::Regular);
+        assert_eq!(next_event(&data[..4]), NextEvent::Regular);
+    }
+}
@@ -25,12 +25,16 @@
 //! Application can await on this [oneshot] to make sure that an Event has been persisted in Pravega.
 //! If an Event is confirmed to be successfully persisted, any previous Events
 //! are also guaranteed to be persisted.
+//! Events can optionally be compressed on the client side with zstd, lz4 or snappy, and carry
+//! headers such as a tracing id or a content type next to their payload.
 //!
 //! ## [EventReader]
 //! [EventReader] reads Events from a Pravega Stream.
 //! An [EventReader] must belong to a [ReaderGroup]. The [EventReader] read call returns a slice of segment data
 //! and application can call its iterator API to get the next Event. After finishing the slice, reader
 //! drops the slice and will try to get another segment slice from the stream.
+//! Alternatively, [EventReader::events] yields the Events one at a time as a futures Stream and
+//! acquires and releases the segment slices internally.
 //!
 //! ## [ReaderGroup]
 //! [ReaderGroup] a named collection of [EventReader].
@@ -42,6 +46,10 @@
 //! that allows a distributed application to read and process Stream data in parallel.
 //! A large amount of Stream data can be consumed by a coordinated group of Readers in a Reader Group.
 //! For example, a collection of Flink tasks processing Stream data in parallel using Reader Group.
+//! A [ReaderGroup] can take a checkpoint, which is a consistent position of all its readers
+//! that can be used to restart reading from.
+//! The segments are evenly distributed among the readers by default, a weighted or sticky
+//! [SegmentAssignmentStrategy] can be configured instead.
 //!
 //! ## [TransactionalEventWriter]
 //! [TransactionalEventWriter] provides a way to execute [Transaction] in Pravega.
@@ -54,13 +62,31 @@
 //! or in the case of an error, the Transaction is aborted and the results disappear.
 //! See more [details].
 //!
+//! ## [TransactionalPipeline]
+//! [TransactionalPipeline] reads Events from an input Stream and writes the processed Events to an
+//! output Stream exactly once. The position of the input Stream is committed together with the
+//! [Transaction] on the output Stream, so a restarted pipeline resumes without duplicating or losing Events.
+//!
+//! ## [TypedEventWriter] and [TypedEventReader]
+//! [TypedEventWriter] and [TypedEventReader] wrap [EventWriter] and [EventReader] so that applications
+//! can write and read their own types instead of raw bytes. The conversion is done by a
+//! [Serializer] and a [Deserializer]; CBOR, JSON and bincode codecs are provided out of the box.
+//! A failure to decode an event is reported for that event only.
+//!
 //! [EventWriter]: crate::event::writer::EventWriter
 //! [oneshot]: https://docs.rs/tokio/1.5.0/tokio/sync/oneshot/index.html
 //! [TransactionalEventWriter]: crate::event::transactional_writer::TransactionalEventWriter
 //! [Transaction]: crate::event::transactional_writer::Transaction
 //! [details]: https://pravega.io/docs/nightly/pravega-concepts/#transactions
+//! [TransactionalPipeline]: crate::event::pipeline::TransactionalPipeline
 //! [EventReader]: crate::event::reader::EventReader
+//! [EventReader::events]: crate::event::reader::EventReader::events
 //! [ReaderGroup]: crate::event::reader_group::ReaderGroup
+//! [SegmentAssignmentStrategy]: crate::event::segment_assignment::SegmentAssignmentStrategy
+//! [TypedEventWriter]: crate::event::typed_writer::TypedEventWriter
+//! [TypedEventReader]: crate::event::typed_reader::TypedEventReader
+//! [Serializer]: crate::event::serializer::Serializer
+//! [Deserializer]: crate::event::serializer::Deserializer
 //!
 pub mod reader;
 #[doc(inline)]
@@ -81,3 +107,29 @@ pub mod writer;
 pub use writer::EventWriter;
 
 pub mod reader_group_state;
+
+pub mod compression;
+
+pub mod headers;
+
+pub(crate) mod large_event;
+
+pub mod pipeline;
+#[doc(inline)]
+pub use pipeline::TransactionalPipeline;
+
+pub mod routing;
+
+pub mod segment_assignment;
+
+pub mod serializer;
+#[doc(inline)]
+pub use serializer::{Deserializer, Serializer};
+
+pub mod typed_reader;
+#[doc(inline)]
+pub use typed_reader::TypedEventReader;
+
+pub mod typed_writer;
+#[doc(inline)]
+pub use typed_writer::TypedEventWriter;
@@ -0,0 +1,720 @@
+//
+// Copyright (c) Dell Inc., or its subsidiaries. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+
+use crate::client_factory::ClientFactoryAsync;
+use crate::event::reader::{EventReader, EventReaderError, ReadEvent};
+use crate::event::reader_group::{ReaderGroup, ReaderGroupConfigBuilder, StreamCutV1, StreamCutVersioned};
+use crate::event::reader_group_state::{Offset, ReaderGroupStateError};
+use crate::event::transactional_writer::{
+    Transaction, TransactionError, TransactionalEventWriter, TransactionalEventWriterError,
+};
+use crate::sync::table::{Table, TableError, Version};
+use crate::util::meta::{MetaClient, MetaClientError};
+
+use pravega_client_shared::{ScopedSegment, ScopedStream, Timestamp, TransactionStatus, TxId, WriterId};
+use pravega_controller_client::ControllerError;
+use pravega_wire_protocol::commands::TableKey;
+
+use serde::{Deserialize, Serialize};
+use snafu::{ResultExt, Snafu};
+use std::collections::{HashMap, HashSet};
+use tracing::{debug, info};
+
+/// Reads events from an input stream and writes the processed events to an output stream exactly
+/// once.
+///
+/// The events written to the output stream are accumulated in a [`Transaction`], and the position of
+/// the input stream the events are derived from is committed along with the transaction. The position
+/// is kept in a Table named after the pipeline. Committing goes in two steps: the position and the
+/// transaction are first recorded as pending, then the transaction is committed and the position
+/// recorded as committed. A pipeline created again with the same name after a crash finishes a
+/// pending commit according to the status of the transaction and resumes reading from the committed
+/// position, so every input event is reflected in the output stream exactly once. The position
+/// follows the scaling of the input stream, a segment read completely is replaced by its successors.
+///
+/// A pipeline reads the input stream with a single reader of a reader group named after the
+/// pipeline, so only one instance of a pipeline should run at a time. An instance that finds the
+/// position updated by another instance fails with [`PipelineError::Fenced`].
+///
+/// A pipeline whose commit, write or release of the events read fails may have lost track of its
+/// position, every later read, write or commit fails with [`PipelineError::Poisoned`]. Abort it and
+/// create it again to resume from the committed position.
+///
+/// # Examples
+///
+/// ```ignore
+/// let mut pipeline = client_factory
+///     .create_transactional_pipeline("pipeline".to_string(), input, output, WriterId(0))
+///     .await
+///     .expect("create pipeline");
+/// loop {
+///     let events = pipeline.read().await.expect("read input events");
+///     let output_events = events
+///         .into_iter()
+///         .map(|event| (None, event.value.to_ascii_uppercase()))
+///         .collect();
+///     pipeline.write_events(output_events).await.expect("write output events");
+///     pipeline.commit(Timestamp(0)).await.expect("commit");
+/// }
+/// ```
+pub struct TransactionalPipeline {
+    name: String,
+    input: ScopedStream,
+    reader_group: ReaderGroup,
+    reader: EventReader,
+    meta: MetaClient,
+    writer: TransactionalEventWriter,
+    state: PipelineState,
+    txn: Option<Transaction>,
+    // the position of the input stream after the events read so far.
+    position: HashMap<ScopedSegment, i64>,
+    // set when a failure leaves the position or the transaction in an unknown state.
+    poisoned: bool,
+}
+
+impl TransactionalPipeline {
+    pub(crate) async fn new(
+        name: String,
+        input: ScopedStream,
+        output: ScopedStream,
+        writer_id: WriterId,
+        factory: ClientFactoryAsync,
+    ) -> Result<Self, PipelineError> {
+        let mut state = PipelineState::load(&name, &input, &factory)
+            .await
+            .context(StateTableFailure {})?;
+        let mut writer = factory
+            .create_transactional_event_writer(output.clone(), writer_id)
+            .await;
+        state.recover(&mut writer, &output, &factory).await?;
+
+        let meta = factory.create_stream_meta_client(input.clone()).await;
+        let start = match &state.record.committed {
+            Some(position) => position.clone(),
+            None => meta
+                .fetch_current_head_segments()
+                .await
+                .context(StreamMetaFailure {})?,
+        };
+        let position = match &start {
+            StreamCutVersioned::V1(cut) => cut.get_positions(),
+            _ => HashMap::new(),
+        };
+        info!("pipeline {} resumes from position {:?}", name, position);
+
+        let config = ReaderGroupConfigBuilder::default()
+            .read_from_stream(input.clone(), start)
+            .build();
+        let mut reader_group = factory
+            .create_reader_group_with_config(input.scope.clone(), name.clone(), config.clone())
+            .await;
+        // the reader of a crashed pipeline is still online.
+        let reader_name = format!("{}-reader", name);
+        if reader_group
+            .list_readers()
+            .await
+            .iter()
+            .any(|reader| reader.name == reader_name)
+        {
+            reader_group
+                .reader_offline(reader_name.clone(), None)
+                .await
+                .context(ReaderGroupFailure {})?;
+        }
+        // the reader group is not reinitialized when it already exists.
+        reader_group.reset(config).await.context(ReaderGroupFailure {})?;
+        let reader = reader_group.create_reader(reader_name).await;
+
+        Ok(TransactionalPipeline {
+            name,
+            input,
+            reader_group,
+            reader,
+            meta,
+            writer,
+            state,
+            txn: None,
+            position,
+            poisoned: false,
+        })
+    }
+
+    /// Reads the events of the next segment slice of the input stream.
+    ///
+    /// An empty vector is returned if there is no event to read at the moment. The events are reflected
+    /// in the committed position of the input stream on the next commit.
+    pub async fn read(&mut self) -> Result<Vec<ReadEvent>, PipelineError> {
+        self.error_if_poisoned()?;
+        let mut slice = match self.reader.acquire_segment().await.context(ReaderFailure {})? {
+            Some(slice) if !slice.is_checkpoint() => slice,
+            _ => return Ok(vec![]),
+        };
+        let segment = ScopedSegment::from(slice.meta.scoped_segment.as_str());
+        let mut events = vec![];
+        for event in slice.by_ref() {
+            events.push(ReadEvent::new(segment.clone(), event));
+        }
+        let read_offset = slice.meta.read_offset;
+        if let Err(e) = self.reader.release_segment(slice).await {
+            self.poisoned = true;
+            return Err(e).context(ReaderFailure {});
+        }
+        self.position.insert(segment, read_offset);
+        Ok(events)
+    }
+
+    /// Writes a batch of processed events with their optional routing keys to the output stream.
+    ///
+    /// The events are written to the transaction of the pipeline, which is begun on the first write
+    /// after a commit. They become visible to the readers of the output stream on the next commit.
+    pub async fn write_events(
+        &mut self,
+        events: Vec<(Option<String>, Vec<u8>)>,
+    ) -> Result<(), PipelineError> {
+        self.error_if_poisoned()?;
+        if self.txn.is_none() {
+            let txn = self.writer.begin().await.context(TxnWriterFailure {})?;
+            debug!("pipeline {} began transaction {:?}", self.name, txn.txn_id());
+            self.txn = Some(txn);
+        }
+        let txn = self.txn.as_mut().expect("get transaction");
+        let result = txn.write_events(events).await.context(TxnFailure {});
+        self.poisoned = result.is_err();
+        result
+    }
+
+    /// Commits the events written to the output stream together with the position of the input
+    /// stream after the events read so far.
+    ///
+    /// If the commit fails, the pipeline is poisoned and should be aborted and created again, which
+    /// finishes or discards the commit and resumes from the committed position.
+    pub async fn commit(&mut self, timestamp: Timestamp) -> Result<(), PipelineError> {
+        self.error_if_poisoned()?;
+        let result = self.commit_position(timestamp).await;
+        self.poisoned = result.is_err();
+        result
+    }
+
+    // the transaction is kept until the position is committed, so that a failed commit can still
+    // be aborted.
+    async fn commit_position(&mut self, timestamp: Timestamp) -> Result<(), PipelineError> {
+        self.refresh_position().await?;
+        let position = StreamCutVersioned::V1(StreamCutV1::new(self.input.clone(), self.position.clone()));
+        let committed = self.state.record.committed.clone();
+        if let Some(txn) = self.txn.as_mut() {
+            txn.flush().await.context(TxnFailure {})?;
+            let pending = PendingCommit {
+                txn_id: PendingCommit::encode_txn_id(txn.txn_id()),
+                timestamp: timestamp.0,
+                position: position.clone(),
+            };
+            self.state
+                .save(PipelineRecord {
+                    committed,
+                    pending: Some(pending),
+                })
+                .await?;
+            txn.commit(timestamp).await.context(TxnFailure {})?;
+            debug!("pipeline {} committed transaction {:?}", self.name, txn.txn_id());
+        }
+        self.state
+            .save(PipelineRecord {
+                committed: Some(position),
+                pending: None,
+            })
+            .await?;
+        self.txn = None;
+        Ok(())
+    }
+
+    /// Aborts the events written to the output stream since the last commit.
+    ///
+    /// The pipeline is consumed because the events read since the last commit are not read again by
+    /// its reader. Create the pipeline again to resume from the committed position.
+    pub async fn abort(mut self) -> Result<(), PipelineError> {
+        if let Some(mut txn) = self.txn.take() {
+            match txn.abort().await {
+                // a failed commit closed the transaction, the pipeline created again recovers it.
+                Err(TransactionError::TxnClosed { .. }) if self.poisoned => {}
+                result => result.context(TxnFailure {})?,
+            }
+        }
+        self.reader.reader_offline().await.context(ReaderFailure {})
+    }
+
+    /// Returns the position of the input stream that is committed, None if nothing is committed yet.
+    pub fn committed_position(&self) -> Option<&StreamCutVersioned> {
+        self.state.record.committed.as_ref()
+    }
+
+    fn error_if_poisoned(&self) -> Result<(), PipelineError> {
+        if self.poisoned {
+            Err(PipelineError::Poisoned {
+                name: self.name.clone(),
+            })
+        } else {
+            Ok(())
+        }
+    }
+
+    // drops the segments which are read completely from the position and adds their successors, so
+    // that a pipeline created again resumes the successors from their offsets instead of reading them
+    // again from the start.
+    async fn refresh_position(&mut self) -> Result<(), PipelineError> {
+        let (segments, future_segments) = {
+            let mut state = self.reader_group.state.lock().await;
+            (
+                state.get_segment_positions().await,
+                state.get_future_segments().await,
+            )
+        };
+        let mut successors = HashMap::new();
+        for segment in self
+            .position
+            .keys()
+            .filter(|segment| !segments.contains_key(segment))
+        {
+            let segment_successors = self
+                .meta
+                .fetch_successors(segment)
+                .await
+                .context(StreamMetaFailure {})?;
+            successors.insert(segment.clone(), segment_successors);
+        }
+        self.position = Self::next_position(&self.position, segments, &future_segments, &successors);
+        Ok(())
+    }
+
+    // the position of the segments the reader group still has to read. The segments read so far are at
+    // the offset they are read up to, the others at the offset the reader group starts them from. A
+    // completed segment is kept until none of its successors waits for other predecessors, since such
+    // a successor would never be read if the pipeline started again without the completed segment.
+    fn next_position(
+        position: &HashMap<ScopedSegment, i64>,
+        segments: HashMap<ScopedSegment, Offset>,
+        future_segments: &HashSet<ScopedSegment>,
+        successors: &HashMap<ScopedSegment, Vec<ScopedSegment>>,
+    ) -> HashMap<ScopedSegment, i64> {
+        let mut next: HashMap<ScopedSegment, i64> = segments
+            .into_iter()
+            .map(|(segment, offset)| {
+                let offset = position.get(&segment).copied().unwrap_or(offset.read);
+                (segment, offset)
+            })
+            .collect();
+        for (segment, segment_successors) in successors {
+            if segment_successors
+                .iter()
+                .any(|successor| future_segments.contains(successor))
+            {
+                next.insert(segment.clone(), position[segment]);
+            }
+        }
+        next
+    }
+}
+
+// the record of the input position of a pipeline that is kept in a Table.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+struct PipelineRecord {
+    // the position up to which the input is reflected in the output stream.
+    committed: Option<StreamCutVersioned>,
+    // the commit of a transaction that may or may not be done yet.
+    pending: Option<PendingCommit>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct PendingCommit {
+    txn_id: String,
+    timestamp: u64,
+    position: StreamCutVersioned,
+}
+
+impl PendingCommit {
+    // cbor cannot encode u128, so the transaction id is stored as a string.
+    fn encode_txn_id(txn_id: TxId) -> String {
+        txn_id.0.to_string()
+    }
+
+    fn txn_id(&self) -> TxId {
+        TxId(self.txn_id.parse().expect("parse transaction id"))
+    }
+}
+
+// the record of a pipeline along with its version in the Table.
+struct PipelineState {
+    name: String,
+    table: Table,
+    record: PipelineRecord,
+    version: Version,
+}
+
+impl PipelineState {
+    const KEY: &'static str = "position";
+
+    async fn load(
+        name: &str,
+        input: &ScopedStream,
+        factory: &ClientFactoryAsync,
+    ) -> Result<Self, TableError> {
+        let table = factory
+            .create_table(input.scope.clone(), format!("{}-pipeline", name))
+            .await;
+        let (record, version) = table
+            .get(&PipelineState::KEY.to_string())
+            .await?
+            .unwrap_or((PipelineRecord::default(), TableKey::KEY_NOT_EXISTS));
+        Ok(PipelineState {
+            name: name.to_owned(),
+            table,
+            record,
+            version,
+        })
+    }
+
+    // the record is updated only if no other instance of the pipeline updated it since.
+    async fn save(&mut self, record: PipelineRecord) -> Result<(), PipelineError> {
+        let result = self
+            .table
+            .insert_conditionally(&PipelineState::KEY.to_string(), &record, self.version, -1)
+            .await;
+        match result {
+            Ok(version) => {
+                self.record = record;
+                self.version = version;
+                Ok(())
+            }
+            Err(TableError::IncorrectKeyVersion { .. }) => Fenced {
+                name: self.name.clone(),
+            }
+            .fail(),
+            Err(e) => Err(e).context(StateTableFailure {}),
+        }
+    }
+
+    // finishes the commit that was pending when the pipeline stopped.
+    async fn recover(
+        &mut self,
+        writer: &mut TransactionalEventWriter,
+        output: &ScopedStream,
+        factory: &ClientFactoryAsync,
+    ) -> Result<(), PipelineError> {
+        let pending = match self.record.pending.clone() {
+            Some(pending) => pending,
+            None => return Ok(()),
+        };
+        let txn_id = pending.txn_id();
+        let status = factory
+            .controller_client()
+            .check_transaction_status(output, txn_id)
+            .await
+            .map_err(|e| e.error)
+            .context(ControllerFailure {})?;
+        info!("recover pending transaction {:?} which is {:?}", txn_id, status);
+        let committed = match status {
+            TransactionStatus::Open => {
+                let mut txn = writer.get_txn(txn_id).await.context(TxnWriterFailure {})?;
+                txn.commit(Timestamp(pending.timestamp))
+                    .await
+                    .context(TxnFailure {})?;
+                Some(pending.position)
+            }
+            TransactionStatus::Committing | TransactionStatus::Committed => Some(pending.position),
+            TransactionStatus::Aborting | TransactionStatus::Aborted => self.record.committed.clone(),
+        };
+        self.save(PipelineRecord {
+            committed,
+            pending: None,
+        })
+        .await
+    }
+}
+
+#[derive(Debug, Snafu)]
+pub enum PipelineError {
+    #[snafu(display("Pipeline failed to read the input stream due to {:?}", source))]
+    ReaderFailure { source: EventReaderError },
+
+    #[snafu(display("Pipeline failed to set up the reader group due to {:?}", source))]
+    ReaderGroupFailure { source: ReaderGroupStateError },
+
+    #[snafu(display("Pipeline failed to fetch the head of the input stream due to {:?}", source))]
+    StreamMetaFailure { source: MetaClientError },
+
+    #[snafu(display("Pipeline failed to open a transaction due to {:?}", source))]
+    TxnWriterFailure { source: TransactionalEventWriterError },
+
+    #[snafu(display("Pipeline transaction failed due to {:?}", source))]
+    TxnFailure { source: TransactionError },
+
+    #[snafu(display("Pipeline failed to check the transaction status due to {:?}", source))]
+    ControllerFailure { source: ControllerError },
+
+    #[snafu(display("Pipeline failed to access its position due to {:?}", source))]
+    StateTableFailure { source: TableError },
+
+    #[snafu(display("Pipeline {} was updated by another instance of the pipeline", name))]
+    Fenced { name: String },
+
+    #[snafu(display("Pipeline {} failed earlier and has to be created again", name))]
+    Poisoned { name: String },
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::client_factory::ClientFactory;
+    use crate::util::create_stream;
+    use pravega_client_config::connection_type::{ConnectionType, MockType};
+    use pravega_client_config::ClientConfigBuilder;
+    use pravega_client_shared::PravegaNodeUri;
+    use tokio::runtime::Runtime;
+
+    #[test]
+    fn test_pipeline_position_across_scaling() {
+        let segment = |number: i64| ScopedSegment::from(format!("scope/input/{}", number).as_str());
+        let offsets = |offsets: Vec<(i64, i64)>| -> HashMap<ScopedSegment, Offset> {
+            offsets
+                .into_iter()
+                .map(|(number, offset)| (segment(number), Offset::new(offset)))
+                .collect()
+        };
+        let position = |offsets: Vec<(i64, i64)>| -> HashMap<ScopedSegment, i64> {
+            offsets
+                .into_iter()
+                .map(|(number, offset)| (segment(number), offset))
+                .collect()
+        };
+
+        // segment 0 is read completely and split into segments 1 and 2 between two commits, the
+        // reader group has started reading segment 1.
+        let next = TransactionalPipeline::next_position(
+            &position(vec![(0, 100), (1, 50)]),
+            offsets(vec![(1, 20), (2, 0)]),
+            &HashSet::new(),
+            &vec![(segment(0), vec![segment(1), segment(2)])]
+                .into_iter()
+                .collect(),
+        );
+        assert_eq!(next, position(vec![(1, 50), (2, 0)]));
+
+        // segments 1 and 2 merge into segment 3, segment 1 is kept while segment 3 waits for segment 2.
+        let successors = vec![(segment(1), vec![segment(3)])].into_iter().collect();
+        let future_segments = vec![segment(3)].into_iter().collect();
+        let next = TransactionalPipeline::next_position(
+            &position(vec![(1, 80), (2, 30)]),
+            offsets(vec![(2, 30)]),
+            &future_segments,
+            &successors,
+        );
+        assert_eq!(next, position(vec![(1, 80), (2, 30)]));
+
+        // both predecessors are completed, segment 3 is read from the start.
+        let successors = vec![(segment(1), vec![segment(3)]), (segment(2), vec![segment(3)])]
+            .into_iter()
+            .collect();
+        let next = TransactionalPipeline::next_position(
+            &position(vec![(1, 80), (2, 60)]),
+            offsets(vec![(3, 0)]),
+            &HashSet::new(),
+            &successors,
+        );
+        assert_eq!(next, position(vec![(3, 0)]));
+    }
+
+    #[test]
+    fn test_pipeline_recovery() {
+        let rt = Runtime::new().unwrap();
+        let config = ClientConfigBuilder::default()
+            .connection_type(ConnectionType::Mock(MockType::Happy))
+            .mock(true)
+            .controller_uri(PravegaNodeUri::from("127.0.0.2:9091"))
+            .build()
+            .unwrap();
+        let factory = ClientFactory::new(config);
+        rt.block_on(create_stream(&factory, "scope", "input", 1));
+        rt.block_on(create_stream(&factory, "scope", "output", 1));
+        let input = ScopedStream::from("scope/input");
+        let output = ScopedStream::from("scope/output");
+        let factory = factory.to_async();
+        let position = |offset| {
+            let positions = vec![(ScopedSegment::from("scope/input/0"), offset)]
+                .into_iter()
+                .collect();
+            StreamCutVersioned::V1(StreamCutV1::new(input.clone(), positions))
+        };
+        let load = || async {
+            PipelineState::load("pipeline", &input, &factory)
+                .await
+                .expect("load pipeline state")
+        };
+
+        rt.block_on(async {
+            let mut writer = factory
+                .create_transactional_event_writer(output.clone(), WriterId(0))
+                .await;
+            let mut state = load().await;
+            assert_eq!(state.record, PipelineRecord::default());
+
+            // the pipeline stops after recording the pending commit of an open transaction.
+            let mut txn = writer.begin().await.expect("begin transaction");
+            txn.write_event(None, vec![1; 16]).await.expect("write event");
+            txn.flush().await.expect("flush transaction");
+            let pending = PendingCommit {
+                txn_id: PendingCommit::encode_txn_id(txn.txn_id()),
+                timestamp: 0,
+                position: position(100),
+            };
+            state
+                .save(PipelineRecord {
+                    committed: Some(position(10)),
+                    pending: Some(pending),
+                })
+                .await
+                .expect("save pending commit");
+            // another instance of the pipeline fences this one off.
+            let mut other_state = load().await;
+            let record = other_state.record.clone();
+            other_state.save(record).await.expect("save pipeline state");
+            let result = state.save(PipelineRecord::default()).await;
+            assert!(matches!(result, Err(PipelineError::Fenced { .. })));
+
+            // the transaction is committed when the pipeline recovers.
+            let mut state = load().await;
+            state
+                .recover(&mut writer, &output, &factory)
+                .await
+                .expect("recover pipeline");
+            assert_eq!(state.record.committed, Some(position(100)));
+            assert!(state.record.pending.is_none());
+            let status = txn.check_status().await.expect("check status");
+            assert_eq!(status, TransactionStatus::Committed);
+
+            // an aborted transaction does not move the position.
+            let mut txn = writer.begin().await.expect("begin transaction");
+            let pending = PendingCommit {
+                txn_id: PendingCommit::encode_txn_id(txn.txn_id()),
+                timestamp: 0,
+                position: position(200),
+            };
+            state
+                .save(PipelineRecord {
+                    committed: Some(position(100)),
+                    pending: Some(pending),
+                })
+                .await
+                .expect("save pending commit");
+            txn.abort().await.expect("abort transaction");
+            let mut state = load().await;
+            state
+                .recover(&mut writer, &output, &factory)
+                .await
+                .expect("recover pipeline");
+            assert_eq!(state.record.committed, Some(position(100)));
+            assert!(state.record.pending.is_none());
+        });
+    }
+
+    #[test]
+    fn test_pipeline_poisoned_by_failed_commit() {
+        let rt = Runtime::new().unwrap();
+        let config = ClientConfigBuilder::default()
+            .connection_type(ConnectionType::Mock(MockType::Happy))
+            .mock(true)
+            .controller_uri(PravegaNodeUri::from("127.0.0.2:9091"))
+            .build()
+            .unwrap();
+        let factory = ClientFactory::new(config);
+        rt.block_on(create_stream(&factory, "scope", "input", 1));
+        rt.block_on(create_stream(&factory, "scope", "output", 1));
+        let input = ScopedStream::from("scope/input");
+        let output = ScopedStream::from("scope/output");
+        let factory = factory.to_async();
+
+        rt.block_on(async {
+            let name = "pipeline".to_string();
+            let config = ReaderGroupConfigBuilder::default()
+                .add_stream(input.clone())
+                .build();
+            let reader_group = factory
+                .create_reader_group_with_config(input.scope.clone(), name.clone(), config)
+                .await;
+            {
+                let mut rg_state = reader_group.state.lock().await;
+                rg_state.expect_add_reader().returning(|_| Ok(()));
+                rg_state.expect_get_generation().return_const(0u64);
+                rg_state.expect_get_end_offsets().returning(HashMap::new);
+                rg_state
+                    .expect_compute_segments_to_acquire_or_release()
+                    .returning(|_| Ok(0));
+                rg_state
+                    .expect_get_segments_for_reader()
+                    .returning(|_| Ok(HashSet::new()));
+                rg_state.expect_reader_heartbeat().returning(|_| Ok(()));
+                rg_state.expect_check_online().return_const(false);
+                rg_state.expect_get_segment_positions().returning(HashMap::new);
+                rg_state.expect_get_future_segments().returning(HashSet::new);
+            }
+            let reader = reader_group.create_reader(format!("{}-reader", name)).await;
+            let state = PipelineState::load(&name, &input, &factory)
+                .await
+                .expect("load pipeline state");
+            let mut pipeline = TransactionalPipeline {
+                name: name.clone(),
+                input: input.clone(),
+                reader_group,
+                reader,
+                meta: factory.create_stream_meta_client(input.clone()).await,
+                writer: factory
+                    .create_transactional_event_writer(output.clone(), WriterId(0))
+                    .await,
+                state,
+                txn: None,
+                position: HashMap::new(),
+                poisoned: false,
+            };
+            pipeline
+                .write_events(vec![(None, vec![1; 16])])
+                .await
+                .expect("write events");
+            let txn_id = pipeline.txn.as_ref().expect("get transaction").txn_id();
+
+            // another instance of the pipeline fences this one off, so the commit fails.
+            let mut other_state = PipelineState::load(&name, &input, &factory)
+                .await
+                .expect("load pipeline state");
+            let record = other_state.record.clone();
+            other_state.save(record).await.expect("save pipeline state");
+            let result = pipeline.commit(Timestamp(0)).await;
+            assert!(matches!(result, Err(PipelineError::Fenced { .. })));
+
+            // the pipeline refuses to go on with its position unknown.
+            let result = pipeline.commit(Timestamp(0)).await;
+            assert!(matches!(result, Err(PipelineError::Poisoned { .. })));
+            let result = pipeline.write_events(vec![(None, vec![1; 16])]).await;
+            assert!(matches!(result, Err(PipelineError::Poisoned { .. })));
+            let result = pipeline.read().await;
+            assert!(matches!(result, Err(PipelineError::Poisoned { .. })));
+            let state = PipelineState::load(&name, &input, &factory)
+                .await
+                .expect("load pipeline state");
+            assert_eq!(state.record, PipelineRecord::default());
+
+            // aborting the pipeline aborts the transaction that failed to commit.
+            pipeline.abort().await.expect("abort pipeline");
+            let status = factory
+                .controller_client()
+                .check_transaction_status(&output, txn_id)
+                .await
+                .expect("check transaction status");
+            assert_eq!(status, TransactionStatus::Aborted);
+        });
+    }
+}
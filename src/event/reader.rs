@@ -9,10 +9,17 @@
 //
 
 use crate::client_factory::ClientFactoryAsync;
+use crate::event::compression;
+use crate::event::headers::{self, EventHeaders};
+use crate::event::large_event::{self, NextEvent};
 use crate::event::reader_group_state::ReaderGroupStateError::SyncError;
 use crate::event::reader_group_state::{Offset, ReaderGroupStateError};
 use crate::segment::reader::ReaderError::SegmentSealed;
 use crate::segment::reader::{AsyncSegmentReader, ReaderError};
+use async_stream::try_stream;
+use derive_builder::*;
+use futures::stream::Stream;
+use getset::CopyGetters;
 use snafu::{ResultExt, Snafu};
 
 use pravega_client_retry::retry_result::Retryable;
@@ -23,6 +30,8 @@ use crate::sync::synchronizer::SynchronizerError;
 use bytes::{Buf, BufMut, BytesMut};
 use core::fmt;
 use im::HashMap as ImHashMap;
+use std::borrow::BorrowMut;
+use std::cmp;
 use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
@@ -34,13 +43,12 @@ use tokio::sync::{mpsc, Mutex};
 use tokio::time::timeout;
 use tracing::{debug, error, info, warn};
 
+// The minimum time between two attempts of the event stream to acquire a segment slice.
+const ACQUIRE_BACKOFF: Duration = Duration::from_millis(100);
+
 type ReaderErrorWithOffset = (ReaderError, i64);
 type SegmentReadResult = Result<SegmentDataBuffer, ReaderErrorWithOffset>;
 
-const REBALANCE_INTERVAL: Duration = Duration::from_secs(10);
-
-const READ_BUFFER_SIZE: i32 = 8 * 1024 * 1024; // max size for a single Event
-
 cfg_if::cfg_if! {
     if #[cfg(test)] {
         use crate::event::reader_group_state::MockReaderGroupState as ReaderGroupState;
@@ -104,11 +112,121 @@ cfg_if::cfg_if! {
 /// ```
 pub struct EventReader {
     pub id: Reader,
+    config: Arc<ReaderConfig>,
     factory: ClientFactoryAsync,
     rx: Receiver<SegmentReadResult>,
     tx: Sender<SegmentReadResult>,
     meta: ReaderState,
     rg_state: Arc<Mutex<ReaderGroupState>>,
+    // dropping the sender stops the background heartbeats of the reader.
+    stop_heartbeat: Option<oneshot::Sender<()>>,
+}
+
+/// The configuration of an [`EventReader`].
+///
+/// # Examples
+///
+/// ```no_run
+/// use pravega_client::event::reader::ReaderConfigBuilder;
+/// use std::time::Duration;
+///
+/// // a latency-sensitive reader which rebalances often and prefetches less data.
+/// let config = ReaderConfigBuilder::default()
+///     .rebalance_interval(Duration::from_secs(1))
+///     .read_buffer_size(64 * 1024)
+///     .build()
+///     .expect("creating reader config");
+/// ```
+#[derive(Builder, Debug, CopyGetters, Clone)]
+#[builder(setter(into), build_fn(validate = "Self::validate"))]
+pub struct ReaderConfig {
+    /// The interval at which the reader releases and acquires segments to balance them across
+    /// the readers of the ReaderGroup.
+    #[get_copy = "pub"]
+    #[builder(default = "Duration::from_secs(10)")]
+    pub rebalance_interval: Duration,
+
+    /// The number of bytes fetched from a segment by a single read, it also bounds the size of an event.
+    #[get_copy = "pub"]
+    #[builder(default = "8 * 1024 * 1024")]
+    pub read_buffer_size: i32,
+
+    /// The maximum number of segment slices handed out by the reader at the same time.
+    #[get_copy = "pub"]
+    #[builder(default = "usize::MAX")]
+    pub max_slices_in_flight: usize,
+
+    /// The time after which a reader which has not reported to the ReaderGroup is assumed dead.
+    /// The reader records a heartbeat in the ReaderGroup every third of this time from a background
+    /// task, until it is dropped or put offline.
+    #[get_copy = "pub"]
+    #[builder(default = "Duration::from_secs(30)")]
+    pub liveness_timeout: Duration,
+
+    /// The interval at which the reader publishes the offsets it has read up to in the ReaderGroup
+    /// while the application keeps acquiring segments. The StreamCuts generated by the ReaderGroup
+    /// are built from those offsets.
+    #[get_copy = "pub"]
+    #[builder(default = "Duration::from_secs(1)")]
+    pub position_update_interval: Duration,
+
+    /// Reassemble the events written in chunks by an [`EventWriter`] with large events enabled.
+    /// The chunks of a large event are buffered until all of them are read.
+    ///
+    /// [`EventWriter`]: crate::event::writer::EventWriter
+    #[get_copy = "pub"]
+    #[builder(default = "false")]
+    pub large_events: bool,
+
+    /// Decode the headers of the events written with headers by an [`EventWriter`]. Otherwise the
+    /// events are handed out as they were written. This should only be set if the writers of the
+    /// stream set headers, an event whose headers cannot be decoded is skipped.
+    ///
+    /// [`EventWriter`]: crate::event::writer::EventWriter
+    #[get_copy = "pub"]
+    #[builder(default = "false")]
+    pub event_headers: bool,
+
+    /// With [`ReaderConfig::event_headers`], hand out the events written without headers with their
+    /// whole data as payload and no headers. Otherwise such events are skipped, which suits streams
+    /// whose writers always set headers.
+    #[get_copy = "pub"]
+    #[builder(default = "true")]
+    pub legacy_events_as_payload: bool,
+
+    /// Decompress the events compressed by an [`EventWriter`] with compression enabled. Otherwise the
+    /// events are handed out as they were written, in their compression envelope.
+    ///
+    /// [`EventWriter`]: crate::event::writer::EventWriter
+    #[get_copy = "pub"]
+    #[builder(default = "false")]
+    pub decompress_events: bool,
+
+    /// The maximum size of a decompressed event. A compressed event which would exceed it is handed
+    /// out in its compression envelope.
+    #[get_copy = "pub"]
+    #[builder(default = "64 * 1024 * 1024")]
+    pub max_decompressed_event_size: usize,
+}
+
+impl ReaderConfigBuilder {
+    fn validate(&self) -> Result<(), String> {
+        if matches!(self.read_buffer_size, Some(size) if size <= 0) {
+            return Err("read buffer size should be positive".to_string());
+        }
+        if matches!(self.max_slices_in_flight, Some(0)) {
+            return Err("at least 1 segment slice should be allowed in flight".to_string());
+        }
+        Ok(())
+    }
+}
+
+impl Default for ReaderConfig {
+    fn default() -> Self {
+        ReaderConfigBuilder::default()
+            .build()
+            .expect("build default reader config")
+    }
 }
 
 #[derive(Debug, Snafu)]
@@ -148,8 +266,12 @@ impl EventReader {
         id: String,
         rg_state: Arc<Mutex<ReaderGroupState>>,
         factory: ClientFactoryAsync,
+        config: ReaderConfig,
     ) -> Self {
         let reader = Reader::from(id);
+        // read the generation first so that a concurrent reset is observed by the reader later.
+        let generation = rg_state.lock().await.get_generation().await;
+        let end_offsets = rg_state.lock().await.get_end_offsets().await;
         let new_segments_to_acquire = rg_state
             .lock()
             .await
@@ -193,6 +315,7 @@ impl EventReader {
                     scoped_segment: seg.to_string(),
                     start_offset: offset.read,
                     read_offset: offset.read,
+                    end_offset: end_offsets.get(&seg).copied().unwrap_or(i64::MAX),
                     ..Default::default()
                 },
             )
@@ -207,14 +330,25 @@ impl EventReader {
             factory.runtime_handle().spawn(SegmentSlice::get_segment_data(
                 segment.clone(),
                 meta.start_offset,
+                meta.end_offset,
+                config.read_buffer_size,
                 tx.clone(),
                 rx_stop,
                 factory.clone(),
             ));
         });
 
+        // record heartbeats in the background for as long as the reader is alive.
+        let (stop_heartbeat, rx_stop) = oneshot::channel();
+        factory.runtime_handle().spawn(EventReader::send_heartbeats(
+            rg_state.clone(),
+            reader.clone(),
+            config.liveness_timeout / 3,
+            rx_stop,
+        ));
+
         // initialize the event reader.
-        EventReader::init_event_reader(
+        let mut event_reader = EventReader::init_event_reader(
             rg_state,
             reader,
             factory,
@@ -222,7 +356,38 @@ impl EventReader {
             rx,
             slice_meta_map,
             stop_reading_map,
-        )
+        );
+        event_reader.meta.generation = generation;
+        event_reader.meta.end_offsets = end_offsets;
+        event_reader.config = Arc::new(config);
+        event_reader.stop_heartbeat = Some(stop_heartbeat);
+        event_reader
+    }
+
+    // Record a heartbeat of the reader in the ReaderGroup at the given interval until stopped.
+    async fn send_heartbeats(
+        rg_state: Arc<Mutex<ReaderGroupState>>,
+        reader: Reader,
+        interval: Duration,
+        mut stop: oneshot::Receiver<()>,
+    ) {
+        let mut heartbeats = tokio::time::interval(interval);
+        // the first tick completes immediately.
+        heartbeats.tick().await;
+        loop {
+            tokio::select! {
+                _ = &mut stop => break,
+                _ = heartbeats.tick() => {}
+            }
+            match rg_state.lock().await.reader_heartbeat(&reader).await {
+                Ok(()) => {}
+                Err(ReaderGroupStateError::ReaderAlreadyOfflineError { .. }) => {
+                    info!("reader {:?} is offline, stop recording heartbeats", reader);
+                    break;
+                }
+                Err(e) => warn!("failed to record the heartbeat of reader {:?}: {:?}", reader, e),
+            }
+        }
     }
 
     #[doc(hidden)]
@@ -237,6 +402,7 @@ impl EventReader {
     ) -> Self {
         EventReader {
             id,
+            config: Arc::default(),
             factory,
             rx,
             tx,
@@ -248,8 +414,12 @@ impl EventReader {
                 last_segment_release: Instant::now(),
                 last_segment_acquire: Instant::now(),
                 reader_offline: false,
+                generation: 0,
+                end_offsets: HashMap::new(),
+                last_position_update: Instant::now(),
             },
             rg_state,
+            stop_heartbeat: None,
         }
     }
 
@@ -261,12 +431,93 @@ impl EventReader {
         self.meta.last_segment_acquire = time;
     }
 
+    /// Read the events of the assigned segments one at a time as a [`Stream`].
+    ///
+    /// The segment slices are acquired and released internally. A slice is released before it is
+    /// fully read once the rebalance interval has elapsed, so that its segment can be moved to another
+    /// reader of the ReaderGroup. Checkpoint markers are not yielded; the checkpoint is still passed by
+    /// this reader. The stream ends once the ReaderGroup has no assigned, unassigned or future segments
+    /// left, i.e. when a bounded ReaderGroup reaches its end StreamCut or all of its streams are sealed
+    /// and read. It also ends after yielding an error, e.g. when the reader is offline.
+    ///
+    /// # Examples
+    /// ```ignore
+    /// use futures::StreamExt;
+    ///
+    /// let mut reader = rg.create_reader("reader".to_string()).await;
+    /// let events = reader.events();
+    /// futures::pin_mut!(events);
+    /// while let Some(event) = events.next().await {
+    ///     let event = event.expect("read event");
+    ///     println!("Event {:?} at offset {} of {:?}", event.value, event.offset_in_segment, event.segment);
+    /// }
+    /// ```
+    ///
+    /// [`Stream`]: futures::stream::Stream
+    pub fn events(&mut self) -> impl Stream<Item = Result<ReadEvent, EventReaderError>> + '_ {
+        EventReader::read_events(self)
+    }
+
+    /// Convert the reader into a [`Stream`] of events. See [`EventReader::events`] for details.
+    ///
+    /// [`Stream`]: futures::stream::Stream
+    pub fn into_stream(self) -> impl Stream<Item = Result<ReadEvent, EventReaderError>> {
+        EventReader::read_events(self)
+    }
+
+    fn read_events<'a, R: BorrowMut<EventReader> + 'a>(
+        mut reader: R,
+    ) -> impl Stream<Item = Result<ReadEvent, EventReaderError>> + 'a {
+        try_stream! {
+            let reader = reader.borrow_mut();
+            loop {
+                let started = Instant::now();
+                let mut slice = match reader.acquire_segment().await? {
+                    Some(slice) if slice.is_checkpoint() => continue,
+                    Some(slice) => slice,
+                    None => {
+                        if reader.is_read_completely().await {
+                            break;
+                        }
+                        // acquire_segment returns immediately while too many slices are in flight.
+                        if let Some(backoff) = ACQUIRE_BACKOFF.checked_sub(started.elapsed()) {
+                            tokio::time::sleep(backoff).await;
+                        }
+                        continue;
+                    }
+                };
+                let segment = ScopedSegment::from(slice.meta.scoped_segment.as_str());
+                while let Some(event) = slice.next() {
+                    yield ReadEvent::new(segment.clone(), event);
+                    if reader.meta.last_segment_release.elapsed() > reader.config.rebalance_interval {
+                        // give the segment back so that it can be rebalanced across readers.
+                        break;
+                    }
+                }
+                reader.release_segment(slice).await?;
+            }
+        }
+    }
+
+    // Returns true once there is nothing left to read for the reader, i.e. it holds no segment slices
+    // and the ReaderGroup has no assigned, unassigned or future segments.
+    async fn is_read_completely(&mut self) -> bool {
+        self.meta.reclaim_returned_slices();
+        self.meta.slices.is_empty()
+            && self.meta.slices_dished_out.is_empty()
+            && self.rg_state.lock().await.is_read_completely().await
+    }
+
     /// Release a partially read segment slice back to event reader.
     ///
     /// Note: it may return an error indicating that the reader has already been removed. This means
     /// that another thread removes this reader from the ReaderGroup probably due to the host of this reader
     /// is assumed dead.
     pub async fn release_segment(&mut self, mut slice: SegmentSlice) -> Result<(), EventReaderError> {
+        if slice.is_checkpoint() {
+            // nothing to release for a checkpoint marker.
+            return Ok(());
+        }
         info!(
             "releasing segment slice {} from reader {:?}",
             slice.meta.scoped_segment, self.id
@@ -284,9 +535,17 @@ impl EventReader {
         }
         //update meta data.
         let scoped_segment = ScopedSegment::from(slice.meta.scoped_segment.clone().as_str());
+        if !self.meta.slices_dished_out.contains_key(&scoped_segment) {
+            // the segment was dropped from the reader when the ReaderGroup was reset.
+            debug!(
+                "ignore the release of segment {:?} no longer owned",
+                scoped_segment
+            );
+            return Ok(());
+        }
         self.meta.add_slices(slice.meta.clone());
         self.meta.slices_dished_out.remove(&scoped_segment);
-        if self.meta.last_segment_release.elapsed() > REBALANCE_INTERVAL {
+        if self.meta.last_segment_release.elapsed() > self.config.rebalance_interval {
             debug!("try to rebalance segments across readers");
             let read_offset = slice.meta.read_offset;
             // Note: reader may not online
@@ -318,6 +577,10 @@ impl EventReader {
         slice: SegmentSlice,
         offset: i64,
     ) -> Result<(), EventReaderError> {
+        if slice.is_checkpoint() {
+            // nothing to release for a checkpoint marker.
+            return Ok(());
+        }
         info!(
             "releasing segment slice {} at offset {}",
             slice.meta.scoped_segment, offset
@@ -345,6 +608,11 @@ impl EventReader {
             });
         }
         let segment = ScopedSegment::from(slice.meta.scoped_segment.as_str());
+        if !self.meta.slices_dished_out.contains_key(&segment) {
+            // the segment was dropped from the reader when the ReaderGroup was reset.
+            debug!("ignore the release of segment {:?} no longer owned", segment);
+            return Ok(());
+        }
         if slice.meta.read_offset != offset {
             self.meta.stop_reading(&segment);
 
@@ -363,6 +631,8 @@ impl EventReader {
             tokio::spawn(SegmentSlice::get_segment_data(
                 segment.clone(),
                 slice_meta.read_offset, // start reading from the offset provided.
+                slice_meta.end_offset,
+                self.config.read_buffer_size,
                 self.tx.clone(),
                 rx_drop_fetch,
                 self.factory.clone(),
@@ -385,22 +655,16 @@ impl EventReader {
     pub async fn reader_offline(&mut self) -> Result<(), EventReaderError> {
         if !self.meta.reader_offline && self.rg_state.lock().await.check_online(&self.id).await {
             info!("Putting reader {:?} offline", self.id);
+            // stop recording heartbeats.
+            self.stop_heartbeat.take();
             // stop reading from all the segments.
             self.meta.stop_reading_all();
             // close all slice return Receivers.
             self.meta.close_all_slice_return_channel();
             // use the updated map to return the data.
 
-            let mut offset_map: HashMap<ScopedSegment, Offset> = HashMap::new();
-            for (seg, slice_meta) in self.meta.slices_dished_out.drain() {
-                offset_map.insert(seg, Offset::new(slice_meta.read_offset));
-            }
-            for meta in self.meta.slices.values() {
-                offset_map.insert(
-                    ScopedSegment::from(meta.scoped_segment.as_str()),
-                    Offset::new(meta.read_offset),
-                );
-            }
+            let offset_map = self.meta.positions();
+            self.meta.slices_dished_out.clear();
 
             match self
                 .rg_state
@@ -505,9 +769,57 @@ impl EventReader {
                 },
             });
         }
+        // Publish the offsets read so far so that the StreamCuts of the ReaderGroup stay recent.
+        if self.meta.last_position_update.elapsed() > self.config.position_update_interval {
+            self.meta.reclaim_returned_slices();
+            let positions = self.meta.positions();
+            self.rg_state
+                .lock()
+                .await
+                .update_reader_positions(&self.id, positions)
+                .await
+                .context(StateError {})?;
+            self.meta.last_position_update = Instant::now();
+        }
+        // Check if the ReaderGroup has been reset since the last acquire.
+        let generation = self.rg_state.lock().await.get_generation().await;
+        if generation != self.meta.generation {
+            self.reset_segments(generation).await.context(StateError {})?;
+        }
+        // Check if there is a pending checkpoint that this reader needs to pass.
+        let pending_checkpoint = self
+            .rg_state
+            .lock()
+            .await
+            .get_checkpoint_for_reader(&self.id)
+            .await;
+        if let Some(checkpoint) = pending_checkpoint {
+            // The positions are only known once all the slices handed out have been returned.
+            self.meta.reclaim_returned_slices();
+            if self.meta.slices_dished_out.is_empty() {
+                let positions: HashMap<ScopedSegment, Offset> = self
+                    .meta
+                    .slices
+                    .iter()
+                    .map(|(segment, meta)| (segment.clone(), Offset::new(meta.read_offset)))
+                    .collect();
+                self.rg_state
+                    .lock()
+                    .await
+                    .reader_checkpointed(&self.id, &checkpoint, positions)
+                    .await
+                    .context(StateError {})?;
+                info!("reader {} passed checkpoint {}", self.id, checkpoint);
+                return Ok(Some(SegmentSlice::checkpoint(checkpoint)));
+            }
+            debug!(
+                "reader {} defers checkpoint {} until all the segment slices are returned",
+                self.id, checkpoint
+            );
+        }
         info!("acquiring segment for reader {:?}", self.id);
         // Check if newer segments should be acquired.
-        if self.meta.last_segment_acquire.elapsed() > REBALANCE_INTERVAL {
+        if self.meta.last_segment_acquire.elapsed() > self.config.rebalance_interval {
             info!("need to rebalance segments across readers");
             // assign newer segments to this reader if available.
             // Note: reader may not online.
@@ -536,6 +848,18 @@ impl EventReader {
                 self.meta.last_segment_acquire = Instant::now();
             }
         }
+        // Check if the maximum number of slices are already handed out.
+        if self.meta.slices_dished_out.len() >= self.config.max_slices_in_flight {
+            self.meta.reclaim_returned_slices();
+            if self.meta.slices_dished_out.len() >= self.config.max_slices_in_flight {
+                debug!(
+                    "reader {} already has {} segment slices in flight",
+                    self.id,
+                    self.meta.slices_dished_out.len()
+                );
+                return Ok(None);
+            }
+        }
         // Check if any of the segments already has event data and return it.
         if let Some(segment_with_data) = self.meta.get_segment_id_with_data() {
             info!("segment {} has data ready to read", segment_with_data);
@@ -555,6 +879,8 @@ impl EventReader {
             Ok(Some(SegmentSlice {
                 meta: slice_meta,
                 slice_return_tx: Some(slice_return_tx),
+                checkpoint: None,
+                config: self.config.clone(),
             }))
         } else if let Ok(option) = timeout(Duration::from_millis(1000), self.rx.recv()).await {
             if let Some(read_result) = option {
@@ -589,6 +915,8 @@ impl EventReader {
                                 Ok(Some(SegmentSlice {
                                     meta: slice_meta,
                                     slice_return_tx: Some(slice_return_tx),
+                                    checkpoint: None,
+                                    config: self.config.clone(),
                                 }))
                             }
                         } else {
@@ -609,6 +937,9 @@ impl EventReader {
                                 info!("Error at an invalid offset {:?} observed. Expected offset {:?}. Ignoring this data", offset, slice_meta.start_offset);
                                 self.meta.add_slices(slice_meta);
                                 self.meta.slices_dished_out.remove(&segment);
+                            } else if slice_meta.read_offset >= slice_meta.end_offset {
+                                info!("Segment slice {:?} has reached its end offset", slice_meta);
+                                self.segment_reached_end(segment).await.context(StateError {})?;
                             } else {
                                 info!("Segment slice {:?} has received error {:?}", slice_meta, e);
                                 self.fetch_successors(e).await.context(StateError {})?;
@@ -632,6 +963,38 @@ impl EventReader {
         }
     }
 
+    // Drop all the segments read by this reader after the ReaderGroup is reset and
+    // acquire the segments of the new config.
+    async fn reset_segments(&mut self, generation: u64) -> Result<(), ReaderGroupStateError> {
+        info!(
+            "reader {} observed reset of the ReaderGroup, dropping its segments",
+            self.id
+        );
+        self.meta.stop_reading_all();
+        self.meta.close_all_slice_return_channel();
+        self.meta.slices.clear();
+        self.meta.slices_dished_out.clear();
+        self.meta.generation = generation;
+        self.meta.end_offsets = self.rg_state.lock().await.get_end_offsets().await;
+        self.read_new_segments().await?;
+        self.meta.last_segment_acquire = Instant::now();
+        Ok(())
+    }
+
+    // Mark the segment which has been read up to its end offset as completed. None of its successors
+    // are assigned since they are past the end StreamCut.
+    async fn segment_reached_end(&mut self, segment: ScopedSegment) -> Result<(), ReaderGroupStateError> {
+        self.meta.stop_reading(&segment);
+        self.meta.slices_dished_out.remove(&segment);
+        info!("Segment {:?} has been read up to its end offset", segment);
+        self.rg_state
+            .lock()
+            .await
+            .segment_completed(&self.id, &segment, &ImHashMap::new())
+            .await?;
+        self.read_new_segments().await
+    }
+
     // Fetch successors of the segment where an error was observed.
     // ensure we stop the read task and spawn read tasks for the successor segments.
     async fn fetch_successors(&mut self, e: ReaderError) -> Result<(), ReaderGroupStateError> {
@@ -659,6 +1022,7 @@ impl EventReader {
                     .await
                     .expect("Failed to fetch successors of the segment")
                     .segment_with_predecessors;
+                let successors = EventReader::successors_before_end(successors, &self.meta.end_offsets);
                 info!("Segment Completed {:?}", segment);
                 // Update rg_state with the completed segment and its successors.
                 self.rg_state
@@ -666,34 +1030,39 @@ impl EventReader {
                     .await
                     .segment_completed(&self.id, &completed_scoped_segment, &successors)
                     .await?;
-                // Assign newer segments to this reader if available.
-                let option = self.assign_segments_to_reader().await?;
-                if let Some(new_segments) = option {
-                    // fetch current segments.
-                    let current_segments = self
-                        .rg_state
-                        .lock()
-                        .await
-                        .get_segments_for_reader(&self.id)
-                        .await
-                        .map_err(|e| SyncError {
-                            error_msg: format!("Failed to fetch segments for reader {:?}", self.id),
-                            source: e,
-                        })?;
-                    let new_segments: HashSet<(ScopedSegment, Offset)> = current_segments
-                        .into_iter()
-                        .filter(|(seg, _off)| new_segments.contains(seg))
-                        .collect();
-                    debug!("Segments which can be read next are {:?}", new_segments);
-                    // Initiate segment reads to the newer segments.
-                    self.initiate_segment_reads(new_segments);
-                }
+                self.read_new_segments().await?;
             }
             _ => error!("Error observed while reading from Pravega {:?}", e),
         };
         Ok(())
     }
 
+    // Assign newer segments to this reader if available and initiate reads to them.
+    async fn read_new_segments(&mut self) -> Result<(), ReaderGroupStateError> {
+        let option = self.assign_segments_to_reader().await?;
+        if let Some(new_segments) = option {
+            // fetch current segments.
+            let current_segments = self
+                .rg_state
+                .lock()
+                .await
+                .get_segments_for_reader(&self.id)
+                .await
+                .map_err(|e| SyncError {
+                    error_msg: format!("Failed to fetch segments for reader {:?}", self.id),
+                    source: e,
+                })?;
+            let new_segments: HashSet<(ScopedSegment, Offset)> = current_segments
+                .into_iter()
+                .filter(|(seg, _off)| new_segments.contains(seg))
+                .collect();
+            debug!("Segments which can be read next are {:?}", new_segments);
+            // Initiate segment reads to the newer segments.
+            self.initiate_segment_reads(new_segments);
+        }
+        Ok(())
+    }
+
     // This function tries to acquire newer segments for the reader.
     async fn assign_segments_to_reader(&self) -> Result<Option<Vec<ScopedSegment>>, ReaderGroupStateError> {
         let mut new_segments: Vec<ScopedSegment> = Vec::new();
@@ -734,12 +1103,15 @@ impl EventReader {
                 scoped_segment: seg.to_string(),
                 start_offset: offset.read,
                 read_offset: offset.read, // read offset should be same as start_offset.
+                end_offset: self.meta.end_offsets.get(&seg).copied().unwrap_or(i64::MAX),
                 ..Default::default()
             };
             let (tx_drop_fetch, rx_drop_fetch) = oneshot::channel();
             tokio::spawn(SegmentSlice::get_segment_data(
                 seg.clone(),
                 meta.start_offset,
+                meta.end_offset,
+                self.config.read_buffer_size,
                 self.tx.clone(),
                 rx_drop_fetch,
                 self.factory.clone(),
@@ -760,6 +1132,26 @@ impl EventReader {
         }
     }
 
+    // Drop the successors of the segments listed in the end StreamCuts, they are past the end
+    // StreamCuts even if one of their other predecessors is not bounded by an end StreamCut.
+    fn successors_before_end(
+        successors: ImHashMap<SegmentWithRange, Vec<Segment>>,
+        end_offsets: &HashMap<ScopedSegment, i64>,
+    ) -> ImHashMap<SegmentWithRange, Vec<Segment>> {
+        successors
+            .into_iter()
+            .filter(|(successor, predecessors)| {
+                !predecessors.iter().any(|predecessor| {
+                    end_offsets.contains_key(&ScopedSegment {
+                        scope: successor.scoped_segment.scope.clone(),
+                        stream: successor.scoped_segment.stream.clone(),
+                        segment: predecessor.clone(),
+                    })
+                })
+            })
+            .collect()
+    }
+
     // Fetch the successors for a given segment from the controller.
     async fn get_successors(
         &mut self,
@@ -784,6 +1176,9 @@ struct ReaderState {
     last_segment_release: Instant,
     last_segment_acquire: Instant,
     reader_offline: bool,
+    generation: u64,
+    end_offsets: HashMap<ScopedSegment, i64>,
+    last_position_update: Instant,
 }
 
 impl ReaderState {
@@ -822,6 +1217,38 @@ impl ReaderState {
         }
     }
 
+    // Take back the segment slices that have been returned by the application without
+    // waiting for new data of those segments.
+    fn reclaim_returned_slices(&mut self) {
+        let dished_out: Vec<ScopedSegment> = self.slices_dished_out.keys().cloned().collect();
+        for segment in dished_out {
+            let returned = match self.slice_release_receiver.get_mut(&segment) {
+                Some(receiver) => match receiver.try_recv() {
+                    Ok(returned_meta) => returned_meta,
+                    Err(TryRecvError::Empty) => continue,
+                    Err(TryRecvError::Closed) => self.slices_dished_out.get(&segment).cloned(),
+                },
+                None => continue,
+            };
+            self.slice_release_receiver.remove(&segment);
+            self.slices_dished_out.remove(&segment);
+            // None is returned if the segment has been released from the reader.
+            if let Some(meta) = returned {
+                self.add_slices(meta);
+            }
+        }
+    }
+
+    // The offsets of the owned segments up to which the events have been handed out. A slice that
+    // is out for consumption counts from the offset it was handed out at.
+    fn positions(&self) -> HashMap<ScopedSegment, Offset> {
+        self.slices
+            .iter()
+            .chain(self.slices_dished_out.iter())
+            .map(|(segment, meta)| (segment.clone(), Offset::new(meta.read_offset)))
+            .collect()
+    }
+
     fn close_all_slice_return_channel(&mut self) {
         for (_, mut rx) in self.slice_release_receiver.drain() {
             rx.close();
@@ -899,19 +1326,60 @@ impl ReaderState {
 pub struct Event {
     pub offset_in_segment: i64,
     pub value: Vec<u8>,
+    headers: EventHeaders,
+}
+
+impl Event {
+    /// The headers the event was written with, empty if it was written without headers or if
+    /// [`ReaderConfig::event_headers`] is not set.
+    pub fn headers(&self) -> &EventHeaders {
+        &self.headers
+    }
+}
+
+/// This represents an event yielded by [`EventReader::events`] along with the segment and the
+/// offset at which the event was read from.
+#[derive(Debug)]
+pub struct ReadEvent {
+    pub segment: ScopedSegment,
+    pub offset_in_segment: i64,
+    pub value: Vec<u8>,
+    pub headers: EventHeaders,
+}
+
+impl ReadEvent {
+    pub(crate) fn new(segment: ScopedSegment, event: Event) -> Self {
+        ReadEvent {
+            segment,
+            offset_in_segment: event.offset_in_segment,
+            value: event.value,
+            headers: event.headers,
+        }
+    }
 }
 
 /// This represents a segment slice which can be used to read events from a Pravega segment as an
 /// iterator.
+///
+/// Events compressed by an [`EventWriter`] are detected and decompressed while iterating if
+/// [`ReaderConfig::decompress_events`] is set.
+///
+/// A SegmentSlice may also be a checkpoint marker, which indicates that the reader has passed the
+/// checkpoint initiated by [`ReaderGroup::initiate_checkpoint`]. A checkpoint marker contains no events.
+///
+/// [`ReaderGroup::initiate_checkpoint`]: crate::event::reader_group::ReaderGroup::initiate_checkpoint
+/// [`EventWriter`]: crate::event::writer::EventWriter
 #[derive(Default)]
 pub struct SegmentSlice {
     pub meta: SliceMetadata,
     pub(crate) slice_return_tx: Option<oneshot::Sender<Option<SliceMetadata>>>,
+    pub(crate) checkpoint: Option<String>,
+    pub(crate) config: Arc<ReaderConfig>,
 }
 
 impl SegmentSlice {
     /// Create a new SegmentSlice for a given start_offset, segment.
-    /// This spawns an asynchronous task to fetch data from the segment with length of  `READ_BUFFER_SIZE`.
+    /// This spawns an asynchronous task to fetch data from the segment with length of the read buffer size.
     /// The channel buffer size is 1 which ensure only one outstanding read request to Segment store.
     fn new(
         segment: ScopedSegment,
@@ -929,13 +1397,37 @@ impl SegmentSlice {
                 partial_data_present: false,
             },
             slice_return_tx: Some(slice_return_tx),
+            checkpoint: None,
+            config: Arc::default(),
+        }
+    }
+
+    /// Create a checkpoint marker for the given checkpoint.
+    fn checkpoint(name: String) -> Self {
+        SegmentSlice {
+            meta: SliceMetadata::default(),
+            slice_return_tx: None,
+            checkpoint: Some(name),
+            config: Arc::default(),
         }
     }
 
-    // Method to fetch data from the Segment store from a given start offset.
+    /// Returns true if this slice is a checkpoint marker rather than segment data.
+    pub fn is_checkpoint(&self) -> bool {
+        self.checkpoint.is_some()
+    }
+
+    /// Returns the name of the checkpoint if this slice is a checkpoint marker.
+    pub fn get_checkpoint_name(&self) -> Option<&str> {
+        self.checkpoint.as_deref()
+    }
+
+    // Method to fetch data from the Segment store from a given start offset up to the end offset.
     async fn get_segment_data(
         segment: ScopedSegment,
         start_offset: i64,
+        end_offset: i64,
+        read_buffer_size: i32,
         tx: Sender<SegmentReadResult>,
         mut drop_fetch: oneshot::Receiver<()>,
         factory: ClientFactoryAsync,
@@ -947,11 +1439,25 @@ impl SegmentSlice {
                 info!("Stop reading from the segment");
                 break;
             }
+            if offset >= end_offset {
+                info!("Reached end offset {} of segment {:?}", end_offset, segment);
+                let data = SegmentSealed {
+                    segment: segment.to_string(),
+                    can_retry: false,
+                    operation: "read segment".to_string(),
+                    error_msg: "reached the end offset".to_string(),
+                };
+                if let Err(e) = tx.send(Err((data, offset))).await {
+                    warn!("Error while sending segment data to event parser {:?} ", e);
+                }
+                break;
+            }
+            let length = cmp::min(read_buffer_size as i64, end_offset - offset) as i32;
             debug!(
                 "Send read request to Segment store at offset {:?} with length {:?}",
-                offset, READ_BUFFER_SIZE
+                offset, length
             );
-            let read = segment_reader.read(offset, READ_BUFFER_SIZE).await;
+            let read = segment_reader.read(offset, length).await;
             match read {
                 Ok(reply) => {
                     let len = reply.data.len();
@@ -1031,6 +1537,7 @@ impl SegmentSlice {
                 let event = Event {
                     offset_in_segment: event_data.offset_in_segment,
                     value: event_data.value.freeze().to_vec(),
+                    headers: EventHeaders::new(),
                 };
                 Some(event)
             } else {
@@ -1049,6 +1556,77 @@ impl SegmentSlice {
         }
     }
 
+    // Extract the next event, reassembling the chunks of a large event.
+    // Return None if the event or any of its chunks is partially read.
+    fn extract_large_event(&mut self) -> Option<Event> {
+        loop {
+            match large_event::next_event(self.meta.segment_data.value.bytes()) {
+                NextEvent::Regular => return self.extract_event(SegmentSlice::read_header),
+                NextEvent::Incomplete => {
+                    debug!("partial large event read, waiting for the rest of its chunks");
+                    self.meta.partial_data_present = true;
+                    return None;
+                }
+                NextEvent::Orphan => {
+                    let chunk = self.extract_event(SegmentSlice::read_header)?;
+                    warn!(
+                        "skip chunk at offset {} of segment {} which is not part of a complete large event",
+                        chunk.offset_in_segment, self.meta.scoped_segment
+                    );
+                }
+                NextEvent::Chunked { chunks } => {
+                    let mut value = vec![];
+                    let mut offset_in_segment = None;
+                    for _ in 0..chunks {
+                        let chunk = self
+                            .extract_event(SegmentSlice::read_header)
+                            .expect("all the chunks are read");
+                        offset_in_segment.get_or_insert(chunk.offset_in_segment);
+                        value.extend_from_slice(large_event::chunk_data(&chunk.value));
+                    }
+                    info!("reassembled large event from {} chunks", chunks);
+                    return Some(Event {
+                        offset_in_segment: offset_in_segment.expect("at least one chunk"),
+                        value,
+                        headers: EventHeaders::new(),
+                    });
+                }
+            }
+        }
+    }
+
+    // Split the headers from the payload of the event.
+    // Return None if the event has no headers and the reader does not accept such events.
+    fn decode_headers(&self, mut event: Event) -> Option<Event> {
+        if !self.config.event_headers {
+            return Some(event);
+        }
+        if !headers::has_headers(&event.value) {
+            if self.config.legacy_events_as_payload {
+                return Some(event);
+            }
+            warn!(
+                "skip event at offset {} of segment {} which has no headers",
+                event.offset_in_segment, self.meta.scoped_segment
+            );
+            return None;
+        }
+        match headers::decode(&event.value) {
+            Ok((headers, payload)) => {
+                event.headers = headers;
+                event.value = payload;
+                Some(event)
+            }
+            Err(e) => {
+                error!(
+                    "skip event at offset {} of segment {} whose headers cannot be decoded: {}",
+                    event.offset_in_segment, self.meta.scoped_segment, e
+                );
+                None
+            }
+        }
+    }
+
     // This method reads the header and returns a BytesMut whose size is as big as the event.
     // If complete header is not present return None.
     fn read_header(data: &mut SegmentDataBuffer) -> Option<SegmentDataBuffer> {
@@ -1079,32 +1657,42 @@ impl Iterator for SegmentSlice {
     type Item = Event;
 
     fn next(&mut self) -> Option<Self::Item> {
-        // extract event from already fetched data.
-        let res = self.extract_event(SegmentSlice::read_header);
-
-        match res {
-            Some(event) => {
-                self.meta.last_event_offset = event.offset_in_segment;
-                self.meta.read_offset =
-                    event.offset_in_segment + event.value.len() as i64 + TYPE_PLUS_LENGTH_SIZE as i64;
-                if !self.meta.is_empty() {
-                    assert_eq!(
-                        self.meta.read_offset, self.meta.segment_data.offset_in_segment,
-                        "Error in offset computation"
-                    );
+        loop {
+            // extract event from already fetched data.
+            let res = if self.config.large_events {
+                self.extract_large_event()
+            } else {
+                self.extract_event(SegmentSlice::read_header)
+            };
+
+            match res {
+                Some(mut event) => {
+                    self.meta.last_event_offset = event.offset_in_segment;
+                    // the data of the event, including the chunks of a large event, has been consumed.
+                    self.meta.read_offset = self.meta.segment_data.offset_in_segment;
+                    if self.config.decompress_events {
+                        event.value = compression::decompress_or_raw(
+                            event.value,
+                            self.config.max_decompressed_event_size,
+                            &self.meta.scoped_segment,
+                            event.offset_in_segment,
+                        );
+                    }
+                    if let Some(event) = self.decode_headers(event) {
+                        return Some(event);
+                    }
                 }
-                Some(event)
-            }
-            None => {
-                if self.meta.is_empty() {
-                    info!(
-                        "Finished reading events from the segment slice of {:?}",
-                        self.meta.scoped_segment
-                    );
-                } else {
-                    info!("Partial event present in the segment slice of {:?}, this will be returned post a new read request", self.meta.scoped_segment);
+                None => {
+                    if self.meta.is_empty() {
+                        info!(
+                            "Finished reading events from the segment slice of {:?}",
+                            self.meta.scoped_segment
+                        );
+                    } else {
+                        info!("Partial event present in the segment slice of {:?}, this will be returned post a new read request", self.meta.scoped_segment);
+                    }
+                    return None;
                 }
-                None
             }
         }
     }
@@ -1148,6 +1736,16 @@ impl SliceMetadata {
         !self.partial_data_present && self.segment_data.value.len() > TYPE_PLUS_LENGTH_SIZE as usize
     }
 
+    // for testing purposes.
+    #[cfg(test)]
+    pub(crate) fn set_segment_data(&mut self, offset_in_segment: i64, value: BytesMut) {
+        self.segment_data = SegmentDataBuffer {
+            segment: self.scoped_segment.clone(),
+            offset_in_segment,
+            value,
+        };
+    }
+
     fn copy_meta(&self) -> SliceMetadata {
         SliceMetadata {
             start_offset: self.start_offset,
@@ -1277,12 +1875,15 @@ impl SegmentDataBuffer {
 mod tests {
     use super::*;
     use crate::client_factory::ClientFactory;
+    use crate::event::compression::Compression;
     use crate::event::reader_group_state::ReaderGroupStateError;
     use crate::sync::synchronizer::SynchronizerError;
 
     use bytes::{Buf, BufMut, BytesMut};
+    use futures::StreamExt;
     use mockall::predicate;
     use mockall::predicate::*;
+    use ordered_float::OrderedFloat;
     use pravega_client_config::{ClientConfigBuilder, MOCK_CONTROLLER_URI};
     use pravega_client_shared::{Reader, Scope, ScopedSegment, ScopedStream, Stream};
     use pravega_wire_protocol::commands::{Command, EventCommand};
@@ -1323,6 +1924,9 @@ mod tests {
         let init_segments = vec![create_segment_slice(0), create_segment_slice(1)];
         let mut rg_mock: ReaderGroupState = ReaderGroupState::default();
         rg_mock.expect_check_online().return_const(true);
+        rg_mock.expect_update_reader_positions().returning(|_, _| Ok(()));
+        rg_mock.expect_get_generation().return_const(0u64);
+        rg_mock.expect_get_checkpoint_for_reader().return_const(None);
         rg_mock
             .expect_compute_segments_to_acquire_or_release()
             .return_once(move |_| Ok(0 as isize));
@@ -1365,11 +1969,11 @@ mod tests {
         }
     }
 
+    // This test verifies the events of the segments are yielded one at a time by the event stream.
     #[test]
-    fn test_acquire_segments() {
-        const NUM_EVENTS: usize = 10;
+    fn test_read_events_as_stream() {
+        const NUM_EVENTS: usize = 50;
         let (tx, rx) = mpsc::channel(1);
-        tracing_subscriber::fmt().with_max_level(Level::TRACE).finish();
         let cf = ClientFactory::new(
             ClientConfigBuilder::default()
                 .controller_uri(MOCK_CONTROLLER_URI)
@@ -1381,51 +1985,24 @@ mod tests {
         let _guard = cf.runtime().enter();
         tokio::spawn(generate_variable_size_events(
             tx.clone(),
-            1024,
+            10,
             NUM_EVENTS,
             0,
             false,
         ));
 
-        // simulate initialization of a Reader
-        let init_segments = vec![create_segment_slice(0)];
+        let init_segments = vec![create_segment_slice(0), create_segment_slice(1)];
         let mut rg_mock: ReaderGroupState = ReaderGroupState::default();
+        rg_mock.expect_check_online().return_const(true);
+        rg_mock.expect_update_reader_positions().returning(|_, _| Ok(()));
+        rg_mock.expect_is_read_completely().return_const(false);
+        rg_mock.expect_get_generation().return_const(0u64);
+        rg_mock.expect_get_checkpoint_for_reader().return_const(None);
         rg_mock
             .expect_compute_segments_to_acquire_or_release()
-            .with(predicate::eq(Reader::from("r1".to_string())))
-            .return_once(move |_| Ok(1 as isize));
+            .returning(move |_| Ok(0 as isize));
         rg_mock.expect_remove_reader().return_once(move |_, _| Ok(()));
-        rg_mock.expect_check_online().return_const(true);
-
-        // mock rg_state.assign_segment_to_reader
-        let res: Result<Option<ScopedSegment>, ReaderGroupStateError> =
-            Ok(Some(ScopedSegment::from("scope/test/1.#epoch.0")));
-        rg_mock
-            .expect_assign_segment_to_reader()
-            .with(predicate::eq(Reader::from("r1".to_string())))
-            .return_once(move |_| res);
-        //mock rg_state get_segments for reader
-        let mut new_current_segments: HashSet<(ScopedSegment, Offset)> = HashSet::new();
-        new_current_segments.insert((ScopedSegment::from("scope/test/1.#epoch.0"), Offset::new(0)));
-        new_current_segments.insert((ScopedSegment::from("scope/test/0.#epoch.0"), Offset::new(0)));
-        let res: Result<HashSet<(ScopedSegment, Offset)>, SynchronizerError> = Ok(new_current_segments);
-        rg_mock
-            .expect_get_segments_for_reader()
-            .with(predicate::eq(Reader::from("r1".to_string())))
-            .return_once(move |_| res);
-
-        // simulate data being received from Segment store.
-        tokio::spawn(generate_variable_size_events(
-            tx.clone(),
-            1024,
-            NUM_EVENTS,
-            1,
-            false,
-        ));
-
-        let before_time = Instant::now() - Duration::from_secs(15);
-        // create a new Event Reader with the segment slice data.
-        let mut reader = EventReader::init_event_reader(
+        let reader = EventReader::init_event_reader(
             Arc::new(Mutex::new(rg_mock)),
             Reader::from("r1".to_string()),
             cf.to_async(),
@@ -1434,17 +2011,411 @@ mod tests {
             create_slice_map(init_segments),
             HashMap::new(),
         );
-        reader.set_last_acquire_release_time(before_time);
 
-        let mut event_count = 0;
+        let events = reader.into_stream();
+        futures::pin_mut!(events);
+        let mut last_offset = -1;
+        for event_size in 1..NUM_EVENTS + 1 {
+            let event = cf
+                .runtime()
+                .block_on(events.next())
+                .expect("stream should not end")
+                .expect("read event");
+            assert_eq!(event.segment, ScopedSegment::from("scope/test/0.#epoch.0"));
+            assert!(event.offset_in_segment > last_offset);
+            assert_eq!(event.value.len(), event_size, "Event has been missed");
+            assert!(is_all_same(event.value.as_slice()), "Event has been corrupted");
+            last_offset = event.offset_in_segment;
+        }
+    }
 
-        // Attempt to acquire a segment.
-        while let Some(mut slice) = cf.runtime().block_on(reader.acquire_segment()).unwrap() {
-            loop {
-                if let Some(event) = slice.next() {
-                    println!("Read event {:?}", event);
-                    assert!(is_all_same(event.value.as_slice()), "Event has been corrupted");
-                    event_count += 1;
+    // This test verifies the event stream ends once the ReaderGroup has no segments left to read.
+    #[test]
+    fn test_read_events_stream_ends() {
+        let (tx, rx) = mpsc::channel(1);
+        let cf = ClientFactory::new(
+            ClientConfigBuilder::default()
+                .controller_uri(MOCK_CONTROLLER_URI)
+                .build()
+                .unwrap(),
+        );
+        let _guard = cf.runtime().enter();
+
+        let mut rg_mock: ReaderGroupState = ReaderGroupState::default();
+        rg_mock.expect_check_online().return_const(true);
+        rg_mock.expect_update_reader_positions().returning(|_, _| Ok(()));
+        rg_mock.expect_is_read_completely().times(1).return_const(true);
+        rg_mock.expect_get_generation().return_const(0u64);
+        rg_mock.expect_get_checkpoint_for_reader().return_const(None);
+        rg_mock.expect_remove_reader().return_once(move |_, _| Ok(()));
+        let reader = EventReader::init_event_reader(
+            Arc::new(Mutex::new(rg_mock)),
+            Reader::from("r1".to_string()),
+            cf.to_async(),
+            tx,
+            rx,
+            HashMap::new(),
+            HashMap::new(),
+        );
+
+        let events = reader.into_stream();
+        futures::pin_mut!(events);
+        assert!(cf.runtime().block_on(events.next()).is_none());
+    }
+
+    // This test verifies an EventReader emits a checkpoint marker before returning more data.
+    #[test]
+    fn test_read_events_with_checkpoint() {
+        const NUM_EVENTS: usize = 10;
+        let (tx, rx) = mpsc::channel(1);
+        let cf = ClientFactory::new(
+            ClientConfigBuilder::default()
+                .controller_uri(MOCK_CONTROLLER_URI)
+                .build()
+                .unwrap(),
+        );
+
+        // simulate data being received from Segment store.
+        let _guard = cf.runtime().enter();
+        tokio::spawn(generate_variable_size_events(
+            tx.clone(),
+            10,
+            NUM_EVENTS,
+            0,
+            false,
+        ));
+
+        let init_segments = vec![create_segment_slice(0), create_segment_slice(1)];
+        let mut rg_mock: ReaderGroupState = ReaderGroupState::default();
+        rg_mock.expect_check_online().return_const(true);
+        rg_mock.expect_update_reader_positions().returning(|_, _| Ok(()));
+        rg_mock.expect_get_generation().return_const(0u64);
+        let mut checkpoint_pending = true;
+        rg_mock.expect_get_checkpoint_for_reader().returning(move |_| {
+            if checkpoint_pending {
+                checkpoint_pending = false;
+                Some("cp".to_string())
+            } else {
+                None
+            }
+        });
+        let mut expected_positions = HashMap::new();
+        expected_positions.insert(ScopedSegment::from("scope/test/0.#epoch.0"), Offset::new(0));
+        expected_positions.insert(ScopedSegment::from("scope/test/1.#epoch.0"), Offset::new(0));
+        rg_mock
+            .expect_reader_checkpointed()
+            .with(
+                predicate::eq(Reader::from("r1".to_string())),
+                predicate::eq("cp"),
+                predicate::eq(expected_positions),
+            )
+            .times(1)
+            .returning(|_, _, _| Ok(()));
+        rg_mock
+            .expect_compute_segments_to_acquire_or_release()
+            .return_once(move |_| Ok(0 as isize));
+        rg_mock.expect_remove_reader().return_once(move |_, _| Ok(()));
+        let mut reader = EventReader::init_event_reader(
+            Arc::new(Mutex::new(rg_mock)),
+            Reader::from("r1".to_string()),
+            cf.to_async(),
+            tx.clone(),
+            rx,
+            create_slice_map(init_segments),
+            HashMap::new(),
+        );
+
+        // the first slice is the checkpoint marker.
+        let slice = cf
+            .runtime()
+            .block_on(reader.acquire_segment())
+            .unwrap()
+            .expect("checkpoint marker");
+        assert!(slice.is_checkpoint());
+        assert_eq!(slice.get_checkpoint_name(), Some("cp"));
+        cf.runtime()
+            .block_on(reader.release_segment(slice))
+            .expect("release checkpoint marker");
+
+        // events are read as usual after the checkpoint.
+        let mut event_count = 0;
+        while let Some(slice) = cf.runtime().block_on(reader.acquire_segment()).unwrap() {
+            assert!(!slice.is_checkpoint());
+            event_count += slice.count();
+            if event_count == NUM_EVENTS {
+                break;
+            }
+        }
+        assert_eq!(event_count, NUM_EVENTS);
+    }
+
+    // This test verifies an EventReader drops its segments once the ReaderGroup is reset.
+    #[test]
+    fn test_reader_group_reset() {
+        const NUM_EVENTS: usize = 10;
+        let (tx, rx) = mpsc::channel(1);
+        let cf = ClientFactory::new(
+            ClientConfigBuilder::default()
+                .controller_uri(MOCK_CONTROLLER_URI)
+                .build()
+                .unwrap(),
+        );
+
+        // simulate data being received from Segment store.
+        let _guard = cf.runtime().enter();
+        tokio::spawn(generate_variable_size_events(
+            tx.clone(),
+            100,
+            NUM_EVENTS,
+            0,
+            false,
+        ));
+
+        let init_segments = vec![create_segment_slice(0), create_segment_slice(1)];
+        let mut rg_mock: ReaderGroupState = ReaderGroupState::default();
+        rg_mock.expect_check_online().return_const(true);
+        rg_mock.expect_update_reader_positions().returning(|_, _| Ok(()));
+        rg_mock.expect_get_checkpoint_for_reader().return_const(None);
+        // the reader group is reset after the first acquire.
+        let mut generation = 0;
+        rg_mock.expect_get_generation().returning(move || {
+            let current = generation;
+            generation = 1;
+            current
+        });
+        rg_mock.expect_get_end_offsets().return_const(HashMap::new());
+        rg_mock
+            .expect_compute_segments_to_acquire_or_release()
+            .returning(move |_| Ok(0 as isize));
+        rg_mock.expect_remove_reader().return_once(move |_, _| Ok(()));
+        let mut reader = EventReader::init_event_reader(
+            Arc::new(Mutex::new(rg_mock)),
+            Reader::from("r1".to_string()),
+            cf.to_async(),
+            tx.clone(),
+            rx,
+            create_slice_map(init_segments),
+            HashMap::new(),
+        );
+
+        let slice = cf
+            .runtime()
+            .block_on(reader.acquire_segment())
+            .unwrap()
+            .expect("segment slice before reset");
+        assert_eq!(reader.meta.slices.len(), 1);
+
+        // the reader drops all its segments after observing the reset.
+        let res = cf.runtime().block_on(reader.acquire_segment()).unwrap();
+        assert!(res.is_none());
+        assert_eq!(reader.meta.generation, 1);
+        assert!(reader.meta.slices.is_empty());
+        assert!(reader.meta.slices_dished_out.is_empty());
+
+        // releasing a slice acquired before the reset has no effect.
+        cf.runtime()
+            .block_on(reader.release_segment(slice))
+            .expect("release stale slice");
+        assert!(reader.meta.slices.is_empty());
+    }
+
+    // This test verifies the segments of a scaled stream past its end StreamCut are not read.
+    #[test]
+    fn test_successors_before_end() {
+        // epoch 0: 0 [0, 0.5), 1 [0.5, 1)
+        // epoch 1: 1 splits into 2 [0.5, 0.75) and 3 [0.75, 1)
+        // epoch 2: 0 and 2 merge into 4 [0, 0.75)
+        let segment = |id: i64| ScopedSegment::from(format!("scope/test/{}", id).as_str());
+        let with_range = |id: i64, min_key: f64, max_key: f64| SegmentWithRange {
+            scoped_segment: segment(id),
+            min_key: OrderedFloat(min_key),
+            max_key: OrderedFloat(max_key),
+        };
+        let mut end_offsets = HashMap::new();
+        end_offsets.insert(segment(0), 100);
+        end_offsets.insert(segment(3), 10);
+
+        // the successors of a segment that is not bounded are read.
+        let mut successors = ImHashMap::new();
+        successors.insert(with_range(2, 0.5, 0.75), vec![Segment::from(1)]);
+        successors.insert(with_range(3, 0.75, 1.0), vec![Segment::from(1)]);
+        let before_end = EventReader::successors_before_end(successors.clone(), &end_offsets);
+        assert_eq!(before_end, successors);
+
+        // the merged segment is past the end offset of segment 0.
+        let mut successors = ImHashMap::new();
+        successors.insert(with_range(4, 0.0, 0.75), vec![Segment::from(0), Segment::from(2)]);
+        let before_end = EventReader::successors_before_end(successors, &end_offsets);
+        assert!(before_end.is_empty());
+    }
+
+    // This test verifies an EventReader stops reading a segment at its end offset and does not
+    // acquire its successors.
+    #[test]
+    fn test_read_segment_up_to_end_offset() {
+        let (tx, rx) = mpsc::channel(1);
+        let cf = ClientFactory::new(
+            ClientConfigBuilder::default()
+                .controller_uri(MOCK_CONTROLLER_URI)
+                .build()
+                .unwrap(),
+        );
+        let _guard = cf.runtime().enter();
+
+        let mut init_segment = create_segment_slice(0);
+        let event = generate_event_data(10);
+        let end_offset = event.len() as i64;
+        init_segment.meta.end_offset = end_offset;
+        let segment = init_segment.meta.scoped_segment.clone();
+
+        let mut rg_mock: ReaderGroupState = ReaderGroupState::default();
+        rg_mock.expect_check_online().return_const(true);
+        rg_mock.expect_update_reader_positions().returning(|_, _| Ok(()));
+        rg_mock.expect_get_checkpoint_for_reader().return_const(None);
+        rg_mock.expect_get_generation().return_const(0u64);
+        rg_mock
+            .expect_compute_segments_to_acquire_or_release()
+            .returning(move |_| Ok(0 as isize));
+        // the segment is completed without any successors.
+        rg_mock
+            .expect_segment_completed()
+            .withf(|_, _, successors| successors.is_empty())
+            .times(1)
+            .returning(|_, _, _| Ok(()));
+        rg_mock.expect_remove_reader().return_once(move |_, _| Ok(()));
+        let mut reader = EventReader::init_event_reader(
+            Arc::new(Mutex::new(rg_mock)),
+            Reader::from("r1".to_string()),
+            cf.to_async(),
+            tx.clone(),
+            rx,
+            create_slice_map(vec![init_segment]),
+            HashMap::new(),
+        );
+
+        // simulate the segment data up to the end offset followed by the end of the read.
+        let sender = tx.clone();
+        let segment_name = segment.clone();
+        tokio::spawn(async move {
+            sender
+                .send(Ok(SegmentDataBuffer {
+                    segment: segment_name.clone(),
+                    offset_in_segment: 0,
+                    value: event,
+                }))
+                .await
+                .unwrap();
+            let end = SegmentSealed {
+                segment: segment_name,
+                can_retry: false,
+                operation: "read segment".to_string(),
+                error_msg: "reached the end offset".to_string(),
+            };
+            sender.send(Err((end, end_offset))).await.unwrap();
+        });
+
+        let mut slice = cf
+            .runtime()
+            .block_on(reader.acquire_segment())
+            .unwrap()
+            .expect("segment slice");
+        assert_eq!(slice.next().expect("event").value.len(), 10);
+        assert!(slice.next().is_none());
+        drop(slice);
+
+        let res = cf.runtime().block_on(reader.acquire_segment()).unwrap();
+        assert!(res.is_none());
+        assert!(reader.meta.slices.is_empty());
+        assert!(reader.meta.slices_dished_out.is_empty());
+
+        // there is nothing left to read.
+        let res = cf.runtime().block_on(reader.acquire_segment()).unwrap();
+        assert!(res.is_none());
+    }
+
+    #[test]
+    fn test_acquire_segments() {
+        const NUM_EVENTS: usize = 10;
+        let (tx, rx) = mpsc::channel(1);
+        tracing_subscriber::fmt().with_max_level(Level::TRACE).finish();
+        let cf = ClientFactory::new(
+            ClientConfigBuilder::default()
+                .controller_uri(MOCK_CONTROLLER_URI)
+                .build()
+                .unwrap(),
+        );
+
+        // simulate data being received from Segment store.
+        let _guard = cf.runtime().enter();
+        tokio::spawn(generate_variable_size_events(
+            tx.clone(),
+            1024,
+            NUM_EVENTS,
+            0,
+            false,
+        ));
+
+        // simulate initialization of a Reader
+        let init_segments = vec![create_segment_slice(0)];
+        let mut rg_mock: ReaderGroupState = ReaderGroupState::default();
+        rg_mock
+            .expect_compute_segments_to_acquire_or_release()
+            .with(predicate::eq(Reader::from("r1".to_string())))
+            .return_once(move |_| Ok(1 as isize));
+        rg_mock.expect_remove_reader().return_once(move |_, _| Ok(()));
+        rg_mock.expect_check_online().return_const(true);
+        rg_mock.expect_update_reader_positions().returning(|_, _| Ok(()));
+        rg_mock.expect_get_generation().return_const(0u64);
+        rg_mock.expect_get_checkpoint_for_reader().return_const(None);
+
+        // mock rg_state.assign_segment_to_reader
+        let res: Result<Option<ScopedSegment>, ReaderGroupStateError> =
+            Ok(Some(ScopedSegment::from("scope/test/1.#epoch.0")));
+        rg_mock
+            .expect_assign_segment_to_reader()
+            .with(predicate::eq(Reader::from("r1".to_string())))
+            .return_once(move |_| res);
+        //mock rg_state get_segments for reader
+        let mut new_current_segments: HashSet<(ScopedSegment, Offset)> = HashSet::new();
+        new_current_segments.insert((ScopedSegment::from("scope/test/1.#epoch.0"), Offset::new(0)));
+        new_current_segments.insert((ScopedSegment::from("scope/test/0.#epoch.0"), Offset::new(0)));
+        let res: Result<HashSet<(ScopedSegment, Offset)>, SynchronizerError> = Ok(new_current_segments);
+        rg_mock
+            .expect_get_segments_for_reader()
+            .with(predicate::eq(Reader::from("r1".to_string())))
+            .return_once(move |_| res);
+
+        // simulate data being received from Segment store.
+        tokio::spawn(generate_variable_size_events(
+            tx.clone(),
+            1024,
+            NUM_EVENTS,
+            1,
+            false,
+        ));
+
+        let before_time = Instant::now() - Duration::from_secs(15);
+        // create a new Event Reader with the segment slice data.
+        let mut reader = EventReader::init_event_reader(
+            Arc::new(Mutex::new(rg_mock)),
+            Reader::from("r1".to_string()),
+            cf.to_async(),
+            tx.clone(),
+            rx,
+            create_slice_map(init_segments),
+            HashMap::new(),
+        );
+        reader.set_last_acquire_release_time(before_time);
+
+        let mut event_count = 0;
+
+        // Attempt to acquire a segment.
+        while let Some(mut slice) = cf.runtime().block_on(reader.acquire_segment()).unwrap() {
+            loop {
+                if let Some(event) = slice.next() {
+                    println!("Read event {:?}", event);
+                    assert!(is_all_same(event.value.as_slice()), "Event has been corrupted");
+                    event_count += 1;
                 } else {
                     println!(
                         "Finished reading from segment {:?}, segment is auto released",
@@ -1499,6 +2470,9 @@ mod tests {
             .expect_compute_segments_to_acquire_or_release()
             .return_once(move |_| Ok(0 as isize));
         rg_mock.expect_check_online().return_const(true);
+        rg_mock.expect_update_reader_positions().returning(|_, _| Ok(()));
+        rg_mock.expect_get_generation().return_const(0u64);
+        rg_mock.expect_get_checkpoint_for_reader().return_const(None);
         rg_mock.expect_remove_reader().return_once(move |_, _| Ok(()));
         // create a new Event Reader with the segment slice data.
         let mut reader = EventReader::init_event_reader(
@@ -1543,6 +2517,272 @@ mod tests {
         }
     }
 
+    // This test verifies an EventReader does not hand out more slices than the configured maximum.
+    #[test]
+    fn test_max_slices_in_flight() {
+        const NUM_EVENTS: usize = 10;
+        let (tx, rx) = mpsc::channel(1);
+        let cf = ClientFactory::new(
+            ClientConfigBuilder::default()
+                .controller_uri(MOCK_CONTROLLER_URI)
+                .build()
+                .unwrap(),
+        );
+
+        // simulate data being received from Segment store.
+        let _guard = cf.runtime().enter();
+        tokio::spawn(generate_variable_size_events(
+            tx.clone(),
+            100,
+            NUM_EVENTS,
+            0,
+            false,
+        ));
+
+        let init_segments = vec![create_segment_slice(0), create_segment_slice(1)];
+        let mut rg_mock: ReaderGroupState = ReaderGroupState::default();
+        rg_mock.expect_check_online().return_const(true);
+        rg_mock.expect_update_reader_positions().returning(|_, _| Ok(()));
+        rg_mock.expect_get_generation().return_const(0u64);
+        rg_mock.expect_get_checkpoint_for_reader().return_const(None);
+        rg_mock
+            .expect_compute_segments_to_acquire_or_release()
+            .returning(move |_| Ok(0 as isize));
+        rg_mock.expect_remove_reader().return_once(move |_, _| Ok(()));
+        let mut reader = EventReader::init_event_reader(
+            Arc::new(Mutex::new(rg_mock)),
+            Reader::from("r1".to_string()),
+            cf.to_async(),
+            tx.clone(),
+            rx,
+            create_slice_map(init_segments),
+            HashMap::new(),
+        );
+        reader.config = Arc::new(
+            ReaderConfigBuilder::default()
+                .max_slices_in_flight(1usize)
+                .build()
+                .expect("build reader config"),
+        );
+
+        let mut slice = cf
+            .runtime()
+            .block_on(reader.acquire_segment())
+            .unwrap()
+            .expect("segment slice");
+        assert_eq!(slice.next().expect("event").value.len(), 1);
+
+        // no other slice is handed out while the first one is in flight.
+        let res = cf.runtime().block_on(reader.acquire_segment()).unwrap();
+        assert!(res.is_none());
+
+        // the slice is handed out again once it is returned.
+        drop(slice);
+        let mut slice = cf
+            .runtime()
+            .block_on(reader.acquire_segment())
+            .unwrap()
+            .expect("segment slice");
+        assert_eq!(slice.next().expect("event").value.len(), 2);
+    }
+
+    // This test verifies an EventReader records heartbeats while acquiring segments.
+    #[tokio::test(start_paused = true)]
+    async fn test_reader_heartbeat() {
+        let mut rg_mock: ReaderGroupState = ReaderGroupState::default();
+        rg_mock.expect_reader_heartbeat().times(2).returning(|_| Ok(()));
+        let rg_state = Arc::new(Mutex::new(rg_mock));
+        let (stop_heartbeat, rx_stop) = oneshot::channel();
+        let heartbeats = tokio::spawn(EventReader::send_heartbeats(
+            rg_state.clone(),
+            Reader::from("r1".to_string()),
+            Duration::from_secs(10),
+            rx_stop,
+        ));
+
+        // a heartbeat is recorded at every interval whether or not the reader acquires segments.
+        sleep(Duration::from_secs(25)).await;
+        drop(stop_heartbeat);
+        heartbeats.await.expect("heartbeat task");
+        rg_state.lock().await.checkpoint();
+    }
+
+    #[test]
+    fn test_reader_config_builder() {
+        let config = ReaderConfig::default();
+        assert_eq!(config.rebalance_interval(), Duration::from_secs(10));
+        assert_eq!(config.read_buffer_size(), 8 * 1024 * 1024);
+        assert_eq!(config.max_slices_in_flight(), usize::MAX);
+        assert_eq!(config.liveness_timeout(), Duration::from_secs(30));
+        assert_eq!(config.position_update_interval(), Duration::from_secs(1));
+        assert!(!config.large_events());
+        assert!(!config.event_headers());
+        assert!(config.legacy_events_as_payload());
+        assert!(!config.decompress_events());
+        assert_eq!(config.max_decompressed_event_size(), 64 * 1024 * 1024);
+
+        assert!(ReaderConfigBuilder::default()
+            .read_buffer_size(0)
+            .build()
+            .is_err());
+        assert!(ReaderConfigBuilder::default()
+            .max_slices_in_flight(0usize)
+            .build()
+            .is_err());
+    }
+
+    #[test]
+    fn test_read_large_event_from_slice() {
+        let large: Vec<u8> = (0..200).map(|i| i as u8).collect();
+        let chunks = large_event::split_into_chunks(1, &large, large_event::CHUNK_HEADER_SIZE + 64);
+        let mut events = vec![b"small".to_vec()];
+        events.extend(chunks);
+        events.push(b"tail".to_vec());
+        let mut data = BytesMut::new();
+        for event in events.iter() {
+            data.put_i32(EventCommand::TYPE_CODE);
+            data.put_i32(event.len() as i32);
+            data.put(event.as_slice());
+        }
+        let total_len = data.len() as i64;
+
+        let mut slice = create_segment_slice(0);
+        slice.config = Arc::new(
+            ReaderConfigBuilder::default()
+                .large_events(true)
+                .build()
+                .expect("build reader config"),
+        );
+        // only part of the last chunk has been read so far.
+        let rest = data.split_off(data.len() - 20);
+        slice.meta.set_segment_data(0, data);
+        let event = slice.next().expect("small event");
+        assert_eq!(event.value, b"small".to_vec());
+        assert!(slice.next().is_none());
+        assert!(slice.meta.partial_data_present);
+        let large_event_offset = slice.meta.read_offset;
+        assert_eq!(large_event_offset, 13);
+
+        // the rest of the data is read.
+        EventReader::add_data_to_segment_slice(
+            SegmentDataBuffer {
+                segment: slice.meta.scoped_segment.clone(),
+                offset_in_segment: total_len - 20,
+                value: rest,
+            },
+            &mut slice.meta,
+        );
+        let event = slice.next().expect("large event");
+        assert_eq!(event.offset_in_segment, large_event_offset);
+        assert_eq!(event.value, large);
+        let event = slice.next().expect("tail event");
+        assert_eq!(event.value, b"tail".to_vec());
+        assert_eq!(slice.meta.read_offset, total_len);
+        assert!(slice.next().is_none());
+    }
+
+    #[test]
+    fn test_read_compressed_events_from_slice() {
+        let json = br#"{"sensor":"temperature","value":21.5}"#.repeat(10);
+        let compressed = compression::compress(Compression::Zstd, json.clone()).expect("compress");
+        assert!(compressed.len() < json.len());
+        let mut data = BytesMut::new();
+        for event in [compressed.clone(), json.clone()].iter() {
+            data.put_i32(EventCommand::TYPE_CODE);
+            data.put_i32(event.len() as i32);
+            data.put(event.as_slice());
+        }
+        let total_len = data.len() as i64;
+
+        // the events are handed out as they were written by default.
+        let mut slice = create_segment_slice(0);
+        slice.meta.set_segment_data(0, data.clone());
+        assert_eq!(slice.next().expect("compressed event").value, compressed);
+        assert_eq!(slice.next().expect("uncompressed event").value, json);
+
+        let mut slice = create_segment_slice(0);
+        slice.config = Arc::new(
+            ReaderConfigBuilder::default()
+                .decompress_events(true)
+                .build()
+                .expect("build reader config"),
+        );
+        slice.meta.set_segment_data(0, data);
+        // compressed and uncompressed events are read alike.
+        assert_eq!(slice.next().expect("compressed event").value, json);
+        assert_eq!(slice.meta.read_offset, compressed.len() as i64 + 8);
+        assert_eq!(slice.next().expect("uncompressed event").value, json);
+        assert_eq!(slice.meta.read_offset, total_len);
+        assert!(slice.next().is_none());
+    }
+
+    #[test]
+    fn test_read_events_with_headers_from_slice() {
+        let mut event_headers = EventHeaders::new();
+        event_headers.insert("content-type".to_string(), "application/json".to_string());
+        let with_headers = headers::encode(&event_headers, b"payload").expect("encode");
+        let legacy = b"legacy".to_vec();
+        let mut data = BytesMut::new();
+        for event in [legacy.clone(), with_headers.clone(), legacy].iter() {
+            data.put_i32(EventCommand::TYPE_CODE);
+            data.put_i32(event.len() as i32);
+            data.put(event.as_slice());
+        }
+        let with_event_headers = |legacy_events_as_payload| {
+            Arc::new(
+                ReaderConfigBuilder::default()
+                    .event_headers(true)
+                    .legacy_events_as_payload(legacy_events_as_payload)
+                    .build()
+                    .expect("build reader config"),
+            )
+        };
+
+        // the events are handed out as they were written by default.
+        let mut slice = create_segment_slice(0);
+        slice.meta.set_segment_data(0, data.clone());
+        assert_eq!(slice.next().expect("legacy event").value, b"legacy".to_vec());
+        let event = slice.next().expect("event with headers");
+        assert_eq!(event.value, with_headers);
+        assert!(event.headers().is_empty());
+
+        let mut slice = create_segment_slice(0);
+        slice.config = with_event_headers(true);
+        slice.meta.set_segment_data(0, data.clone());
+        let event = slice.next().expect("legacy event");
+        assert_eq!(event.value, b"legacy".to_vec());
+        assert!(event.headers().is_empty());
+        let event = slice.next().expect("event with headers");
+        assert_eq!(event.value, b"payload".to_vec());
+        assert_eq!(event.headers(), &event_headers);
+        assert!(slice.next().is_some());
+        assert!(slice.next().is_none());
+
+        // the legacy events are skipped.
+        let mut slice = create_segment_slice(0);
+        slice.config = with_event_headers(false);
+        slice.meta.set_segment_data(0, data.clone());
+        let event = slice.next().expect("event with headers");
+        assert_eq!(event.value, b"payload".to_vec());
+        assert_eq!(event.offset_in_segment, 14);
+        assert!(slice.next().is_none());
+        assert_eq!(slice.meta.read_offset, data.len() as i64);
+
+        // an event whose headers cannot be decoded is skipped.
+        let truncated = &with_headers[..with_headers.len() - 12];
+        let mut data = BytesMut::new();
+        for event in [truncated, b"legacy"].iter() {
+            data.put_i32(EventCommand::TYPE_CODE);
+            data.put_i32(event.len() as i32);
+            data.put(*event);
+        }
+        let mut slice = create_segment_slice(0);
+        slice.config = with_event_headers(true);
+        slice.meta.set_segment_data(0, data);
+        assert_eq!(slice.next().expect("legacy event").value, b"legacy".to_vec());
+        assert!(slice.next().is_none());
+    }
+
     #[test]
     fn test_return_slice() {
         const NUM_EVENTS: usize = 2;
@@ -1570,6 +2810,9 @@ mod tests {
 
         let mut rg_mock: ReaderGroupState = ReaderGroupState::default();
         rg_mock.expect_check_online().return_const(true);
+        rg_mock.expect_update_reader_positions().returning(|_, _| Ok(()));
+        rg_mock.expect_get_generation().return_const(0u64);
+        rg_mock.expect_get_checkpoint_for_reader().return_const(None);
         rg_mock
             .expect_compute_segments_to_acquire_or_release()
             .return_once(move |_| Ok(0 as isize));
@@ -1624,6 +2867,74 @@ mod tests {
         assert_eq!(event.offset_in_segment, 8 + 1); // first event.
     }
 
+    // This test verifies an EventReader publishes the offsets of the returned slices.
+    #[test]
+    fn test_reader_publishes_positions() {
+        let (tx, rx) = mpsc::channel(1);
+        let cf = ClientFactory::new(
+            ClientConfigBuilder::default()
+                .controller_uri(MOCK_CONTROLLER_URI)
+                .build()
+                .unwrap(),
+        );
+        let _guard = cf.runtime().enter();
+        tokio::spawn(generate_variable_size_events(tx.clone(), 10, 2, 0, false));
+        let init_segments = vec![create_segment_slice(0), create_segment_slice(1)];
+
+        let published = Arc::new(std::sync::Mutex::new(vec![]));
+        let published_clone = published.clone();
+        let mut rg_mock: ReaderGroupState = ReaderGroupState::default();
+        rg_mock.expect_check_online().return_const(true);
+        rg_mock
+            .expect_update_reader_positions()
+            .returning(move |_, positions| {
+                published_clone.lock().unwrap().push(positions);
+                Ok(())
+            });
+        rg_mock.expect_get_generation().return_const(0u64);
+        rg_mock.expect_get_checkpoint_for_reader().return_const(None);
+        rg_mock
+            .expect_compute_segments_to_acquire_or_release()
+            .returning(move |_| Ok(0 as isize));
+        rg_mock.expect_remove_reader().return_once(move |_, _| Ok(()));
+        let mut reader = EventReader::init_event_reader(
+            Arc::new(Mutex::new(rg_mock)),
+            Reader::from("r1".to_string()),
+            cf.to_async(),
+            tx.clone(),
+            rx,
+            create_slice_map(init_segments),
+            HashMap::new(),
+        );
+        reader.config = Arc::new(
+            ReaderConfigBuilder::default()
+                .position_update_interval(Duration::from_millis(0))
+                .build()
+                .expect("build reader config"),
+        );
+
+        // the segment slice counts from the offset it was handed out at until it is returned.
+        let mut slice = cf
+            .runtime()
+            .block_on(reader.acquire_segment())
+            .expect("acquire segment")
+            .expect("segment slice");
+        let segment = ScopedSegment::from(slice.meta.scoped_segment.as_str());
+        let event = slice.next().expect("event");
+        assert_eq!(event.offset_in_segment, 0);
+        cf.runtime()
+            .block_on(reader.release_segment(slice))
+            .expect("release segment");
+        cf.runtime()
+            .block_on(reader.acquire_segment())
+            .expect("acquire segment");
+
+        let published = published.lock().unwrap();
+        assert_eq!(published.len(), 2);
+        assert_eq!(published[0].get(&segment), Some(&Offset::new(0)));
+        assert_eq!(published[1].get(&segment), Some(&Offset::new(9)));
+    }
+
     #[test]
     fn test_return_slice_at_offset() {
         const NUM_EVENTS: usize = 2;
@@ -1654,6 +2965,9 @@ mod tests {
         let init_segments = vec![create_segment_slice(0), create_segment_slice(1)];
         let mut rg_mock: ReaderGroupState = ReaderGroupState::default();
         rg_mock.expect_check_online().return_const(true);
+        rg_mock.expect_update_reader_positions().returning(|_, _| Ok(()));
+        rg_mock.expect_get_generation().return_const(0u64);
+        rg_mock.expect_get_checkpoint_for_reader().return_const(None);
         rg_mock
             .expect_compute_segments_to_acquire_or_release()
             .return_once(move |_| Ok(0 as isize));
@@ -2090,6 +3404,8 @@ mod tests {
                 partial_data_present: false,
             },
             slice_return_tx: None,
+            checkpoint: None,
+            config: Arc::default(),
         };
         segment_slice
     }
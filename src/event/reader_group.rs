@@ -9,8 +9,16 @@
 //
 
 use crate::client_factory::ClientFactoryAsync;
-use crate::event::reader::EventReader;
+use crate::event::reader::{EventReader, ReaderConfig};
 use crate::event::reader_group_state::{Offset, ReaderGroupStateError};
+use crate::event::segment_assignment::{
+    AssignmentStrategy, CustomAssignmentStrategy, SegmentAssignmentStrategy,
+};
+use crate::event::serializer::Deserializer;
+use crate::event::typed_reader::TypedEventReader;
+use crate::update;
+use crate::util::meta::MetaClientError;
+use crate::util::metric::ClientMetrics;
 
 use pravega_client_shared::{Reader, Scope, ScopedSegment, ScopedStream};
 
@@ -21,9 +29,14 @@ use serde_cbor::Error as CborError;
 use serde_cbor::{from_slice, to_vec};
 use snafu::ResultExt;
 use snafu::Snafu;
-use std::collections::HashMap;
+use std::cmp;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::Mutex;
+use tokio::time::{sleep, Instant};
+
+const CHECKPOINT_POLL_INTERVAL: Duration = Duration::from_millis(500);
 
 cfg_if::cfg_if! {
     if #[cfg(test)] {
@@ -69,7 +82,7 @@ cfg_if::cfg_if! {
 pub struct ReaderGroup {
     pub name: String,
     pub config: ReaderGroupConfig,
-    state: Arc<Mutex<ReaderGroupState>>,
+    pub(crate) state: Arc<Mutex<ReaderGroupState>>,
     client_factory: ClientFactoryAsync,
 }
 
@@ -94,7 +107,15 @@ impl ReaderGroup {
                 client_factory: &ClientFactoryAsync,
                 init_segments: HashMap<ScopedSegment, Offset>,
             ) -> ReaderGroupState {
-                ReaderGroupState::new(scope, name, client_factory, rg_config.config, init_segments).await
+                ReaderGroupState::new(
+                    scope,
+                    name,
+                    client_factory,
+                    rg_config.config,
+                    rg_config.custom_assignment_strategy.map(|strategy| strategy.0),
+                    init_segments,
+                )
+                .await
             }
         }
     }
@@ -108,6 +129,28 @@ impl ReaderGroup {
         rg_config: ReaderGroupConfig,
         client_factory: ClientFactoryAsync,
     ) -> ReaderGroup {
+        let init_segments = ReaderGroup::get_init_segments(&rg_config, &client_factory).await;
+        let rg_state = ReaderGroup::create_rg_state(
+            scope,
+            name.clone(),
+            rg_config.clone(),
+            &client_factory,
+            init_segments,
+        )
+        .await;
+        ReaderGroup {
+            name: name.clone(),
+            config: rg_config.clone(),
+            state: Arc::new(Mutex::new(rg_state)),
+            client_factory,
+        }
+    }
+
+    // Fetch the segments and their offsets to read from for the starting StreamCuts of the config.
+    async fn get_init_segments(
+        rg_config: &ReaderGroupConfig,
+        client_factory: &ClientFactoryAsync,
+    ) -> HashMap<ScopedSegment, Offset> {
         let streams = rg_config.get_start_stream_cuts();
         let mut init_segments: HashMap<ScopedSegment, Offset> = HashMap::new();
 
@@ -129,20 +172,7 @@ impl ReaderGroup {
                 init_segments.extend(segments.iter().map(|(seg, off)| (seg.clone(), Offset::new(*off))));
             }
         }
-        let rg_state = ReaderGroup::create_rg_state(
-            scope,
-            name.clone(),
-            rg_config.clone(),
-            &client_factory,
-            init_segments,
-        )
-        .await;
-        ReaderGroup {
-            name: name.clone(),
-            config: rg_config.clone(),
-            state: Arc::new(Mutex::new(rg_state)),
-            client_factory,
-        }
+        init_segments
     }
 
     /// Delete a reader group.
@@ -163,6 +193,23 @@ impl ReaderGroup {
     /// let reader = rg.create_reader("reader".to_string()).await;
     /// ```
     pub async fn create_reader(&self, reader_id: String) -> EventReader {
+        self.create_reader_with_config(reader_id, ReaderConfig::default())
+            .await
+    }
+
+    /// Create a new EventReader under the ReaderGroup with the given [`ReaderConfig`]. This method
+    /// panics if the reader is already part of the reader group.
+    ///
+    /// # Examples
+    /// ```ignore
+    /// let rg = client_factory.create_reader_group(scope, "rg".to_string(), stream).await;
+    /// let config = ReaderConfigBuilder::default()
+    ///     .rebalance_interval(Duration::from_secs(1))
+    ///     .build()
+    ///     .expect("creating reader config");
+    /// let reader = rg.create_reader_with_config("reader".to_string(), config).await;
+    /// ```
+    pub async fn create_reader_with_config(&self, reader_id: String, config: ReaderConfig) -> EventReader {
         let r: Reader = reader_id.into();
         self.state
             .lock()
@@ -170,7 +217,23 @@ impl ReaderGroup {
             .add_reader(&r)
             .await
             .expect("Error while creating the reader");
-        EventReader::init_reader(r.name, self.state.clone(), self.client_factory.clone()).await
+        EventReader::init_reader(r.name, self.state.clone(), self.client_factory.clone(), config).await
+    }
+
+    /// Create a new TypedEventReader under the ReaderGroup. Events are decoded using the given deserializer.
+    /// This method panics if the reader is already part of the reader group.
+    ///
+    /// # Examples
+    /// ```ignore
+    /// let rg = client_factory.create_reader_group(scope, "rg".to_string(), stream).await;
+    /// let reader = rg.create_typed_reader::<MyEvent, _>("reader".to_string(), CborCodec).await;
+    /// ```
+    pub async fn create_typed_reader<T, D: Deserializer<T> + Clone>(
+        &self,
+        reader_id: String,
+        deserializer: D,
+    ) -> TypedEventReader<T, D> {
+        TypedEventReader::new(self.create_reader(reader_id).await, deserializer)
     }
 
     /// Returns the readers which are currently online.
@@ -208,6 +271,219 @@ impl ReaderGroup {
         }
     }
 
+    /// Marks the readers which have not recorded a heartbeat within the given timeout as offline.
+    /// The segments owned by those readers are put back to the unassigned pool at their last known
+    /// offsets so that the other readers can acquire them. Returns the readers which have been evicted.
+    ///
+    /// Readers record heartbeats in the background, see [`ReaderConfig::liveness_timeout`].
+    /// The timeout should not be smaller than the liveness timeout of the readers.
+    ///
+    /// [`ReaderConfig::liveness_timeout`]: crate::event::reader::ReaderConfig::liveness_timeout
+    pub async fn evict_dead_readers(&self, timeout: Duration) -> Result<Vec<Reader>, ReaderGroupStateError> {
+        self.state.lock().await.evict_dead_readers(timeout).await
+    }
+
+    /// Initiate a checkpoint with the given name and wait for it to complete.
+    ///
+    /// Every reader that is online when the checkpoint is initiated returns a checkpoint marker
+    /// from [`EventReader::acquire_segment`] once all the segment slices it handed out have been returned.
+    /// The checkpoint completes when all those readers have passed it or gone offline, so it only
+    /// completes while the readers keep calling `acquire_segment`. The readers do not acquire or
+    /// release segments while the checkpoint is in progress. If the checkpoint does not complete
+    /// within the given timeout it is discarded and [`ReaderGroupStateError::CheckpointTimeoutError`]
+    /// is returned.
+    ///
+    /// The returned [`Checkpoint`] contains a StreamCut per stream that can be passed to
+    /// [`ReaderGroupConfigBuilder::read_from_stream`] to resume reading from the checkpoint.
+    ///
+    /// # Examples
+    /// ```ignore
+    /// let rg = client_factory.create_reader_group(scope, "rg".to_string(), stream).await;
+    /// let checkpoint = rg
+    ///     .initiate_checkpoint("checkpoint".to_string(), Duration::from_secs(30))
+    ///     .await
+    ///     .expect("checkpoint");
+    /// ```
+    ///
+    /// [`EventReader::acquire_segment`]: crate::event::reader::EventReader::acquire_segment
+    pub async fn initiate_checkpoint(
+        &self,
+        checkpoint_name: String,
+        timeout: Duration,
+    ) -> Result<Checkpoint, ReaderGroupStateError> {
+        self.state
+            .lock()
+            .await
+            .initiate_checkpoint(&checkpoint_name)
+            .await?;
+        let deadline = Instant::now() + timeout;
+        loop {
+            let completed = self
+                .state
+                .lock()
+                .await
+                .get_completed_checkpoint(&checkpoint_name)
+                .await?;
+            if let Some(positions) = completed {
+                self.state
+                    .lock()
+                    .await
+                    .remove_checkpoint(&checkpoint_name)
+                    .await?;
+                return Ok(Checkpoint::new(checkpoint_name, positions));
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                // an incomplete checkpoint keeps the readers from acquiring segments.
+                self.state
+                    .lock()
+                    .await
+                    .remove_checkpoint(&checkpoint_name)
+                    .await?;
+                return Err(ReaderGroupStateError::CheckpointTimeoutError {
+                    name: checkpoint_name,
+                    timeout,
+                });
+            }
+            sleep(cmp::min(CHECKPOINT_POLL_INTERVAL, deadline - now)).await;
+        }
+    }
+
+    /// Reset the ReaderGroup to the given config.
+    ///
+    /// The segments to read from are replaced by the ones of the starting StreamCuts of the new config
+    /// and all the segments assigned to the online readers are revoked. The online readers stay in the
+    /// ReaderGroup; they stop reading from their previous segments and acquire the new segments on their
+    /// next call to `acquire_segment`. Pending checkpoints are discarded.
+    ///
+    /// # Examples
+    /// ```ignore
+    /// let mut rg = client_factory.create_reader_group(scope, "rg".to_string(), stream).await;
+    /// let config = ReaderGroupConfigBuilder::default().read_from_tail_of_stream(other_stream).build();
+    /// rg.reset(config).await.expect("reset reader group");
+    /// ```
+    pub async fn reset(&mut self, config: ReaderGroupConfig) -> Result<(), ReaderGroupStateError> {
+        let init_segments = ReaderGroup::get_init_segments(&config, &self.client_factory).await;
+        self.state
+            .lock()
+            .await
+            .reset(config.config.clone(), init_segments)
+            .await?;
+        self.config = config;
+        Ok(())
+    }
+
+    /// Generate a StreamCut for each of the streams managed by the ReaderGroup from the
+    /// last known positions of the readers.
+    ///
+    /// The positions of the assigned segments are the offsets most recently published by the readers,
+    /// which do so every [`ReaderConfig::position_update_interval`] while they acquire segments.
+    /// A segment slice that is out for consumption counts from the offset it was handed out at, so
+    /// the StreamCuts never skip an event, but they may lag behind the events the readers have read.
+    /// Use [`ReaderGroup::initiate_checkpoint`] if the positions need to be exact.
+    ///
+    /// [`ReaderConfig::position_update_interval`]: crate::event::reader::ReaderConfig::position_update_interval
+    pub async fn generate_stream_cuts(&self) -> HashMap<ScopedStream, StreamCutVersioned> {
+        let positions = self.state.lock().await.get_segment_positions().await;
+        StreamCutV1::from_segment_positions(positions)
+    }
+
+    /// Returns the number of bytes the ReaderGroup has not read yet.
+    ///
+    /// This is the sum of [`ReaderGroup::unread_bytes_per_segment`] and it is also published as the
+    /// `pravega.client.reader_group.unread_bytes` gauge. The gauge is only refreshed when this method
+    /// is called, so an application that exports it should call this method periodically.
+    pub async fn unread_bytes(&self) -> Result<i64, MetaClientError> {
+        let unread_bytes: i64 = self.unread_bytes_per_segment().await?.values().sum();
+        update!(
+            ClientMetrics::ReaderGroupUnreadBytes,
+            unread_bytes,
+            "Reader Group" => self.name.clone()
+        );
+        Ok(unread_bytes)
+    }
+
+    /// Returns the number of bytes the ReaderGroup has not read yet for each segment.
+    ///
+    /// The unread bytes of a segment are computed from the last known position of the readers, as
+    /// in [`ReaderGroup::generate_stream_cuts`], and the current length of the segment. The segments
+    /// the readers have not reached yet, i.e. the future segments and the successors of the unread
+    /// segments up to the current tail of the streams, are counted as entirely unread. Segments in
+    /// the end StreamCut of the ReaderGroup are only counted up to their end offset and their
+    /// successors are not counted.
+    pub async fn unread_bytes_per_segment(&self) -> Result<HashMap<ScopedSegment, i64>, MetaClientError> {
+        let (positions, future_segments, end_offsets) = {
+            let mut state = self.state.lock().await;
+            let positions = state.get_segment_positions().await;
+            let future_segments = state.get_future_segments().await;
+            let end_offsets = state.get_end_offsets().await;
+            (positions, future_segments, end_offsets)
+        };
+        let mut tail_lengths: HashMap<ScopedSegment, i64> = HashMap::new();
+        for stream in self.config.get_streams() {
+            let meta_client = self.client_factory.create_stream_meta_client(stream).await;
+            match meta_client.fetch_current_tail_segments().await {
+                Ok(StreamCutVersioned::V1(tail)) => tail_lengths.extend(tail.positions),
+                Ok(_) => {}
+                // a sealed stream has no tail segments.
+                Err(MetaClientError::StreamSealed { .. }) => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        // walk from the segments the readers have not completed to the tail of the streams so that
+        // the segments of the epochs in between are counted as well.
+        let mut pending: VecDeque<ScopedSegment> =
+            positions.keys().chain(future_segments.iter()).cloned().collect();
+        let mut visited: HashSet<ScopedSegment> = pending.iter().cloned().collect();
+        let mut segment_lengths: HashMap<ScopedSegment, i64> = HashMap::new();
+        while let Some(segment) = pending.pop_front() {
+            if let Some(length) = tail_lengths.get(&segment) {
+                segment_lengths.insert(segment, *length);
+                continue;
+            }
+            let meta_client = self
+                .client_factory
+                .create_stream_meta_client(ScopedStream::from(&segment))
+                .await;
+            let length = meta_client.fetch_segment_length(&segment).await?;
+            segment_lengths.insert(segment.clone(), length);
+            // the successors of the segments in the end StreamCut are never read.
+            if end_offsets.contains_key(&segment) {
+                continue;
+            }
+            for successor in meta_client.fetch_successors(&segment).await? {
+                if visited.insert(successor.clone()) {
+                    pending.push_back(successor);
+                }
+            }
+        }
+        Ok(ReaderGroup::compute_unread_bytes(
+            positions,
+            segment_lengths,
+            &end_offsets,
+        ))
+    }
+
+    // Compute the unread bytes of each segment from the positions of the readers, the segment lengths
+    // and the end offsets. Segments without a position have not been reached by the readers yet.
+    fn compute_unread_bytes(
+        positions: HashMap<ScopedSegment, Offset>,
+        segment_lengths: HashMap<ScopedSegment, i64>,
+        end_offsets: &HashMap<ScopedSegment, i64>,
+    ) -> HashMap<ScopedSegment, i64> {
+        segment_lengths
+            .into_iter()
+            .map(|(segment, length)| {
+                let end = end_offsets
+                    .get(&segment)
+                    .map_or(length, |end| cmp::min(*end, length));
+                let read = positions.get(&segment).map_or(0, |offset| offset.read);
+                (segment, cmp::max(end - read, 0))
+            })
+            .collect()
+    }
+
     ///
     /// Return the managed Streams by the ReaderGroup.
     ///
@@ -216,11 +492,39 @@ impl ReaderGroup {
     }
 }
 
+/// A consistent position of all the readers of a ReaderGroup.
+#[derive(PartialEq, Debug, Clone)]
+pub struct Checkpoint {
+    name: String,
+    positions: HashMap<ScopedStream, StreamCutVersioned>,
+}
+
+impl Checkpoint {
+    fn new(name: String, segment_positions: HashMap<ScopedSegment, Offset>) -> Self {
+        Checkpoint {
+            name,
+            positions: StreamCutV1::from_segment_positions(segment_positions),
+        }
+    }
+
+    /// Returns the name of the checkpoint.
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the StreamCut of each stream at the checkpoint.
+    pub fn get_positions(&self) -> &HashMap<ScopedStream, StreamCutVersioned> {
+        &self.positions
+    }
+}
+
 /// Specifies the ReaderGroupConfig.
 /// ReaderGroupConfig::default() ensures the group refresh interval is set to 3 seconds.
 #[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
 pub struct ReaderGroupConfig {
     pub(crate) config: ReaderGroupConfigVersioned,
+    #[serde(skip)]
+    pub(crate) custom_assignment_strategy: Option<CustomAssignmentStrategy>,
 }
 
 impl ReaderGroupConfig {
@@ -230,9 +534,11 @@ impl ReaderGroupConfig {
             group_refresh_time_millis,
             starting_stream_cuts: HashMap::new(),
             ending_stream_cuts: HashMap::new(),
+            assignment_strategy: AssignmentStrategy::default(),
         };
         ReaderGroupConfig {
             config: ReaderGroupConfigVersioned::V1(conf_v1),
+            custom_assignment_strategy: None,
         }
     }
 
@@ -244,7 +550,10 @@ impl ReaderGroupConfig {
     /// Method to de-serialize the ReaderGroupConfig object from bytes.
     pub fn from_bytes(input: &[u8]) -> Result<Self, SerdeError> {
         let decoded = ReaderGroupConfigVersioned::from_bytes(input);
-        decoded.map(|config| ReaderGroupConfig { config })
+        decoded.map(|config| ReaderGroupConfig {
+            config,
+            custom_assignment_strategy: None,
+        })
     }
 
     /// Method to obtain the streams in a ReaderGroupConfig.
@@ -261,6 +570,19 @@ impl ReaderGroupConfig {
         let ReaderGroupConfigVersioned::V1(v1) = &self.config;
         v1.starting_stream_cuts.clone()
     }
+
+    /// Method to obtain the streams and end Streamcut in a ReaderGroupConfig.
+    pub fn get_end_stream_cuts(&self) -> HashMap<ScopedStream, StreamCutVersioned> {
+        let ReaderGroupConfigVersioned::V1(v1) = &self.config;
+        v1.ending_stream_cuts.clone()
+    }
+
+    /// Method to obtain the strategy persisted in the ReaderGroup to distribute the segments among
+    /// the readers. The readers created from this config use the custom strategy instead if one is set.
+    pub fn get_assignment_strategy(&self) -> AssignmentStrategy {
+        let ReaderGroupConfigVersioned::V1(v1) = &self.config;
+        v1.assignment_strategy.clone()
+    }
 }
 
 ///
@@ -269,6 +591,9 @@ impl ReaderGroupConfig {
 pub struct ReaderGroupConfigBuilder {
     group_refresh_time_millis: u64,
     starting_stream_cuts: HashMap<ScopedStream, StreamCutVersioned>,
+    ending_stream_cuts: HashMap<ScopedStream, StreamCutVersioned>,
+    assignment_strategy: AssignmentStrategy,
+    custom_assignment_strategy: Option<CustomAssignmentStrategy>,
 }
 
 impl ReaderGroupConfigBuilder {
@@ -278,6 +603,27 @@ impl ReaderGroupConfigBuilder {
         self
     }
 
+    /// Set the strategy used to distribute the segments among the readers. The segments are evenly
+    /// distributed by default.
+    pub fn set_assignment_strategy(&mut self, assignment_strategy: AssignmentStrategy) -> &mut Self {
+        self.assignment_strategy = assignment_strategy;
+        self
+    }
+
+    /// Set a user defined strategy used to distribute the segments among the readers.
+    ///
+    /// The custom strategy cannot be persisted in the state of the ReaderGroup. It is used by the
+    /// readers created from a ReaderGroup with this config, while readers created from the same
+    /// ReaderGroup elsewhere without it use the strategy set by
+    /// [`ReaderGroupConfigBuilder::set_assignment_strategy`], which should be set as a fallback.
+    pub fn set_custom_assignment_strategy(
+        &mut self,
+        assignment_strategy: Arc<dyn SegmentAssignmentStrategy>,
+    ) -> &mut Self {
+        self.custom_assignment_strategy = Some(CustomAssignmentStrategy(assignment_strategy));
+        self
+    }
+
     /// Add a Pravega Stream to the reader group which will be read from Current HEAD/start of the stream.
     pub fn add_stream(&mut self, stream: ScopedStream) -> &mut Self {
         self.read_from_head_of_stream(stream)
@@ -302,6 +648,25 @@ impl ReaderGroupConfigBuilder {
         self
     }
 
+    /// Add a Pravega Stream to the reader group which will be read from the start StreamCut up to the
+    /// end StreamCut. Segments past the end StreamCut are never assigned to the readers.
+    ///
+    /// The segments listed in the end StreamCut are read up to their offsets and none of their
+    /// successors is read, even if the stream has scaled since. The end StreamCut should cover the
+    /// whole key space of the stream, such as the ones generated by the ReaderGroup: a segment that
+    /// is not listed is read until it is sealed and then its successors are read, so the part of the
+    /// key space left out of the end StreamCut is not bounded.
+    pub fn read_from_stream_until(
+        &mut self,
+        stream: ScopedStream,
+        start_stream_cut: StreamCutVersioned,
+        end_stream_cut: StreamCutVersioned,
+    ) -> &mut Self {
+        self.starting_stream_cuts.insert(stream.clone(), start_stream_cut);
+        self.ending_stream_cuts.insert(stream, end_stream_cut);
+        self
+    }
+
     ///
     /// Build a ReaderGroupConfig object.
     /// This method panics for invalid configuration.
@@ -315,8 +680,10 @@ impl ReaderGroupConfigBuilder {
             config: ReaderGroupConfigVersioned::V1(ReaderGroupConfigV1 {
                 group_refresh_time_millis: self.group_refresh_time_millis,
                 starting_stream_cuts: self.starting_stream_cuts.clone(),
-                ending_stream_cuts: Default::default(), // This will be extended when bounded processing is enabled.
+                ending_stream_cuts: self.ending_stream_cuts.clone(),
+                assignment_strategy: self.assignment_strategy.clone(),
             }),
+            custom_assignment_strategy: self.custom_assignment_strategy.clone(),
         }
     }
 }
@@ -326,6 +693,9 @@ impl Default for ReaderGroupConfigBuilder {
         Self {
             group_refresh_time_millis: 3000,
             starting_stream_cuts: Default::default(),
+            ending_stream_cuts: Default::default(),
+            assignment_strategy: Default::default(),
+            custom_assignment_strategy: None,
         }
     }
 }
@@ -350,6 +720,25 @@ impl ReaderGroupConfigVersioned {
         })?;
         Ok(decoded)
     }
+
+    /// The strategy used to distribute the segments among the readers.
+    pub(crate) fn get_assignment_strategy(&self) -> &AssignmentStrategy {
+        let ReaderGroupConfigVersioned::V1(v1) = self;
+        &v1.assignment_strategy
+    }
+
+    /// The offsets up to which the segments listed in the end StreamCuts should be read.
+    pub(crate) fn get_end_offsets(&self) -> HashMap<ScopedSegment, i64> {
+        let ReaderGroupConfigVersioned::V1(v1) = self;
+        v1.ending_stream_cuts
+            .values()
+            .filter_map(|cut| match cut {
+                StreamCutVersioned::V1(cut) => Some(cut.get_positions()),
+                _ => None,
+            })
+            .flatten()
+            .collect()
+    }
 }
 
 #[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
@@ -359,6 +748,9 @@ pub(crate) struct ReaderGroupConfigV1 {
     group_refresh_time_millis: u64,
     starting_stream_cuts: HashMap<ScopedStream, StreamCutVersioned>,
     ending_stream_cuts: HashMap<ScopedStream, StreamCutVersioned>,
+    /// configs created before the strategy was introduced use the default strategy.
+    #[serde(default)]
+    assignment_strategy: AssignmentStrategy,
 }
 
 impl Default for ReaderGroupConfigV1 {
@@ -373,6 +765,7 @@ impl ReaderGroupConfigV1 {
             group_refresh_time_millis: 3000,
             starting_stream_cuts: HashMap::new(),
             ending_stream_cuts: HashMap::new(),
+            assignment_strategy: AssignmentStrategy::default(),
         }
     }
 
@@ -443,17 +836,38 @@ pub struct StreamCutV1 {
 }
 
 impl StreamCutV1 {
-    pub(crate) fn new(stream: ScopedStream, positions: HashMap<ScopedSegment, i64>) -> Self {
+    /// Create a StreamCut from the offsets of the segments of a stream.
+    pub fn new(stream: ScopedStream, positions: HashMap<ScopedSegment, i64>) -> Self {
         StreamCutV1 { stream, positions }
     }
 
+    /// Group the segment positions by stream into a StreamCut per stream.
+    pub(crate) fn from_segment_positions(
+        segment_positions: HashMap<ScopedSegment, Offset>,
+    ) -> HashMap<ScopedStream, StreamCutVersioned> {
+        let mut stream_positions: HashMap<ScopedStream, HashMap<ScopedSegment, i64>> = HashMap::new();
+        for (segment, offset) in segment_positions {
+            stream_positions
+                .entry(ScopedStream::from(&segment))
+                .or_default()
+                .insert(segment, offset.read);
+        }
+        stream_positions
+            .into_iter()
+            .map(|(stream, positions)| {
+                let cut = StreamCutVersioned::V1(StreamCutV1::new(stream.clone(), positions));
+                (stream, cut)
+            })
+            .collect()
+    }
+
     /// gets a clone of the internal scoped stream
-    pub(crate) fn get_stream(&self) -> ScopedStream {
+    pub fn get_stream(&self) -> ScopedStream {
         self.stream.clone()
     }
 
     /// gets a clone of the internal positions
-    pub(crate) fn get_positions(&self) -> HashMap<ScopedSegment, i64> {
+    pub fn get_positions(&self) -> HashMap<ScopedSegment, i64> {
         self.positions.clone()
     }
 }
@@ -529,6 +943,172 @@ mod tests {
             .block_on(rg.create_reader("r1".to_string()));
     }
 
+    #[test]
+    fn test_initiate_checkpoint_timeout() {
+        let client_factory = ClientFactory::new(
+            ClientConfigBuilder::default()
+                .controller_uri(MOCK_CONTROLLER_URI)
+                .build()
+                .unwrap(),
+        );
+        let mut mock_rg_state = ReaderGroupState::default();
+        mock_rg_state
+            .expect_initiate_checkpoint()
+            .times(1)
+            .returning(|_| Ok(()));
+        mock_rg_state
+            .expect_get_completed_checkpoint()
+            .returning(|_| Ok(None));
+        // the incomplete checkpoint is discarded.
+        mock_rg_state
+            .expect_remove_checkpoint()
+            .times(1)
+            .returning(|_| Ok(()));
+        let rg = ReaderGroup {
+            name: "rg".to_string(),
+            config: ReaderGroupConfigBuilder::default()
+                .add_stream(ScopedStream::from("scope/s1"))
+                .build(),
+            state: Arc::new(Mutex::new(mock_rg_state)),
+            client_factory: client_factory.to_async(),
+        };
+        let result = client_factory
+            .runtime()
+            .block_on(rg.initiate_checkpoint("cp".to_string(), Duration::from_millis(10)));
+        assert!(matches!(
+            result,
+            Err(ReaderGroupStateError::CheckpointTimeoutError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_initiate_checkpoint() {
+        let client_factory = ClientFactory::new(
+            ClientConfigBuilder::default()
+                .controller_uri(MOCK_CONTROLLER_URI)
+                .build()
+                .unwrap(),
+        );
+        let segment0 = ScopedSegment::from("scope/s1/0.#epoch.0");
+        let segment1 = ScopedSegment::from("scope/s2/1.#epoch.0");
+        let mut segment_positions = HashMap::new();
+        segment_positions.insert(segment0.clone(), Offset::new(10));
+        segment_positions.insert(segment1.clone(), Offset::new(20));
+
+        let mut mock_rg_state = ReaderGroupState::default();
+        mock_rg_state
+            .expect_initiate_checkpoint()
+            .times(1)
+            .returning(|_| Ok(()));
+        // the checkpoint is completed on the second poll.
+        let mut completed = None;
+        mock_rg_state
+            .expect_get_completed_checkpoint()
+            .times(2)
+            .returning(move |_| Ok(completed.replace(segment_positions.clone())));
+        mock_rg_state
+            .expect_remove_checkpoint()
+            .times(1)
+            .returning(|_| Ok(()));
+        let rg = ReaderGroup {
+            name: "rg".to_string(),
+            config: ReaderGroupConfigBuilder::default()
+                .add_stream(ScopedStream::from("scope/s1"))
+                .add_stream(ScopedStream::from("scope/s2"))
+                .build(),
+            state: Arc::new(Mutex::new(mock_rg_state)),
+            client_factory: client_factory.to_async(),
+        };
+        let checkpoint = client_factory
+            .runtime()
+            .block_on(rg.initiate_checkpoint("cp".to_string(), Duration::from_secs(10)))
+            .expect("initiate checkpoint");
+
+        assert_eq!(checkpoint.get_name(), "cp");
+        let positions = checkpoint.get_positions();
+        assert_eq!(positions.len(), 2);
+        let mut expected = HashMap::new();
+        expected.insert(segment0, 10);
+        assert_eq!(
+            positions.get(&ScopedStream::from("scope/s1")),
+            Some(&StreamCutVersioned::V1(StreamCutV1::new(
+                ScopedStream::from("scope/s1"),
+                expected
+            )))
+        );
+    }
+
+    #[test]
+    fn test_generate_stream_cuts() {
+        let client_factory = ClientFactory::new(
+            ClientConfigBuilder::default()
+                .controller_uri(MOCK_CONTROLLER_URI)
+                .build()
+                .unwrap(),
+        );
+        let stream = ScopedStream::from("scope/s1");
+        let segment0 = ScopedSegment::from("scope/s1/0.#epoch.0");
+        let segment1 = ScopedSegment::from("scope/s1/1.#epoch.0");
+        let mut segment_positions = HashMap::new();
+        segment_positions.insert(segment0.clone(), Offset::new(10));
+        segment_positions.insert(segment1.clone(), Offset::new(0));
+
+        let mut mock_rg_state = ReaderGroupState::default();
+        mock_rg_state
+            .expect_get_segment_positions()
+            .return_once(move || segment_positions);
+        let rg = ReaderGroup {
+            name: "rg".to_string(),
+            config: ReaderGroupConfigBuilder::default()
+                .add_stream(stream.clone())
+                .build(),
+            state: Arc::new(Mutex::new(mock_rg_state)),
+            client_factory: client_factory.to_async(),
+        };
+        let stream_cuts = client_factory.runtime().block_on(rg.generate_stream_cuts());
+
+        assert_eq!(stream_cuts.len(), 1);
+        if let Some(StreamCutVersioned::V1(cut)) = stream_cuts.get(&stream) {
+            assert_eq!(cut.get_stream(), stream);
+            let positions = cut.get_positions();
+            assert_eq!(positions.get(&segment0), Some(&10));
+            assert_eq!(positions.get(&segment1), Some(&0));
+        } else {
+            panic!("should generate a StreamCut for the managed stream");
+        }
+    }
+
+    #[test]
+    fn test_compute_unread_bytes() {
+        let segment0 = ScopedSegment::from("scope/s1/0.#epoch.0");
+        let segment1 = ScopedSegment::from("scope/s1/1.#epoch.0");
+        let segment2 = ScopedSegment::from("scope/s1/2.#epoch.1");
+        let mut positions = HashMap::new();
+        positions.insert(segment0.clone(), Offset::new(10));
+        positions.insert(segment1.clone(), Offset::new(50));
+        let mut segment_lengths = HashMap::new();
+        segment_lengths.insert(segment0.clone(), 100);
+        segment_lengths.insert(segment1.clone(), 50);
+        // successor which has not been reached by the readers.
+        segment_lengths.insert(segment2.clone(), 30);
+
+        let unread =
+            ReaderGroup::compute_unread_bytes(positions.clone(), segment_lengths.clone(), &HashMap::new());
+        assert_eq!(unread.len(), 3);
+        assert_eq!(unread.get(&segment0), Some(&90));
+        assert_eq!(unread.get(&segment1), Some(&0));
+        assert_eq!(unread.get(&segment2), Some(&30));
+
+        // segments in the end StreamCut are only read up to their end offset.
+        let mut end_offsets = HashMap::new();
+        end_offsets.insert(segment0.clone(), 60);
+        end_offsets.insert(segment2.clone(), 40);
+        let unread = ReaderGroup::compute_unread_bytes(positions, segment_lengths, &end_offsets);
+        assert_eq!(unread.get(&segment0), Some(&50));
+        assert_eq!(unread.get(&segment1), Some(&0));
+        assert_eq!(unread.get(&segment2), Some(&30));
+    }
+
     #[test]
     fn test_reader_group_config_serde() {
         let scope = Scope::from("scope".to_owned());
@@ -545,6 +1125,32 @@ mod tests {
         assert_eq!(ReaderGroupConfigVersioned::V1(v1), decoded);
     }
 
+    #[test]
+    fn test_reader_group_config_serde_without_assignment_strategy() {
+        // the layout of the configs stored before the assignment strategy was introduced.
+        #[derive(Serialize)]
+        struct LegacyConfigV1 {
+            group_refresh_time_millis: u64,
+            starting_stream_cuts: HashMap<ScopedStream, StreamCutVersioned>,
+            ending_stream_cuts: HashMap<ScopedStream, StreamCutVersioned>,
+        }
+        #[derive(Serialize)]
+        enum LegacyConfigVersioned {
+            V1(LegacyConfigV1),
+        }
+        let mut starting_stream_cuts = HashMap::new();
+        starting_stream_cuts.insert(ScopedStream::from("scope/stream"), StreamCutVersioned::Unbounded);
+        let legacy = LegacyConfigVersioned::V1(LegacyConfigV1 {
+            group_refresh_time_millis: 3000,
+            starting_stream_cuts,
+            ending_stream_cuts: HashMap::new(),
+        });
+
+        let encoded = to_vec(&legacy).expect("encode to byte array");
+        let decoded = ReaderGroupConfigVersioned::from_bytes(&encoded).expect("decode from byte array");
+        assert_eq!(decoded.get_assignment_strategy(), &AssignmentStrategy::default());
+    }
+
     #[test]
     fn test_reader_group_config_builder() {
         let rg_config = ReaderGroupConfigBuilder::default()
@@ -619,6 +1225,26 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_reader_group_config_builder_read_until() {
+        let stream = ScopedStream::from("scope1/s1");
+        let segment = ScopedSegment::from("scope1/s1/0.#epoch.0");
+        let mut positions = HashMap::new();
+        positions.insert(segment.clone(), 100);
+        let end = StreamCutVersioned::V1(StreamCutV1::new(stream.clone(), positions));
+        let rg_config = ReaderGroupConfigBuilder::default()
+            .read_from_stream_until(stream.clone(), StreamCutVersioned::Unbounded, end.clone())
+            .build();
+        assert_eq!(
+            rg_config.get_start_stream_cuts().get(&stream),
+            Some(&StreamCutVersioned::Unbounded)
+        );
+        assert_eq!(rg_config.get_end_stream_cuts().get(&stream), Some(&end));
+        let end_offsets = rg_config.config.get_end_offsets();
+        assert_eq!(end_offsets.len(), 1);
+        assert_eq!(end_offsets.get(&segment), Some(&100));
+    }
+
     #[test]
     #[should_panic]
     fn test_reader_group_config_builder_invalid() {
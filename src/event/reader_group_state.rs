@@ -10,6 +10,7 @@
 
 use crate::client_factory::ClientFactoryAsync;
 use crate::event::reader_group::ReaderGroupConfigVersioned;
+use crate::event::segment_assignment::{AssignmentStrategy, SegmentAssignment, SegmentAssignmentStrategy};
 use crate::sync::synchronizer::*;
 
 use pravega_client_shared::{Reader, Scope, ScopedSegment, Segment, SegmentWithRange};
@@ -20,16 +21,19 @@ use mockall::automock;
 use serde::{Deserialize, Serialize};
 use snafu::{ensure, OptionExt, ResultExt, Snafu};
 use std::collections::{HashMap, HashSet};
-use std::convert::TryInto;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use tracing::{debug, info, warn};
 
-const ASSUMED_LAG_MILLIS: u64 = 30000;
 const DEFAULT_INNER_KEY: &str = "default";
 
 const ASSIGNED: &str = "assigned_segments";
 const UNASSIGNED: &str = "unassigned_segments";
 const FUTURE: &str = "future_segments";
 const DISTANCE: &str = "distance_to_tail";
+const CHECKPOINTS: &str = "checkpoints";
+const GENERATION: &str = "generation";
+const HEARTBEATS: &str = "heartbeats";
 
 #[derive(Debug, Snafu)]
 pub enum ReaderGroupStateError {
@@ -42,6 +46,8 @@ pub enum ReaderGroupStateError {
         error_msg: String,
         source: SynchronizerError,
     },
+    #[snafu(display("Checkpoint {} did not complete within {:?}", name, timeout))]
+    CheckpointTimeoutError { name: String, timeout: Duration },
 }
 
 impl ReaderGroupStateError {
@@ -73,7 +79,20 @@ pub struct ReaderGroupState {
     ///
     /// Segments waiting to be assigned to readers.
     /// unassigned_segments: HashMap<ScopedSegment, Offset>
+    ///
+    /// Checkpoints that have been initiated and the readers that have not yet passed them.
+    /// checkpoints: HashMap<String, CheckpointState>
+    ///
+    /// Incremented every time the reader group is reset. Readers observing a new generation
+    /// drop the segments they read from.
+    /// generation: u64
+    ///
+    /// The last time in milliseconds since the UNIX epoch at which each reader was known to be alive.
+    /// heartbeats: HashMap<Reader, u64>
     sync: Synchronizer,
+
+    /// The user defined strategy of the local ReaderGroupConfig, it overrides the persisted one.
+    custom_assignment_strategy: Option<Arc<dyn SegmentAssignmentStrategy>>,
 }
 
 #[cfg_attr(test, automock)]
@@ -83,6 +102,7 @@ impl ReaderGroupState {
         reader_group_name: String,
         client_factory: &ClientFactoryAsync,
         config: ReaderGroupConfigVersioned,
+        custom_assignment_strategy: Option<Arc<dyn SegmentAssignmentStrategy>>,
         segments_to_offsets: HashMap<ScopedSegment, Offset>,
     ) -> ReaderGroupState {
         let mut sync = client_factory
@@ -111,14 +131,21 @@ impl ReaderGroupState {
         })
         .await
         .expect("should initialize table synchronizer");
-        ReaderGroupState { sync }
+        ReaderGroupState {
+            sync,
+            custom_assignment_strategy,
+        }
     }
 
     /// Adds a reader to the reader group state.
     pub async fn add_reader(&mut self, reader: &Reader) -> Result<(), ReaderGroupStateError> {
         info!("Adding reader {:?} to reader group", reader);
         self.sync
-            .insert(|table| ReaderGroupState::add_reader_internal(table, reader))
+            .insert(|table| {
+                ReaderGroupState::add_reader_internal(table, reader)?;
+                ReaderGroupState::record_heartbeat(table, reader, current_time_millis());
+                Ok(())
+            })
             .await
             .context(SyncError {
                 error_msg: format!("add reader {:?}", reader),
@@ -180,6 +207,96 @@ impl ReaderGroupState {
             .collect::<Vec<Reader>>()
     }
 
+    /// Records that the given reader is alive.
+    pub(crate) async fn reader_heartbeat(&mut self, reader: &Reader) -> Result<(), ReaderGroupStateError> {
+        debug!("Recording heartbeat of reader {:?}", reader);
+        self.sync
+            .insert(|table| ReaderGroupState::reader_heartbeat_internal(table, reader, current_time_millis()))
+            .await
+            .map_err(|err| match err {
+                SynchronizerError::SyncPreconditionError { .. } => {
+                    ReaderGroupStateError::ReaderAlreadyOfflineError {
+                        error_msg: format!("Reader {:?} already offline", reader),
+                        source: err,
+                    }
+                }
+                _ => ReaderGroupStateError::SyncError {
+                    error_msg: format!("record heartbeat of reader {:?}", reader),
+                    source: err,
+                },
+            })
+    }
+
+    fn reader_heartbeat_internal(
+        table: &mut Update,
+        reader: &Reader,
+        now: u64,
+    ) -> Result<(), SynchronizerError> {
+        if !table.contains_key(ASSIGNED, &reader.to_string()) {
+            return Err(SynchronizerError::SyncPreconditionError {
+                error_msg: format!(
+                    "Failed to record heartbeat of reader {:?}: reader already offline",
+                    reader
+                ),
+            });
+        }
+        ReaderGroupState::record_heartbeat(table, reader, now);
+        Ok(())
+    }
+
+    fn record_heartbeat(table: &mut Update, reader: &Reader, now: u64) {
+        table.insert(
+            HEARTBEATS.to_owned(),
+            reader.to_string(),
+            "u64".to_owned(),
+            Box::new(now),
+        );
+    }
+
+    /// Removes the online readers whose last heartbeat is older than the given timeout and puts
+    /// the segments they owned to the unassigned list at their last known offsets.
+    /// Returns the readers which have been removed.
+    pub(crate) async fn evict_dead_readers(
+        &mut self,
+        timeout: Duration,
+    ) -> Result<Vec<Reader>, ReaderGroupStateError> {
+        self.sync
+            .insert(|table| {
+                ReaderGroupState::evict_dead_readers_internal(table, current_time_millis(), timeout)
+            })
+            .await
+            .context(SyncError {
+                error_msg: "evict dead readers".to_owned(),
+            })
+    }
+
+    fn evict_dead_readers_internal(
+        table: &mut Update,
+        now: u64,
+        timeout: Duration,
+    ) -> Result<Vec<Reader>, SynchronizerError> {
+        let heartbeats = table.get_inner_map(HEARTBEATS);
+        // readers that never recorded a heartbeat are not considered dead.
+        let dead_readers: Vec<Reader> = table
+            .get_inner_map(ASSIGNED)
+            .keys()
+            .filter(|reader| match heartbeats.get(*reader) {
+                Some(v) => {
+                    let last_heartbeat: u64 =
+                        deserialize_from(&v.data).expect("deserialize reader heartbeat");
+                    now.saturating_sub(last_heartbeat) > timeout.as_millis() as u64
+                }
+                None => false,
+            })
+            .map(|reader| Reader::from(reader.to_owned()))
+            .collect();
+        for reader in &dead_readers {
+            warn!("Reader {:?} missed its heartbeats, marking it offline", reader);
+            ReaderGroupState::remove_reader_internal_default(table, reader)?;
+        }
+        Ok(dead_readers)
+    }
+
     /// Gets the latest positions for the given reader.
     pub(crate) async fn get_reader_positions(
         &mut self,
@@ -282,6 +399,7 @@ impl ReaderGroupState {
 
     fn remove_reader_internal_default(table: &mut Update, reader: &Reader) -> Result<(), SynchronizerError> {
         let assigned_segments = ReaderGroupState::get_reader_owned_segments_from_table(table, reader)?;
+        ReaderGroupState::reader_checkpointed_internal_all(table, reader, &assigned_segments);
 
         for (segment, pos) in assigned_segments {
             table.insert(
@@ -293,6 +411,9 @@ impl ReaderGroupState {
         }
         table.insert_tombstone(ASSIGNED.to_owned(), reader.to_string())?;
         table.insert_tombstone(DISTANCE.to_owned(), reader.to_string())?;
+        if table.contains_key(HEARTBEATS, &reader.to_string()) {
+            table.insert_tombstone(HEARTBEATS.to_owned(), reader.to_string())?;
+        }
         Ok(())
     }
 
@@ -331,11 +452,18 @@ impl ReaderGroupState {
         owned_segments: &HashMap<ScopedSegment, Offset>,
     ) -> Result<(), SynchronizerError> {
         let assigned_segments = ReaderGroupState::get_reader_owned_segments_from_table(table, reader)?;
+        // update offset using owned_segments
+        let positions: HashMap<ScopedSegment, Offset> = assigned_segments
+            .into_iter()
+            .map(|(segment, pos)| {
+                let offset = owned_segments.get(&segment).map_or(pos, |v| v.to_owned());
+                (segment, offset)
+            })
+            .collect();
+        // a reader going offline will not pass the pending checkpoints, record its positions instead.
+        ReaderGroupState::reader_checkpointed_internal_all(table, reader, &positions);
 
-        for (segment, pos) in assigned_segments {
-            // update offset using owned_segments
-            let offset = owned_segments.get(&segment).map_or(pos, |v| v.to_owned());
-
+        for (segment, offset) in positions {
             table.insert(
                 UNASSIGNED.to_owned(),
                 segment.to_string(),
@@ -345,6 +473,9 @@ impl ReaderGroupState {
         }
         table.insert_tombstone(ASSIGNED.to_owned(), reader.to_string())?;
         table.insert_tombstone(DISTANCE.to_owned(), reader.to_string())?;
+        if table.contains_key(HEARTBEATS, &reader.to_string()) {
+            table.insert_tombstone(HEARTBEATS.to_owned(), reader.to_string())?;
+        }
         Ok(())
     }
 
@@ -365,33 +496,57 @@ impl ReaderGroupState {
             }
             _ => panic!("Fetch updates failed after all retries"),
         }
-        let assigned_segment_map = self.sync.get_inner_map(ASSIGNED);
-        let num_of_readers = assigned_segment_map.len();
-        let mut num_assigned_segments = 0;
-        for v in assigned_segment_map.values() {
-            let segments: HashMap<ScopedSegment, Offset> =
-                deserialize_from(&v.data).expect("deserialize assigned segments");
-            num_assigned_segments += segments.len();
+        ReaderGroupState::compute_segments_to_acquire_or_release_internal(
+            reader,
+            self.custom_assignment_strategy.clone(),
+            self.sync.get_inner_map("config"),
+            self.sync.get_inner_map(ASSIGNED),
+            self.sync.get_inner_map(UNASSIGNED),
+            self.sync.get_inner_map(CHECKPOINTS),
+        )
+    }
+
+    fn compute_segments_to_acquire_or_release_internal(
+        reader: &Reader,
+        custom_assignment_strategy: Option<Arc<dyn SegmentAssignmentStrategy>>,
+        config: HashMap<String, Value>,
+        assigned_segments: HashMap<String, Value>,
+        unassigned_segments: HashMap<String, Value>,
+        checkpoints: HashMap<String, Value>,
+    ) -> Result<isize, ReaderGroupStateError> {
+        // segments do not change hands while a checkpoint is in progress.
+        if ReaderGroupState::is_checkpoint_in_progress(checkpoints) {
+            debug!("checkpoint in progress, reader {:?} keeps its segments", reader);
+            return Ok(0);
         }
-        let num_of_segments = num_assigned_segments + self.sync.get_inner_map(UNASSIGNED).len();
+        let segments_per_reader: HashMap<Reader, usize> = assigned_segments
+            .iter()
+            .map(|(reader, v)| {
+                let segments: HashMap<ScopedSegment, Offset> =
+                    deserialize_from(&v.data).expect("deserialize assigned segments");
+                (Reader::from(reader.to_owned()), segments.len())
+            })
+            .collect();
+        let assignment = SegmentAssignment {
+            segments_per_reader,
+            unassigned_segments: unassigned_segments.len(),
+        };
         debug!(
             " number of segments {:?}, number of readers {:?} in reader group state",
-            num_of_segments, num_of_readers
+            assignment.num_of_segments(),
+            assignment.num_of_readers()
         );
-        let num_segments_per_reader = num_of_segments / num_of_readers;
-        let expected_segment_count_per_reader = if num_of_segments % num_of_readers == 0 {
-            num_segments_per_reader
-        } else {
-            num_segments_per_reader + 1
-        };
-        let current_segment_count = assigned_segment_map.get(&reader.name).map(|v| {
-            let seg: HashMap<ScopedSegment, Offset> =
-                deserialize_from(&v.data).expect("deserialize of assigned segments");
-            seg.len()
-        });
-        let expected: isize = expected_segment_count_per_reader.try_into().unwrap();
-        let current: isize = current_segment_count.unwrap_or_default().try_into().unwrap();
-        Ok(expected - current)
+        if let Some(strategy) = custom_assignment_strategy {
+            return Ok(strategy.segments_to_acquire_or_release(reader, &assignment));
+        }
+        let strategy = config
+            .get(DEFAULT_INNER_KEY)
+            .map_or_else(AssignmentStrategy::default, |v| {
+                let config: ReaderGroupConfigVersioned =
+                    deserialize_from(&v.data).expect("deserialize reader group config");
+                config.get_assignment_strategy().clone()
+            });
+        Ok(strategy.segments_to_acquire_or_release(reader, &assignment))
     }
 
     /// Return the list of all segments.
@@ -425,6 +580,64 @@ impl ReaderGroupState {
         set
     }
 
+    /// Return the last known positions of all the segments, including the assigned and unassigned ones.
+    pub(crate) async fn get_segment_positions(&mut self) -> HashMap<ScopedSegment, Offset> {
+        self.sync.fetch_updates().await.expect("should fetch updates");
+        ReaderGroupState::get_segment_positions_internal(
+            self.sync.get_inner_map(ASSIGNED),
+            self.sync.get_inner_map(UNASSIGNED),
+        )
+    }
+
+    fn get_segment_positions_internal(
+        assigned_segments: HashMap<String, Value>,
+        unassigned_segments: HashMap<String, Value>,
+    ) -> HashMap<ScopedSegment, Offset> {
+        let mut positions: HashMap<ScopedSegment, Offset> = HashMap::new();
+        for v in assigned_segments.values() {
+            let segments: HashMap<ScopedSegment, Offset> =
+                deserialize_from(&v.data).expect("deserialize assigned segments");
+            positions.extend(segments);
+        }
+        positions.extend(unassigned_segments.iter().map(|(k, v)| {
+            (
+                ScopedSegment::from(k.as_str()),
+                deserialize_from(&v.data).expect("deserialize offset"),
+            )
+        }));
+        positions
+    }
+
+    /// Return the segments that will be read once all their predecessors are completed.
+    pub(crate) async fn get_future_segments(&mut self) -> HashSet<ScopedSegment> {
+        self.sync.fetch_updates().await.expect("should fetch updates");
+        self.sync
+            .get_inner_map(FUTURE)
+            .keys()
+            .map(|segment| ScopedSegment::from(segment.as_str()))
+            .collect()
+    }
+
+    /// Returns true if the ReaderGroup has no assigned, unassigned or future segments left to read.
+    /// This uses the local copy of the state, which is refreshed by `check_online`.
+    pub(crate) async fn is_read_completely(&mut self) -> bool {
+        ReaderGroupState::is_read_completely_internal(
+            self.sync.get_inner_map(ASSIGNED),
+            self.sync.get_inner_map(UNASSIGNED),
+            self.sync.get_inner_map(FUTURE),
+        )
+    }
+
+    fn is_read_completely_internal(
+        assigned_segments: HashMap<String, Value>,
+        unassigned_segments: HashMap<String, Value>,
+        future_segments: HashMap<String, Value>,
+    ) -> bool {
+        unassigned_segments.is_empty()
+            && future_segments.is_empty()
+            && ReaderGroupState::get_segment_positions_internal(assigned_segments, HashMap::new()).is_empty()
+    }
+
     /// Assign an unassigned segment to a given reader
     pub async fn assign_segment_to_reader(
         &mut self,
@@ -451,6 +664,11 @@ impl ReaderGroupState {
     ) -> Result<Option<String>, SynchronizerError> {
         let mut assigned_segments = ReaderGroupState::get_reader_owned_segments_from_table(table, reader)?;
         let unassigned_segments = ReaderGroupState::get_unassigned_segments_from_table(table);
+        // a reader that has passed the checkpoint would read the segment past the position
+        // recorded in the checkpoint by a pending reader, or the other way around.
+        if ReaderGroupState::is_checkpoint_in_progress(table.get_inner_map(CHECKPOINTS)) {
+            return Ok(None);
+        }
 
         if let Some((segment, offset)) = unassigned_segments.into_iter().next() {
             assigned_segments.insert(segment.clone(), offset);
@@ -540,6 +758,10 @@ impl ReaderGroupState {
                         segment
                     )
                 });
+        // the released segment is not acquired again until the pending checkpoints complete.
+        let mut released = HashMap::new();
+        released.insert(segment.to_owned(), offset.to_owned());
+        ReaderGroupState::record_checkpoint_positions(table, reader, &released, &[]);
         // update table
         table.insert(
             ASSIGNED.to_owned(),
@@ -642,6 +864,18 @@ impl ReaderGroupState {
             .map(|(segment, _set)| segment.to_owned())
             .collect::<Vec<ScopedSegment>>();
 
+        // the successors are not acquired until the pending checkpoints complete.
+        let successors: HashMap<ScopedSegment, Offset> = ready_to_read
+            .iter()
+            .map(|segment| (segment.to_owned(), Offset::new(0)))
+            .collect();
+        ReaderGroupState::record_checkpoint_positions(
+            table,
+            reader,
+            &successors,
+            &[segment_completed.to_owned()],
+        );
+
         for segment in ready_to_read {
             // add ready to read segments to unassigned_segments
             table.insert(
@@ -656,6 +890,317 @@ impl ReaderGroupState {
         Ok(None)
     }
 
+    /// Reset the reader group state to the given config and segments. The online readers are kept
+    /// but all their assigned segments are revoked.
+    pub(crate) async fn reset(
+        &mut self,
+        config: ReaderGroupConfigVersioned,
+        segments_to_offsets: HashMap<ScopedSegment, Offset>,
+    ) -> Result<(), ReaderGroupStateError> {
+        info!(
+            "Resetting reader group state with segments {:?}",
+            segments_to_offsets
+        );
+        self.sync
+            .insert(|table| ReaderGroupState::reset_internal(table, &config, &segments_to_offsets))
+            .await
+            .context(SyncError {
+                error_msg: "reset reader group".to_owned(),
+            })
+    }
+
+    fn reset_internal(
+        table: &mut Update,
+        config: &ReaderGroupConfigVersioned,
+        segments_to_offsets: &HashMap<ScopedSegment, Offset>,
+    ) -> Result<(), SynchronizerError> {
+        table.insert(
+            "config".to_owned(),
+            DEFAULT_INNER_KEY.to_owned(),
+            "ReaderGroupConfigVersioned".to_owned(),
+            Box::new(config.clone()),
+        );
+        let generation = ReaderGroupState::get_generation_internal(table.get_inner_map(GENERATION));
+        table.insert(
+            GENERATION.to_owned(),
+            DEFAULT_INNER_KEY.to_owned(),
+            "u64".to_owned(),
+            Box::new(generation + 1),
+        );
+
+        for reader in table.get_inner_map(ASSIGNED).keys() {
+            let empty_map: HashMap<ScopedSegment, Offset> = HashMap::new();
+            table.insert(
+                ASSIGNED.to_owned(),
+                reader.to_owned(),
+                "HashMap<ScopedSegment, Offset>".to_owned(),
+                Box::new(empty_map),
+            );
+        }
+        // segments that are unassigned again are overwritten below instead of being removed.
+        let new_segments: HashSet<String> = segments_to_offsets.keys().map(|s| s.to_string()).collect();
+        for segment in table.get_inner_map(UNASSIGNED).keys() {
+            if !new_segments.contains(segment) {
+                table.insert_tombstone(UNASSIGNED.to_owned(), segment.to_owned())?;
+            }
+        }
+        for segment in table.get_inner_map(FUTURE).keys() {
+            table.insert_tombstone(FUTURE.to_owned(), segment.to_owned())?;
+        }
+        for checkpoint in table.get_inner_map(CHECKPOINTS).keys() {
+            table.insert_tombstone(CHECKPOINTS.to_owned(), checkpoint.to_owned())?;
+        }
+        for (segment, offset) in segments_to_offsets {
+            table.insert(
+                UNASSIGNED.to_owned(),
+                segment.to_string(),
+                "Offset".to_owned(),
+                Box::new(offset.to_owned()),
+            );
+        }
+        Ok(())
+    }
+
+    /// Returns the number of times the reader group has been reset.
+    /// This uses the local copy of the state, which is refreshed by `check_online`.
+    pub(crate) async fn get_generation(&mut self) -> u64 {
+        ReaderGroupState::get_generation_internal(self.sync.get_inner_map(GENERATION))
+    }
+
+    fn get_generation_internal(generation: HashMap<String, Value>) -> u64 {
+        generation.get(DEFAULT_INNER_KEY).map_or(0, |v| {
+            deserialize_from(&v.data).expect("deserialize reader group generation")
+        })
+    }
+
+    /// Returns the offsets up to which the segments in the end StreamCuts of the reader group config
+    /// should be read. Segments that are not present are read until they are sealed.
+    /// This uses the local copy of the state, which is refreshed by `check_online`.
+    pub(crate) async fn get_end_offsets(&mut self) -> HashMap<ScopedSegment, i64> {
+        ReaderGroupState::get_end_offsets_internal(self.sync.get_inner_map("config"))
+    }
+
+    fn get_end_offsets_internal(config: HashMap<String, Value>) -> HashMap<ScopedSegment, i64> {
+        config.get(DEFAULT_INNER_KEY).map_or_else(HashMap::new, |v| {
+            let config: ReaderGroupConfigVersioned =
+                deserialize_from(&v.data).expect("deserialize reader group config");
+            config.get_end_offsets()
+        })
+    }
+
+    /// Initiate a checkpoint with the given name. All the online readers need to pass the checkpoint
+    /// before it is completed.
+    pub(crate) async fn initiate_checkpoint(&mut self, name: &str) -> Result<(), ReaderGroupStateError> {
+        info!("Initiating checkpoint {}", name);
+        self.sync
+            .insert(|table| ReaderGroupState::initiate_checkpoint_internal(table, name))
+            .await
+            .context(SyncError {
+                error_msg: format!("initiate checkpoint {}", name),
+            })
+    }
+
+    fn initiate_checkpoint_internal(table: &mut Update, name: &str) -> Result<(), SynchronizerError> {
+        let checkpoints = ReaderGroupState::get_checkpoints_from_table(table);
+        if checkpoints.contains_key(name) {
+            return Err(SynchronizerError::SyncPreconditionError {
+                error_msg: format!(
+                    "Failed to initiate checkpoint {}: checkpoint already exists",
+                    name
+                ),
+            });
+        }
+        let ordinal = checkpoints
+            .values()
+            .map(|cp| cp.ordinal + 1)
+            .max()
+            .unwrap_or_default();
+        let readers_pending = table.get_inner_map(ASSIGNED).keys().cloned().collect();
+        // segments that are not owned by any reader are recorded at their current offsets.
+        let positions = ReaderGroupState::get_unassigned_segments_from_table(table);
+        table.insert(
+            CHECKPOINTS.to_owned(),
+            name.to_owned(),
+            "CheckpointState".to_owned(),
+            Box::new(CheckpointState {
+                ordinal,
+                readers_pending,
+                positions,
+            }),
+        );
+        Ok(())
+    }
+
+    /// Returns the positions recorded by the checkpoint if all the readers have passed it.
+    pub(crate) async fn get_completed_checkpoint(
+        &mut self,
+        name: &str,
+    ) -> Result<Option<HashMap<ScopedSegment, Offset>>, ReaderGroupStateError> {
+        self.sync
+            .fetch_updates()
+            .await
+            .map_err(|e| SynchronizerError::SyncTableError {
+                operation: "fetch updates".to_owned(),
+                source: e,
+            })
+            .context(SyncError {
+                error_msg: format!("get checkpoint {}", name),
+            })?;
+        ReaderGroupState::get_completed_checkpoint_internal(name, self.sync.get(CHECKPOINTS, name).cloned())
+            .context(SyncError {
+                error_msg: format!("get checkpoint {}", name),
+            })
+    }
+
+    fn get_completed_checkpoint_internal(
+        name: &str,
+        value: Option<Value>,
+    ) -> Result<Option<HashMap<ScopedSegment, Offset>>, SynchronizerError> {
+        let value = value.ok_or(SynchronizerError::SyncPreconditionError {
+            error_msg: format!("checkpoint {} does not exist", name),
+        })?;
+        let checkpoint: CheckpointState = deserialize_from(&value.data).expect("deserialize checkpoint");
+        if checkpoint.readers_pending.is_empty() {
+            Ok(Some(checkpoint.positions))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Removes the checkpoint from the reader group state.
+    pub(crate) async fn remove_checkpoint(&mut self, name: &str) -> Result<(), ReaderGroupStateError> {
+        self.sync
+            .insert(|table| table.insert_tombstone(CHECKPOINTS.to_owned(), name.to_owned()))
+            .await
+            .context(SyncError {
+                error_msg: format!("remove checkpoint {}", name),
+            })
+    }
+
+    /// Returns the oldest checkpoint that the given reader has not passed yet.
+    /// This uses the local copy of the state, which is refreshed by `check_online`.
+    pub(crate) async fn get_checkpoint_for_reader(&mut self, reader: &Reader) -> Option<String> {
+        ReaderGroupState::get_checkpoint_for_reader_internal(self.sync.get_inner_map(CHECKPOINTS), reader)
+    }
+
+    fn get_checkpoint_for_reader_internal(
+        checkpoints: HashMap<String, Value>,
+        reader: &Reader,
+    ) -> Option<String> {
+        checkpoints
+            .into_iter()
+            .map(|(name, v)| {
+                let checkpoint: CheckpointState = deserialize_from(&v.data).expect("deserialize checkpoint");
+                (name, checkpoint)
+            })
+            .filter(|(_name, checkpoint)| checkpoint.readers_pending.contains(&reader.name))
+            .min_by_key(|(_name, checkpoint)| checkpoint.ordinal)
+            .map(|(name, _checkpoint)| name)
+    }
+
+    /// Records the positions of the given reader in the checkpoint and marks the checkpoint
+    /// as passed by the reader.
+    pub(crate) async fn reader_checkpointed(
+        &mut self,
+        reader: &Reader,
+        name: &str,
+        positions: HashMap<ScopedSegment, Offset>,
+    ) -> Result<(), ReaderGroupStateError> {
+        debug!(
+            "Reader {:?} passed checkpoint {} at {:?}",
+            reader, name, positions
+        );
+        self.sync
+            .insert(|table| ReaderGroupState::reader_checkpointed_internal(table, reader, name, &positions))
+            .await
+            .context(SyncError {
+                error_msg: format!("reader {:?} pass checkpoint {}", reader, name),
+            })
+    }
+
+    fn reader_checkpointed_internal(
+        table: &mut Update,
+        reader: &Reader,
+        name: &str,
+        positions: &HashMap<ScopedSegment, Offset>,
+    ) -> Result<(), SynchronizerError> {
+        let value = table
+            .get(CHECKPOINTS, name)
+            .ok_or(SynchronizerError::SyncPreconditionError {
+                error_msg: format!("checkpoint {} does not exist", name),
+            })?;
+        let mut checkpoint: CheckpointState = deserialize_from(&value.data).expect("deserialize checkpoint");
+        if checkpoint.readers_pending.remove(&reader.name) {
+            checkpoint.positions.extend(positions.clone());
+            table.insert(
+                CHECKPOINTS.to_owned(),
+                name.to_owned(),
+                "CheckpointState".to_owned(),
+                Box::new(checkpoint),
+            );
+        }
+        Ok(())
+    }
+
+    // Marks all the checkpoints pending on the reader as passed at the given positions.
+    fn reader_checkpointed_internal_all(
+        table: &mut Update,
+        reader: &Reader,
+        positions: &HashMap<ScopedSegment, Offset>,
+    ) {
+        for (name, mut checkpoint) in ReaderGroupState::get_checkpoints_from_table(table) {
+            if checkpoint.readers_pending.remove(&reader.name) {
+                checkpoint.positions.extend(positions.clone());
+                table.insert(
+                    CHECKPOINTS.to_owned(),
+                    name,
+                    "CheckpointState".to_owned(),
+                    Box::new(checkpoint),
+                );
+            }
+        }
+    }
+
+    // Records segment positions that leave the reader's ownership in the checkpoints the reader
+    // has not passed yet.
+    fn record_checkpoint_positions(
+        table: &mut Update,
+        reader: &Reader,
+        positions: &HashMap<ScopedSegment, Offset>,
+        completed: &[ScopedSegment],
+    ) {
+        for (name, mut checkpoint) in ReaderGroupState::get_checkpoints_from_table(table) {
+            if checkpoint.readers_pending.contains(&reader.name) {
+                for segment in completed {
+                    checkpoint.positions.remove(segment);
+                }
+                checkpoint.positions.extend(positions.clone());
+                table.insert(
+                    CHECKPOINTS.to_owned(),
+                    name,
+                    "CheckpointState".to_owned(),
+                    Box::new(checkpoint),
+                );
+            }
+        }
+    }
+
+    // A checkpoint is in progress until all the readers have passed it.
+    fn is_checkpoint_in_progress(checkpoints: HashMap<String, Value>) -> bool {
+        checkpoints.values().any(|v| {
+            let checkpoint: CheckpointState = deserialize_from(&v.data).expect("deserialize checkpoint");
+            !checkpoint.readers_pending.is_empty()
+        })
+    }
+
+    fn get_checkpoints_from_table(table: &mut Update) -> HashMap<String, CheckpointState> {
+        table
+            .get_inner_map(CHECKPOINTS)
+            .into_iter()
+            .map(|(k, v)| (k, deserialize_from(&v.data).expect("deserialize checkpoint")))
+            .collect::<HashMap<String, CheckpointState>>()
+    }
+
     fn get_reader_owned_segments_from_table(
         table: &mut Update,
         reader: &Reader,
@@ -721,13 +1266,34 @@ pub struct Offset {
     pub read: i64,
 }
 
+/// The state of a checkpoint stored in the reader group state.
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub(crate) struct CheckpointState {
+    /// Used to order the checkpoints by the time they are initiated.
+    ordinal: u64,
+    /// The readers that have not passed the checkpoint yet.
+    readers_pending: HashSet<String>,
+    /// The positions recorded by the readers that have passed the checkpoint.
+    positions: HashMap<ScopedSegment, Offset>,
+}
+
+// The wall clock time is used for heartbeats since they are compared across processes.
+fn current_time_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system time before UNIX epoch")
+        .as_millis() as u64
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::event::reader_group::ReaderGroupConfigBuilder;
+    use crate::event::segment_assignment::WeightedStrategy;
     use crate::sync::synchronizer::{serialize, Value};
     use lazy_static::*;
     use ordered_float::OrderedFloat;
-    use pravega_client_shared::{Scope, Segment, Stream};
+    use pravega_client_shared::{Scope, ScopedStream, Segment, Stream};
 
     lazy_static! {
         static ref READER: Reader = Reader::from("test".to_owned());
@@ -908,4 +1474,303 @@ mod test {
         }
         assert_eq!(table.get_inner_map(UNASSIGNED).len(), 2);
     }
+
+    #[test]
+    fn test_reader_group_state_checkpoint() {
+        let mut table = set_up();
+        ReaderGroupState::add_reader_internal(&mut table, &READER).expect("add reader");
+        ReaderGroupState::assign_segment_to_reader_internal(&mut table, &READER)
+            .expect("assign segment to reader");
+
+        ReaderGroupState::initiate_checkpoint_internal(&mut table, "cp1").expect("initiate checkpoint");
+        let res = ReaderGroupState::initiate_checkpoint_internal(&mut table, "cp1");
+        assert!(
+            matches!(res, Err(SynchronizerError::SyncPreconditionError { .. })),
+            "checkpoint name should be unique"
+        );
+        assert_eq!(
+            ReaderGroupState::get_checkpoint_for_reader_internal(table.get_inner_map(CHECKPOINTS), &READER),
+            Some("cp1".to_owned())
+        );
+        let completed = ReaderGroupState::get_completed_checkpoint_internal(
+            "cp1",
+            table.get(CHECKPOINTS, "cp1").cloned(),
+        )
+        .expect("get checkpoint");
+        assert!(completed.is_none(), "reader has not passed the checkpoint");
+
+        // the reader passes the checkpoint after reading from the segment.
+        let mut positions = HashMap::new();
+        positions.insert(SEGMENT_TEST.clone(), Offset::new(10));
+        ReaderGroupState::reader_checkpointed_internal(&mut table, &READER, "cp1", &positions)
+            .expect("reader checkpointed");
+        assert!(ReaderGroupState::get_checkpoint_for_reader_internal(
+            table.get_inner_map(CHECKPOINTS),
+            &READER
+        )
+        .is_none());
+        let completed = ReaderGroupState::get_completed_checkpoint_internal(
+            "cp1",
+            table.get(CHECKPOINTS, "cp1").cloned(),
+        )
+        .expect("get checkpoint");
+        assert_eq!(completed, Some(positions));
+
+        // a reader going offline passes the pending checkpoints at its last positions.
+        ReaderGroupState::initiate_checkpoint_internal(&mut table, "cp2").expect("initiate checkpoint");
+        let mut last_positions = HashMap::new();
+        last_positions.insert(SEGMENT_TEST.clone(), Offset::new(20));
+        ReaderGroupState::remove_reader_internal(&mut table, &READER, &last_positions)
+            .expect("remove online reader");
+        let completed = ReaderGroupState::get_completed_checkpoint_internal(
+            "cp2",
+            table.get(CHECKPOINTS, "cp2").cloned(),
+        )
+        .expect("get checkpoint");
+        assert_eq!(completed, Some(last_positions));
+    }
+
+    #[test]
+    fn test_reader_group_state_checkpoint_blocks_acquire() {
+        let mut table = set_up();
+        let reader2 = Reader::from("reader2".to_owned());
+        ReaderGroupState::add_reader_internal(&mut table, &READER).expect("add reader");
+        ReaderGroupState::add_reader_internal(&mut table, &reader2).expect("add reader");
+        ReaderGroupState::assign_segment_to_reader_internal(&mut table, &READER)
+            .expect("assign segment to reader");
+        ReaderGroupState::initiate_checkpoint_internal(&mut table, "cp").expect("initiate checkpoint");
+
+        // the first reader passes the checkpoint and then releases its segment further on.
+        let mut positions = HashMap::new();
+        positions.insert(SEGMENT_TEST.clone(), Offset::new(30));
+        ReaderGroupState::reader_checkpointed_internal(&mut table, &READER, "cp", &positions)
+            .expect("reader checkpointed");
+        ReaderGroupState::release_segment_internal(&mut table, &READER, &SEGMENT_TEST, &Offset::new(50))
+            .expect("release segment");
+
+        // the pending reader neither acquires the segment nor rebalances.
+        let acquired = ReaderGroupState::assign_segment_to_reader_internal(&mut table, &reader2)
+            .expect("assign segment to reader");
+        assert!(acquired.is_none());
+        let count = ReaderGroupState::compute_segments_to_acquire_or_release_internal(
+            &reader2,
+            None,
+            table.get_inner_map("config"),
+            table.get_inner_map(ASSIGNED),
+            table.get_inner_map(UNASSIGNED),
+            table.get_inner_map(CHECKPOINTS),
+        )
+        .expect("compute segments");
+        assert_eq!(count, 0);
+
+        // the checkpoint keeps the position of the reader that passed it.
+        ReaderGroupState::reader_checkpointed_internal(&mut table, &reader2, "cp", &HashMap::new())
+            .expect("reader checkpointed");
+        let completed =
+            ReaderGroupState::get_completed_checkpoint_internal("cp", table.get(CHECKPOINTS, "cp").cloned())
+                .expect("get checkpoint");
+        assert_eq!(completed, Some(positions));
+
+        // the segment is acquired once the checkpoint has completed.
+        let acquired = ReaderGroupState::assign_segment_to_reader_internal(&mut table, &reader2)
+            .expect("assign segment to reader");
+        assert_eq!(acquired, Some(SEGMENT_TEST.to_string()));
+    }
+
+    #[test]
+    fn test_reader_group_state_segment_positions() {
+        let mut table = set_up();
+        ReaderGroupState::add_reader_internal(&mut table, &READER).expect("add reader");
+
+        let mut segment1 = SEGMENT_TEST.clone();
+        segment1.segment.number = 1;
+        table.insert(
+            UNASSIGNED.to_owned(),
+            segment1.to_string(),
+            "Offset".to_owned(),
+            Box::new(Offset::new(5)),
+        );
+        ReaderGroupState::assign_segment_to_reader_internal(&mut table, &READER)
+            .expect("assign segment to reader");
+        let assigned =
+            ReaderGroupState::get_reader_positions_internal(&READER, table.get_inner_map(ASSIGNED))
+                .expect("get reader positions");
+        let mut latest_positions = HashMap::new();
+        for segment in assigned.keys() {
+            latest_positions.insert(segment.clone(), Offset::new(100));
+        }
+        ReaderGroupState::update_reader_positions_internal(&mut table, &READER, &latest_positions)
+            .expect("update reader position");
+
+        let positions = ReaderGroupState::get_segment_positions_internal(
+            table.get_inner_map(ASSIGNED),
+            table.get_inner_map(UNASSIGNED),
+        );
+        assert_eq!(
+            positions.len(),
+            2,
+            "should contain assigned and unassigned segments"
+        );
+        for (segment, offset) in positions {
+            if assigned.contains_key(&segment) {
+                assert_eq!(offset, Offset::new(100));
+            } else if segment == segment1 {
+                assert_eq!(offset, Offset::new(5));
+            } else {
+                assert_eq!(offset, Offset::new(0));
+            }
+        }
+    }
+
+    #[test]
+    fn test_reader_group_state_read_completely() {
+        let mut table = set_up();
+        ReaderGroupState::add_reader_internal(&mut table, &READER).expect("add reader");
+        let is_read_completely = |table: &Update| {
+            ReaderGroupState::is_read_completely_internal(
+                table.get_inner_map(ASSIGNED),
+                table.get_inner_map(UNASSIGNED),
+                table.get_inner_map(FUTURE),
+            )
+        };
+        assert!(!is_read_completely(&table));
+
+        ReaderGroupState::assign_segment_to_reader_internal(&mut table, &READER)
+            .expect("assign segment to reader");
+        assert!(!is_read_completely(&table));
+
+        // the last segment is completed and has no successors.
+        ReaderGroupState::segment_completed_internal(&mut table, &READER, &SEGMENT_TEST, &im::HashMap::new())
+            .expect("complete segment");
+        assert!(is_read_completely(&table));
+    }
+
+    #[test]
+    fn test_reader_group_state_reset() {
+        let mut table = set_up();
+        ReaderGroupState::add_reader_internal(&mut table, &READER).expect("add reader");
+        ReaderGroupState::assign_segment_to_reader_internal(&mut table, &READER)
+            .expect("assign segment to reader");
+        ReaderGroupState::initiate_checkpoint_internal(&mut table, "cp").expect("initiate checkpoint");
+        assert_eq!(
+            ReaderGroupState::get_generation_internal(table.get_inner_map(GENERATION)),
+            0
+        );
+
+        let mut segment1 = SEGMENT_TEST.clone();
+        segment1.segment.number = 1;
+        let mut segments = HashMap::new();
+        segments.insert(segment1.clone(), Offset::new(50));
+        ReaderGroupState::reset_internal(
+            &mut table,
+            &ReaderGroupConfigVersioned::V1(Default::default()),
+            &segments,
+        )
+        .expect("reset reader group");
+
+        assert_eq!(
+            ReaderGroupState::get_generation_internal(table.get_inner_map(GENERATION)),
+            1
+        );
+        // the reader stays online without any segments.
+        let owned = ReaderGroupState::get_reader_positions_internal(&READER, table.get_inner_map(ASSIGNED))
+            .expect("get reader positions");
+        assert!(owned.is_empty());
+        let unassigned = ReaderGroupState::get_unassigned_segments_from_table(&mut table);
+        assert_eq!(unassigned.len(), 1);
+        assert_eq!(unassigned.get(&segment1), Some(&Offset::new(50)));
+        assert!(table.get_inner_map(CHECKPOINTS).is_empty());
+    }
+
+    #[test]
+    fn test_reader_group_state_assignment_strategy() {
+        let mut table = set_up();
+        let reader2 = Reader::from("reader2".to_owned());
+        ReaderGroupState::add_reader_internal(&mut table, &READER).expect("add reader");
+        ReaderGroupState::add_reader_internal(&mut table, &reader2).expect("add reader");
+        for number in 1..4 {
+            let mut segment = SEGMENT_TEST.clone();
+            segment.segment.number = number;
+            table.insert(
+                UNASSIGNED.to_owned(),
+                segment.to_string(),
+                "Offset".to_owned(),
+                Box::new(Offset::new(0)),
+            );
+        }
+        let compute = |table: &Update, custom: Option<Arc<dyn SegmentAssignmentStrategy>>| {
+            ReaderGroupState::compute_segments_to_acquire_or_release_internal(
+                &READER,
+                custom,
+                table.get_inner_map("config"),
+                table.get_inner_map(ASSIGNED),
+                table.get_inner_map(UNASSIGNED),
+                table.get_inner_map(CHECKPOINTS),
+            )
+            .expect("compute segments")
+        };
+        // the segments are evenly distributed without a config.
+        assert_eq!(compute(&table, None), 2);
+
+        let mut weights = HashMap::new();
+        weights.insert(READER.name.clone(), 1);
+        let config = ReaderGroupConfigBuilder::default()
+            .add_stream(ScopedStream::from("scope/scope"))
+            .set_assignment_strategy(AssignmentStrategy::Weighted(
+                WeightedStrategy::new(weights).with_default_weight(3),
+            ))
+            .build();
+        table.insert(
+            "config".to_owned(),
+            DEFAULT_INNER_KEY.to_owned(),
+            "ReaderGroupConfigVersioned".to_owned(),
+            Box::new(config.config),
+        );
+        assert_eq!(compute(&table, None), 1);
+
+        // a custom strategy overrides the persisted one.
+        struct Idle;
+        impl SegmentAssignmentStrategy for Idle {
+            fn segments_to_acquire_or_release(
+                &self,
+                _reader: &Reader,
+                _assignment: &SegmentAssignment,
+            ) -> isize {
+                0
+            }
+        }
+        assert_eq!(compute(&table, Some(Arc::new(Idle))), 0);
+    }
+
+    #[test]
+    fn test_reader_group_state_evict_dead_readers() {
+        let mut table = set_up();
+        let reader2 = Reader::from("reader2".to_owned());
+        ReaderGroupState::add_reader_internal(&mut table, &READER).expect("add reader");
+        ReaderGroupState::add_reader_internal(&mut table, &reader2).expect("add reader");
+        ReaderGroupState::assign_segment_to_reader_internal(&mut table, &READER)
+            .expect("assign segment to reader");
+        ReaderGroupState::reader_heartbeat_internal(&mut table, &READER, 1000).expect("heartbeat");
+        ReaderGroupState::reader_heartbeat_internal(&mut table, &reader2, 5000).expect("heartbeat");
+
+        // no reader is evicted while the heartbeats are within the timeout.
+        let evicted =
+            ReaderGroupState::evict_dead_readers_internal(&mut table, 6000, Duration::from_millis(5000))
+                .expect("evict dead readers");
+        assert!(evicted.is_empty());
+
+        let evicted =
+            ReaderGroupState::evict_dead_readers_internal(&mut table, 7000, Duration::from_millis(5000))
+                .expect("evict dead readers");
+        assert_eq!(evicted, vec![READER.clone()]);
+        let online = ReaderGroupState::get_online_readers_internal(table.get_inner_map(ASSIGNED));
+        assert_eq!(online, vec![reader2.clone()]);
+        assert!(!table.contains_key(HEARTBEATS, &READER.to_string()));
+        // the segment owned by the dead reader is unassigned at its last known offset.
+        let unassigned = ReaderGroupState::get_unassigned_segments_from_table(&mut table);
+        assert_eq!(unassigned.get(&SEGMENT_TEST), Some(&Offset::new(0)));
+
+        // a reader that is offline cannot record heartbeats.
+        assert!(ReaderGroupState::reader_heartbeat_internal(&mut table, &READER, 8000).is_err());
+    }
 }
@@ -0,0 +1,84 @@
+//
+// Copyright (c) Dell Inc., or its subsidiaries. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+
+use pravega_client_shared::StreamSegments;
+use std::fmt::Debug;
+
+/// Decides which segment of the stream an event is written to based on its routing key.
+///
+/// The routing key is mapped to a key in the range [0, 1] and the event is written to the segment
+/// whose key range contains it. The events with the same routing key are written to the same
+/// segment, so they are read in order.
+pub trait RoutingStrategy: Debug + Send + Sync {
+    /// Returns the key of the routing key, which must be in the range [0, 1]. An event whose key is
+    /// out of the range or NaN fails with an `InvalidInput` error.
+    fn key(&self, routing_key: &str) -> f64;
+}
+
+/// Hashes the routing key with murmur3 the same way as the Java client, so that both clients write
+/// the events of a routing key to the same segment. This is the default strategy.
+#[derive(Debug, Clone, Default)]
+pub struct HashRoutingStrategy;
+
+impl RoutingStrategy for HashRoutingStrategy {
+    fn key(&self, routing_key: &str) -> f64 {
+        StreamSegments::hash_routing_key(routing_key)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ordered_float::OrderedFloat;
+    use pravega_client_shared::{ScopedSegment, SegmentWithRange};
+    use std::collections::BTreeMap;
+
+    // Routes the routing keys made of a number in [0, 100) to the matching percentile of the key space.
+    #[derive(Debug)]
+    struct PercentileStrategy;
+
+    impl RoutingStrategy for PercentileStrategy {
+        fn key(&self, routing_key: &str) -> f64 {
+            routing_key.parse::<f64>().expect("numeric routing key") / 100.0
+        }
+    }
+
+    #[test]
+    fn test_routing_strategies() {
+        let mut segments = BTreeMap::new();
+        for (number, max_key) in [0.5, 1.0].iter().enumerate() {
+            let segment = SegmentWithRange {
+                scoped_segment: ScopedSegment::from(format!("scope/stream/{}", number).as_str()),
+                min_key: OrderedFloat(max_key - 0.5),
+                max_key: OrderedFloat(*max_key),
+            };
+            segments.insert(OrderedFloat(*max_key), segment);
+        }
+        let segments = StreamSegments::new(segments);
+
+        let strategy = HashRoutingStrategy;
+        for routing_key in ["a", "routing key", "42"].iter() {
+            assert_eq!(
+                segments.get_segment(strategy.key(routing_key)),
+                segments.get_segment_for_string(routing_key)
+            );
+        }
+
+        let strategy = PercentileStrategy;
+        assert_eq!(
+            segments.get_segment(strategy.key("10")),
+            &ScopedSegment::from("scope/stream/0")
+        );
+        assert_eq!(
+            segments.get_segment(strategy.key("75")),
+            &ScopedSegment::from("scope/stream/1")
+        );
+    }
+}
@@ -0,0 +1,280 @@
+//
+// Copyright (c) Dell Inc., or its subsidiaries. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+
+use pravega_client_shared::Reader;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+/// Decides how the segments of a ReaderGroup are distributed among its online readers.
+///
+/// A reader periodically asks the strategy how many segments it should acquire or release given
+/// the current assignment of the ReaderGroup. Besides the strategies of this module, a user defined
+/// strategy can be configured with [`ReaderGroupConfigBuilder::set_custom_assignment_strategy`].
+///
+/// [`ReaderGroupConfigBuilder::set_custom_assignment_strategy`]: crate::event::reader_group::ReaderGroupConfigBuilder::set_custom_assignment_strategy
+pub trait SegmentAssignmentStrategy: Send + Sync {
+    /// Returns the number of segments the reader should acquire if positive, or release if negative.
+    fn segments_to_acquire_or_release(&self, reader: &Reader, assignment: &SegmentAssignment) -> isize;
+}
+
+/// A snapshot of how the segments of a ReaderGroup are assigned to its online readers.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SegmentAssignment {
+    /// The number of segments owned by each online reader.
+    pub segments_per_reader: HashMap<Reader, usize>,
+    /// The number of segments that are not assigned to any reader.
+    pub unassigned_segments: usize,
+}
+
+impl SegmentAssignment {
+    /// The number of segments that can be read, including the unassigned ones.
+    pub fn num_of_segments(&self) -> usize {
+        self.segments_per_reader.values().sum::<usize>() + self.unassigned_segments
+    }
+
+    /// The number of online readers.
+    pub fn num_of_readers(&self) -> usize {
+        self.segments_per_reader.len()
+    }
+
+    /// The number of segments owned by the given reader.
+    pub fn owned_by(&self, reader: &Reader) -> usize {
+        self.segments_per_reader.get(reader).copied().unwrap_or_default()
+    }
+
+    // The number of segments each reader owns when they are evenly distributed, rounded up.
+    fn fair_share(&self) -> usize {
+        self.num_of_segments().div_ceil(self.num_of_readers())
+    }
+}
+
+/// Distributes the segments evenly among the readers. This is the default strategy.
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, Default)]
+pub struct FairShareStrategy;
+
+impl SegmentAssignmentStrategy for FairShareStrategy {
+    fn segments_to_acquire_or_release(&self, reader: &Reader, assignment: &SegmentAssignment) -> isize {
+        assignment.fair_share() as isize - assignment.owned_by(reader) as isize
+    }
+}
+
+/// Distributes the segments in proportion to the capacity of the readers.
+///
+/// A reader with a weight of 2 owns twice as many segments as a reader with a weight of 1.
+/// Readers without a configured weight use the default weight.
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct WeightedStrategy {
+    weights: HashMap<String, u32>,
+    default_weight: u32,
+}
+
+impl WeightedStrategy {
+    /// Create a weighted strategy from the weight of each reader, readers which are not listed
+    /// have a weight of 1.
+    /// This method panics if a weight is zero.
+    pub fn new(weights: HashMap<String, u32>) -> Self {
+        assert!(
+            weights.values().all(|weight| *weight > 0),
+            "The weight of a reader should be positive"
+        );
+        WeightedStrategy {
+            weights,
+            default_weight: 1,
+        }
+    }
+
+    /// Set the weight of the readers which are not listed.
+    /// This method panics if the weight is zero.
+    pub fn with_default_weight(mut self, default_weight: u32) -> Self {
+        assert!(default_weight > 0, "The weight of a reader should be positive");
+        self.default_weight = default_weight;
+        self
+    }
+
+    fn weight_of(&self, reader: &Reader) -> u64 {
+        self.weights
+            .get(&reader.name)
+            .copied()
+            .unwrap_or(self.default_weight) as u64
+    }
+}
+
+impl SegmentAssignmentStrategy for WeightedStrategy {
+    fn segments_to_acquire_or_release(&self, reader: &Reader, assignment: &SegmentAssignment) -> isize {
+        let total_weight: u64 = assignment
+            .segments_per_reader
+            .keys()
+            .map(|r| self.weight_of(r))
+            .sum();
+        let current = assignment.owned_by(reader) as isize;
+        if total_weight == 0 {
+            return -current;
+        }
+        // round up so that all the segments are assigned.
+        let weighted = assignment.num_of_segments() as u64 * self.weight_of(reader);
+        let expected = weighted.div_ceil(total_weight) as isize;
+        expected - current
+    }
+}
+
+/// Keeps the segments with the readers that own them to minimise segment movement when readers
+/// join or leave the ReaderGroup.
+///
+/// A reader acquires segments up to its fair share but only releases segments once it owns more
+/// than `max_imbalance` segments above its fair share. As a result a reader joining the group may
+/// stay idle until the imbalance exceeds `max_imbalance`.
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct StickyStrategy {
+    max_imbalance: usize,
+}
+
+impl StickyStrategy {
+    /// Create a sticky strategy which tolerates readers owning up to `max_imbalance` segments
+    /// more than their fair share.
+    pub fn new(max_imbalance: usize) -> Self {
+        StickyStrategy { max_imbalance }
+    }
+}
+
+impl SegmentAssignmentStrategy for StickyStrategy {
+    fn segments_to_acquire_or_release(&self, reader: &Reader, assignment: &SegmentAssignment) -> isize {
+        let fair_share = assignment.fair_share() as isize;
+        let tolerated = fair_share + self.max_imbalance as isize;
+        let current = assignment.owned_by(reader) as isize;
+        if current > tolerated {
+            tolerated - current
+        } else if current < fair_share {
+            fair_share - current
+        } else {
+            0
+        }
+    }
+}
+
+// A user defined strategy configured on a ReaderGroupConfig. It is not persisted in the state of the
+// ReaderGroup, two configs are equal only if they share the same strategy.
+#[derive(Clone)]
+pub(crate) struct CustomAssignmentStrategy(pub(crate) Arc<dyn SegmentAssignmentStrategy>);
+
+impl fmt::Debug for CustomAssignmentStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("CustomAssignmentStrategy")
+    }
+}
+
+impl PartialEq for CustomAssignmentStrategy {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+/// The segment assignment strategy configured on a ReaderGroupConfig.
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub enum AssignmentStrategy {
+    FairShare(FairShareStrategy),
+    Weighted(WeightedStrategy),
+    Sticky(StickyStrategy),
+}
+
+impl Default for AssignmentStrategy {
+    fn default() -> Self {
+        AssignmentStrategy::FairShare(FairShareStrategy)
+    }
+}
+
+impl SegmentAssignmentStrategy for AssignmentStrategy {
+    fn segments_to_acquire_or_release(&self, reader: &Reader, assignment: &SegmentAssignment) -> isize {
+        match self {
+            AssignmentStrategy::FairShare(s) => s.segments_to_acquire_or_release(reader, assignment),
+            AssignmentStrategy::Weighted(s) => s.segments_to_acquire_or_release(reader, assignment),
+            AssignmentStrategy::Sticky(s) => s.segments_to_acquire_or_release(reader, assignment),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn assignment(owned: &[(&str, usize)], unassigned_segments: usize) -> SegmentAssignment {
+        SegmentAssignment {
+            segments_per_reader: owned
+                .iter()
+                .map(|(reader, count)| (Reader::from(reader.to_string()), *count))
+                .collect(),
+            unassigned_segments,
+        }
+    }
+
+    #[test]
+    fn test_fair_share_strategy() {
+        let strategy = FairShareStrategy;
+        let r1 = Reader::from("r1".to_string());
+        let r2 = Reader::from("r2".to_string());
+        assert_eq!(
+            strategy.segments_to_acquire_or_release(&r1, &assignment(&[("r1", 0), ("r2", 0)], 5)),
+            3
+        );
+        assert_eq!(
+            strategy.segments_to_acquire_or_release(&r2, &assignment(&[("r1", 4), ("r2", 0)], 0)),
+            2
+        );
+        assert_eq!(
+            strategy.segments_to_acquire_or_release(&r1, &assignment(&[("r1", 4), ("r2", 0)], 0)),
+            -2
+        );
+    }
+
+    #[test]
+    fn test_weighted_strategy() {
+        let mut weights = HashMap::new();
+        weights.insert("big".to_string(), 3);
+        let strategy = WeightedStrategy::new(weights);
+        let big = Reader::from("big".to_string());
+        let small = Reader::from("small".to_string());
+        let state = assignment(&[("big", 0), ("small", 0)], 8);
+        assert_eq!(strategy.segments_to_acquire_or_release(&big, &state), 6);
+        assert_eq!(strategy.segments_to_acquire_or_release(&small, &state), 2);
+
+        let state = assignment(&[("big", 2), ("small", 6)], 0);
+        assert_eq!(strategy.segments_to_acquire_or_release(&big, &state), 4);
+        assert_eq!(strategy.segments_to_acquire_or_release(&small, &state), -4);
+
+        let strategy = WeightedStrategy::new(HashMap::new()).with_default_weight(3);
+        assert_eq!(strategy.segments_to_acquire_or_release(&small, &state), -2);
+    }
+
+    #[test]
+    #[should_panic(expected = "The weight of a reader should be positive")]
+    fn test_weighted_strategy_zero_weight() {
+        let mut weights = HashMap::new();
+        weights.insert("idle".to_string(), 0);
+        WeightedStrategy::new(weights);
+    }
+
+    #[test]
+    fn test_sticky_strategy() {
+        let strategy = StickyStrategy::new(1);
+        let r1 = Reader::from("r1".to_string());
+        let r3 = Reader::from("r3".to_string());
+        // a new reader joins, the others own one segment above their fair share and keep it.
+        let state = assignment(&[("r1", 3), ("r2", 3), ("r3", 0)], 0);
+        assert_eq!(strategy.segments_to_acquire_or_release(&r1, &state), 0);
+        assert_eq!(strategy.segments_to_acquire_or_release(&r3, &state), 2);
+        // a reader owning too many segments releases down to the tolerated imbalance.
+        let state = assignment(&[("r1", 6), ("r3", 0)], 0);
+        assert_eq!(strategy.segments_to_acquire_or_release(&r1, &state), -2);
+        // unassigned segments are acquired up to the fair share.
+        let state = assignment(&[("r1", 1), ("r3", 2)], 3);
+        assert_eq!(strategy.segments_to_acquire_or_release(&r1, &state), 2);
+    }
+}
@@ -0,0 +1,156 @@
+//
+// Copyright (c) Dell Inc., or its subsidiaries. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+
+use bincode2::Error as BincodeError;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_cbor::Error as CborError;
+use serde_json::Error as JsonError;
+use snafu::{ResultExt, Snafu};
+
+/// Converts an application type into the bytes of an event.
+///
+/// Implementations are used by [`TypedEventWriter`] to turn a typed value into the payload
+/// that is written to the stream.
+///
+/// [`TypedEventWriter`]: crate::event::typed_writer::TypedEventWriter
+pub trait Serializer<T> {
+    fn serialize(&self, value: &T) -> Result<Vec<u8>, CodecError>;
+}
+
+/// Converts the bytes of an event back into an application type.
+///
+/// Implementations are used by [`TypedEventReader`] to decode every event read from the stream.
+///
+/// [`TypedEventReader`]: crate::event::typed_reader::TypedEventReader
+pub trait Deserializer<T> {
+    fn deserialize(&self, bytes: &[u8]) -> Result<T, CodecError>;
+}
+
+/// Codec that encodes values using [CBOR](https://cbor.io/).
+#[derive(Debug, Default, Clone, Copy)]
+pub struct CborCodec;
+
+impl<T: Serialize> Serializer<T> for CborCodec {
+    fn serialize(&self, value: &T) -> Result<Vec<u8>, CodecError> {
+        serde_cbor::to_vec(value).context(Cbor {
+            msg: "serialize event".to_owned(),
+        })
+    }
+}
+
+impl<T: DeserializeOwned> Deserializer<T> for CborCodec {
+    fn deserialize(&self, bytes: &[u8]) -> Result<T, CodecError> {
+        serde_cbor::from_slice(bytes).context(Cbor {
+            msg: "deserialize event".to_owned(),
+        })
+    }
+}
+
+/// Codec that encodes values as JSON documents.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct JsonCodec;
+
+impl<T: Serialize> Serializer<T> for JsonCodec {
+    fn serialize(&self, value: &T) -> Result<Vec<u8>, CodecError> {
+        serde_json::to_vec(value).context(Json {
+            msg: "serialize event".to_owned(),
+        })
+    }
+}
+
+impl<T: DeserializeOwned> Deserializer<T> for JsonCodec {
+    fn deserialize(&self, bytes: &[u8]) -> Result<T, CodecError> {
+        serde_json::from_slice(bytes).context(Json {
+            msg: "deserialize event".to_owned(),
+        })
+    }
+}
+
+/// Codec that encodes values using bincode.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct BincodeCodec;
+
+impl<T: Serialize> Serializer<T> for BincodeCodec {
+    fn serialize(&self, value: &T) -> Result<Vec<u8>, CodecError> {
+        bincode2::serialize(value).context(Bincode {
+            msg: "serialize event".to_owned(),
+        })
+    }
+}
+
+impl<T: DeserializeOwned> Deserializer<T> for BincodeCodec {
+    fn deserialize(&self, bytes: &[u8]) -> Result<T, CodecError> {
+        bincode2::deserialize(bytes).context(Bincode {
+            msg: "deserialize event".to_owned(),
+        })
+    }
+}
+
+#[derive(Debug, Snafu)]
+#[snafu(visibility = "pub(crate)")]
+pub enum CodecError {
+    #[snafu(display("Failed to {} using cbor due to {:?}", msg, source))]
+    Cbor { msg: String, source: CborError },
+
+    #[snafu(display("Failed to {} using json due to {:?}", msg, source))]
+    Json { msg: String, source: JsonError },
+
+    #[snafu(display("Failed to {} using bincode due to {:?}", msg, source))]
+    Bincode { msg: String, source: BincodeError },
+
+    #[snafu(display("Failed to {} due to {}", msg, error_msg))]
+    Custom { msg: String, error_msg: String },
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    struct Reading {
+        sensor: String,
+        value: f64,
+        tags: Vec<String>,
+    }
+
+    fn reading() -> Reading {
+        Reading {
+            sensor: "temperature".to_owned(),
+            value: 21.5,
+            tags: vec!["indoor".to_owned()],
+        }
+    }
+
+    fn round_trip<C: Serializer<Reading> + Deserializer<Reading>>(codec: C) {
+        let encoded = codec.serialize(&reading()).expect("serialize");
+        let decoded: Reading = codec.deserialize(&encoded).expect("deserialize");
+        assert_eq!(decoded, reading());
+    }
+
+    #[test]
+    fn test_codec_round_trip() {
+        round_trip(CborCodec);
+        round_trip(JsonCodec);
+        round_trip(BincodeCodec);
+    }
+
+    #[test]
+    fn test_codec_decode_error() {
+        let garbage = vec![0xff, 0x00, 0x13];
+        let res: Result<Reading, CodecError> = CborCodec.deserialize(&garbage);
+        assert!(matches!(res, Err(CodecError::Cbor { .. })));
+        let res: Result<Reading, CodecError> = JsonCodec.deserialize(&garbage);
+        assert!(matches!(res, Err(CodecError::Json { .. })));
+        let res: Result<Reading, CodecError> = BincodeCodec.deserialize(&garbage);
+        assert!(matches!(res, Err(CodecError::Bincode { .. })));
+    }
+}
@@ -10,8 +10,11 @@
 
 use crate::client_factory::ClientFactoryAsync;
 use crate::error::Error;
+use crate::event::routing::HashRoutingStrategy;
 use crate::segment::event::{Incoming, PendingEvent, RoutingInfo};
 use crate::segment::reactor::Reactor;
+use crate::segment::writer::SegmentWriterConfig;
+use crate::sync::table::{Table, TableError, Version};
 
 use pravega_client_auth::DelegationTokenProvider;
 use pravega_client_channel::{create_channel, ChannelSender};
@@ -20,17 +23,19 @@ use pravega_client_shared::{
 };
 use pravega_controller_client::ControllerError;
 
-use futures::FutureExt;
+use futures::future::join_all;
+use futures::pin_mut;
+use futures::stream::StreamExt;
 use snafu::{ResultExt, Snafu};
 use std::collections::BTreeMap;
-use std::collections::HashSet;
+use std::collections::HashMap;
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
 use tokio::sync::oneshot;
 use tokio::sync::oneshot::error::TryRecvError;
-use tokio::time::sleep;
-use tracing::{debug, error, info, info_span};
+use tokio::time::{sleep, timeout};
+use tracing::{debug, error, info, info_span, warn};
 use tracing_futures::Instrument;
 
 /// Write events to a stream transactionally.
@@ -81,14 +86,14 @@ pub struct TransactionalEventWriter {
     writer_id: WriterId,
     factory: ClientFactoryAsync,
     pinger_handle: PingerHandle,
+    journal: Option<Arc<TransactionJournal>>,
     delegation_token_provider: Arc<DelegationTokenProvider>,
-    shutdown: oneshot::Sender<()>,
 }
 
 impl TransactionalEventWriter {
     // use ClientFactory to initialize a TransactionalEventStreamWriter.
     pub(crate) async fn new(stream: ScopedStream, writer_id: WriterId, factory: ClientFactoryAsync) -> Self {
-        let (mut pinger, pinger_handle, shutdown) = Pinger::new(
+        let (mut pinger, pinger_handle) = Pinger::new(
             stream.clone(),
             factory.config().transaction_timeout_time,
             factory.clone(),
@@ -104,12 +109,41 @@ impl TransactionalEventWriter {
             writer_id,
             factory,
             pinger_handle,
+            journal: None,
             delegation_token_provider,
-            shutdown,
         }
     }
 
+    /// Records the transactions opened by this writer in a journal, so that they can be found after a
+    /// restart, see [`TransactionalEventWriter::list_open_transactions`].
+    ///
+    /// The journal is kept in a Table named after the stream and the writer id, so a writer created
+    /// with the same writer id after a restart finds the transactions opened before the restart. The
+    /// Table outlives the writer, it should be deleted with
+    /// [`TransactionalEventWriter::delete_journal`] once the writer id is no longer used.
+    pub async fn with_journal(mut self) -> Result<Self, TransactionalEventWriterError> {
+        let journal = TransactionJournal::new(&self.stream, self.writer_id, &self.factory)
+            .await
+            .context(TxnJournalError {})?;
+        self.journal = Some(Arc::new(journal));
+        Ok(self)
+    }
+
+    /// Deletes the journal of this writer id along with the transactions recorded in it, the writer
+    /// no longer records its transactions afterwards.
+    pub async fn delete_journal(&mut self) -> Result<(), TransactionalEventWriterError> {
+        TransactionJournal::delete(&self.stream, self.writer_id, &self.factory)
+            .await
+            .context(TxnJournalError {})?;
+        self.journal = None;
+        Ok(())
+    }
+
     /// This method opens a transaction by sending a request to Pravega controller.
+    ///
+    /// If the journal is enabled, the transaction is recorded in it until it is committed or aborted.
+    /// The transaction is aborted if it cannot be recorded, see
+    /// [`TransactionalEventWriter::with_journal`].
     pub async fn begin(&mut self) -> Result<Transaction, TransactionalEventWriterError> {
         let txn_segments = self
             .factory
@@ -123,11 +157,30 @@ impl TransactionalEventWriter {
             .context(TxnStreamControllerError {})?;
         info!("Transaction {} created", txn_segments.tx_id);
         let txn_id = txn_segments.tx_id;
-        self.pinger_handle.add(txn_id)?;
+        if let Some(journal) = &self.journal {
+            if let Err(e) = journal.add(txn_id).await {
+                // a transaction missing from the journal could not be recovered after a restart.
+                if let Err(abort_error) = self
+                    .factory
+                    .controller_client()
+                    .abort_transaction(&self.stream, txn_id)
+                    .await
+                {
+                    warn!(
+                        "failed to abort transaction {:?} which is not journaled: {:?}",
+                        txn_id, abort_error
+                    );
+                }
+                return Err(TransactionalEventWriterError::TxnJournalError { source: e });
+            }
+        }
+        let lease = self.pinger_handle.add(txn_id)?;
         Ok(Transaction::new(
             TransactionInfo::new(txn_id, self.writer_id, self.stream.clone(), false),
             txn_segments.stream_segments,
             self.pinger_handle.clone(),
+            lease,
+            self.journal.clone(),
             self.factory.clone(),
             false,
         )
@@ -137,7 +190,8 @@ impl TransactionalEventWriter {
     /// This method returns the Transaction based on the given transaction id.
     /// If the current transaction is not in open status, meaning it has been committed
     /// or aborted, this method will create a closed transaction that only contains the meta data
-    /// of this transaction.
+    /// of this transaction. An open transaction can be written, committed or aborted, and it is pinged
+    /// by this writer from now on.
     pub async fn get_txn(&self, txn_id: TxId) -> Result<Transaction, TransactionalEventWriterError> {
         let status = self
             .factory
@@ -147,10 +201,14 @@ impl TransactionalEventWriter {
             .map_err(|e| e.error)
             .context(TxnStreamControllerError {})?;
         if status != TransactionStatus::Open {
+            // a closed transaction has no lease to lose.
+            let (_lease_sender, lease) = oneshot::channel();
             return Ok(Transaction::new(
                 TransactionInfo::new(txn_id, self.writer_id, self.stream.clone(), true),
                 StreamSegments::new(BTreeMap::new()),
                 self.pinger_handle.clone(),
+                lease,
+                self.journal.clone(),
                 self.factory.clone(),
                 true,
             )
@@ -163,15 +221,173 @@ impl TransactionalEventWriter {
             .await
             .map_err(|e| e.error)
             .context(TxnStreamControllerError {})?;
+        let lease = self.pinger_handle.add(txn_id)?;
         Ok(Transaction::new(
-            TransactionInfo::new(txn_id, self.writer_id, self.stream.clone(), true),
+            TransactionInfo::new(txn_id, self.writer_id, self.stream.clone(), false),
             segments,
             self.pinger_handle.clone(),
+            lease,
+            self.journal.clone(),
             self.factory.clone(),
             false,
         )
         .await)
     }
+
+    /// Returns the open transactions recorded in the journal of this writer.
+    ///
+    /// The transactions that are no longer open are removed from the journal. It fails with
+    /// [`TransactionalEventWriterError::TxnJournalDisabled`] unless the journal is enabled with
+    /// [`TransactionalEventWriter::with_journal`].
+    pub async fn list_open_transactions(&self) -> Result<Vec<TxId>, TransactionalEventWriterError> {
+        let journal = self
+            .journal
+            .as_ref()
+            .ok_or(TransactionalEventWriterError::TxnJournalDisabled {})?;
+        let mut open_txns = vec![];
+        for txn_id in journal.list().await.context(TxnJournalError {})? {
+            let status = self
+                .factory
+                .controller_client()
+                .check_transaction_status(&self.stream, txn_id)
+                .await
+                .map_err(|e| e.error)
+                .context(TxnStreamControllerError {})?;
+            if status == TransactionStatus::Open {
+                open_txns.push(txn_id);
+            } else {
+                debug!(
+                    "transaction {:?} is {:?}, remove it from the journal",
+                    txn_id, status
+                );
+                journal.remove(txn_id).await.context(TxnJournalError {})?;
+            }
+        }
+        Ok(open_txns)
+    }
+
+    /// Commits or aborts each open transaction recorded in the journal of this writer, as decided
+    /// by the given callback.
+    ///
+    /// It is meant to be called after a restart to finish the transactions left open by the previous
+    /// run of the writer. The result of the commit or abort is returned for each transaction.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let mut writer = client_factory
+    ///     .create_transactional_event_writer(stream, WriterId(0))
+    ///     .await
+    ///     .with_journal()
+    ///     .await
+    ///     .expect("enable journal");
+    /// let results = writer
+    ///     .recover_transactions(|_txn_id| TransactionRecovery::Abort)
+    ///     .await
+    ///     .expect("recover transactions");
+    /// ```
+    pub async fn recover_transactions<F>(
+        &mut self,
+        mut decide: F,
+    ) -> Result<Vec<(TxId, Result<(), TransactionError>)>, TransactionalEventWriterError>
+    where
+        F: FnMut(TxId) -> TransactionRecovery,
+    {
+        let mut results = vec![];
+        for txn_id in self.list_open_transactions().await? {
+            let mut txn = self.get_txn(txn_id).await?;
+            let result = match decide(txn_id) {
+                TransactionRecovery::Commit(timestamp) => txn.commit(timestamp).await,
+                TransactionRecovery::Abort => txn.abort().await,
+            };
+            info!("recovered transaction {:?}: {:?}", txn_id, result);
+            results.push((txn_id, result));
+        }
+        Ok(results)
+    }
+}
+
+/// What to do with a transaction found by [`TransactionalEventWriter::recover_transactions`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TransactionRecovery {
+    /// Commits the transaction with the given timestamp.
+    Commit(Timestamp),
+    /// Aborts the transaction.
+    Abort,
+}
+
+// keeps the ids of the transactions opened by a writer in a Table, so that they can be found
+// after the writer restarts.
+struct TransactionJournal {
+    table: Table,
+}
+
+impl TransactionJournal {
+    async fn new(
+        stream: &ScopedStream,
+        writer_id: WriterId,
+        factory: &ClientFactoryAsync,
+    ) -> Result<Self, TableError> {
+        let name = Self::name(stream, writer_id);
+        let table = Table::new(stream.scope.clone(), name, factory.clone()).await?;
+        Ok(TransactionJournal { table })
+    }
+
+    async fn delete(
+        stream: &ScopedStream,
+        writer_id: WriterId,
+        factory: &ClientFactoryAsync,
+    ) -> Result<(), TableError> {
+        Table::delete(
+            stream.scope.clone(),
+            Self::name(stream, writer_id),
+            factory.clone(),
+        )
+        .await
+    }
+
+    fn name(stream: &ScopedStream, writer_id: WriterId) -> String {
+        format!("{}-{}-transactions", stream.stream.name, writer_id)
+    }
+
+    async fn add(&self, txn_id: TxId) -> Result<(), TableError> {
+        self.table.insert(&Self::key(txn_id), &(), -1).await.map(|_| ())
+    }
+
+    async fn remove(&self, txn_id: TxId) -> Result<(), TableError> {
+        match self.table.remove(&Self::key(txn_id), -1).await {
+            Err(TableError::KeyDoesNotExist { .. }) => Ok(()),
+            result => result,
+        }
+    }
+
+    async fn list(&self) -> Result<Vec<TxId>, TableError> {
+        let mut keys: Vec<String> = vec![];
+        let entries = self.table.read_entries_stream_from_position(10, 0);
+        pin_mut!(entries);
+        while let Some(entry) = entries.next().await {
+            let (key, (), _version, _position): (String, (), Version, i64) = entry?;
+            if !keys.contains(&key) {
+                keys.push(key);
+            }
+        }
+        if keys.is_empty() {
+            return Ok(vec![]);
+        }
+        // the entries read include the keys that are removed since.
+        let values: Vec<Option<((), Version)>> = self.table.get_all(keys.iter().collect()).await?;
+        Ok(keys
+            .iter()
+            .zip(values)
+            .filter(|(_key, value)| value.is_some())
+            .map(|(key, _value)| TxId(key.parse().expect("parse transaction id")))
+            .collect())
+    }
+
+    // cbor cannot encode u128, so the transaction id is stored as a string.
+    fn key(txn_id: TxId) -> String {
+        txn_id.0.to_string()
+    }
 }
 
 // contains the metadata of Transaction
@@ -190,8 +406,13 @@ pub struct Transaction {
     info: TransactionInfo,
     sender: ChannelSender<Incoming>,
     handle: PingerHandle,
+    lease: LeaseReceiver,
+    lease_lost: Option<String>,
+    journal: Option<Arc<TransactionJournal>>,
     factory: ClientFactoryAsync,
-    event_handles: Vec<EventHandle>,
+    event_handles: Vec<(usize, EventHandle)>,
+    events_written: usize,
+    failed_events: Vec<(usize, String)>,
 }
 
 type EventHandle = oneshot::Receiver<Result<(), Error>>;
@@ -206,6 +427,8 @@ impl Transaction {
         info: TransactionInfo,
         stream_segments: StreamSegments,
         handle: PingerHandle,
+        lease: LeaseReceiver,
+        journal: Option<Arc<TransactionJournal>>,
         factory: ClientFactoryAsync,
         closed: bool,
     ) -> Self {
@@ -215,8 +438,13 @@ impl Transaction {
                 info,
                 sender: tx,
                 handle,
+                lease,
+                lease_lost: None,
+                journal,
                 factory,
                 event_handles: vec![],
+                events_written: 0,
+                failed_events: vec![],
             };
         }
         let rt_handle = factory.runtime_handle();
@@ -230,6 +458,8 @@ impl Transaction {
                 rx,
                 factory.clone(),
                 Some(stream_segments),
+                SegmentWriterConfig::default(),
+                Arc::new(HashRoutingStrategy),
             )
             .instrument(span),
         );
@@ -237,8 +467,13 @@ impl Transaction {
             info,
             sender: tx,
             handle,
+            lease,
+            lease_lost: None,
+            journal,
             factory,
             event_handles: vec![],
+            events_written: 0,
+            failed_events: vec![],
         }
     }
 
@@ -255,28 +490,23 @@ impl Transaction {
     /// write_event accepts a vec of bytes as the input event and an optional routing key which is used
     /// to determine which segment to write to. It calls the corresponding transactional event segment
     /// writer to write the data to the server.
+    ///
+    /// It fails with [`TransactionError::LeaseExpired`] once the lease of the transaction is lost.
     pub async fn write_event(
         &mut self,
         routing_key: Option<String>,
         event: Vec<u8>,
     ) -> Result<(), TransactionError> {
         self.error_if_closed()?;
+        self.error_if_lease_lost()?;
 
         let size = event.len();
         let (tx, rx) = oneshot::channel();
         let routing_info = RoutingInfo::RoutingKey(routing_key);
         if let Some(pending_event) = PendingEvent::with_header(routing_info, event, None, tx) {
             let append_event = Incoming::AppendEvent(pending_event);
-            if let Err(e) = self.sender.send((append_event, size)).await {
-                error!(
-                    "failed to write to transaction {:?} due to {:?}",
-                    self.info.txn_id, e
-                );
-                return Err(TransactionError::TxnSegmentWriterError {
-                    error_msg: format!("{:?}", e),
-                });
-            }
-            self.event_handles.push(rx);
+            self.send(append_event, size).await?;
+            self.push_event_handle(rx);
         } else {
             let e = rx.await.expect("get error");
             error!(
@@ -287,58 +517,94 @@ impl Transaction {
                 error_msg: format!("{:?}", e),
             });
         }
+        self.check_acked_events()
+    }
 
-        let mut ack_up_to = 0;
-        for event in &mut self.event_handles {
-            match event.try_recv() {
-                Ok(res) => {
-                    if let Err(e) = res {
-                        return Err(TransactionError::TxnSegmentWriterError {
-                            error_msg: format!("error when writing event: {:?}", e),
-                        });
-                    } else {
-                        ack_up_to += 1;
-                    }
-                }
-                Err(TryRecvError::Empty) => {
-                    break;
-                }
-                Err(TryRecvError::Closed) => {
-                    return Err(TransactionError::TxnSegmentWriterError {
-                        error_msg: "event handle closed, cannot get result for event".to_string(),
-                    });
-                }
+    /// write_events writes a batch of events with their optional routing keys to the transaction.
+    ///
+    /// The batch is sent to the transaction segments at once. If an event of the batch cannot be
+    /// written, for example because it is too large, none of the events of the batch are written.
+    /// Same as write_event, the events are not acknowledged when this method returns, see
+    /// [`Transaction::flush`].
+    pub async fn write_events(
+        &mut self,
+        events: Vec<(Option<String>, Vec<u8>)>,
+    ) -> Result<(), TransactionError> {
+        self.error_if_closed()?;
+        self.error_if_lease_lost()?;
+
+        let mut pending_events = Vec::with_capacity(events.len());
+        let mut receivers = Vec::with_capacity(events.len());
+        let mut size = 0;
+        for (index, (routing_key, event)) in events.into_iter().enumerate() {
+            let (tx, rx) = oneshot::channel();
+            let routing_info = RoutingInfo::RoutingKey(routing_key);
+            if let Some(pending_event) = PendingEvent::with_header(routing_info, event, None, tx) {
+                size += pending_event.data.len();
+                pending_events.push(pending_event);
+                receivers.push(rx);
+            } else {
+                let e = rx.await.expect("get error");
+                error!(
+                    "failed to write to transaction {:?} due to {:?}",
+                    self.info.txn_id, e
+                );
+                return Err(TransactionError::TxnSegmentWriterError {
+                    error_msg: format!("event {} of the batch cannot be written: {:?}", index, e),
+                });
             }
         }
-        self.event_handles.drain(0..ack_up_to);
-        Ok(())
+        if pending_events.is_empty() {
+            return Ok(());
+        }
+        self.send(Incoming::AppendEvents(pending_events), size).await?;
+        for rx in receivers {
+            self.push_event_handle(rx);
+        }
+        self.check_acked_events()
+    }
+
+    /// flush waits until all the events written to the transaction are acknowledged by the
+    /// transaction segments.
+    ///
+    /// It fails with [`TransactionError::UnacknowledgedEvents`] listing every event that could not
+    /// be written since the transaction began. Once an event failed, the transaction can no longer be
+    /// committed and it should be aborted.
+    pub async fn flush(&mut self) -> Result<(), TransactionError> {
+        for (index, event) in self.event_handles.drain(..) {
+            let result = event.await.unwrap_or_else(|e| {
+                Err(Error::InternalFailure {
+                    msg: format!("event handle closed, cannot get result for event: {:?}", e),
+                })
+            });
+            if let Err(e) = result {
+                self.failed_events.push((index, e.to_string()));
+            }
+        }
+        if self.failed_events.is_empty() {
+            Ok(())
+        } else {
+            Err(TransactionError::UnacknowledgedEvents {
+                id: self.info.txn_id,
+                failures: self.failed_events.clone(),
+            })
+        }
     }
 
     /// commit accepts a timestamp and will send a commit request to Pravega controller.
+    ///
+    /// It flushes the transaction first and fails with [`TransactionError::UnacknowledgedEvents`]
+    /// without committing if any event written to the transaction is not acknowledged. It fails with
+    /// [`TransactionError::LeaseExpired`] once the lease of the transaction is lost.
     pub async fn commit(&mut self, timestamp: Timestamp) -> Result<(), TransactionError> {
         debug!("committing transaction {:?}", self.info.txn_id);
 
         self.error_if_closed()?;
+        self.error_if_lease_lost()?;
+        // partial data is never committed, the transaction stays open so it can be aborted.
+        self.flush().await?;
         self.info.closed = true;
 
-        for event in &mut self.event_handles {
-            match event.await {
-                Ok(res) => {
-                    if let Err(e) = res {
-                        return Err(TransactionError::TxnSegmentWriterError {
-                            error_msg: format!("error when writing event: {:?}", e),
-                        });
-                    }
-                }
-                Err(e) => {
-                    return Err(TransactionError::TxnSegmentWriterError {
-                        error_msg: format!("event handle closed, cannot get result for event: {:?}", e),
-                    });
-                }
-            }
-        }
-        self.event_handles.clear();
-
         // remove this transaction from ping list
         self.handle
             .remove(self.info.txn_id)
@@ -356,6 +622,7 @@ impl Transaction {
             .map_err(|e| e.error)
             .context(TxnControllerError {})?;
 
+        self.remove_from_journal().await;
         debug!("transaction {:?} committed", self.info.txn_id);
         Ok(())
     }
@@ -379,6 +646,7 @@ impl Transaction {
             .map_err(|e| e.error)
             .context(TxnControllerError {})?;
 
+        self.remove_from_journal().await;
         debug!("transaction {:?} aborted", self.info.txn_id);
         Ok(())
     }
@@ -400,30 +668,122 @@ impl Transaction {
             Ok(())
         }
     }
+
+    async fn send(&mut self, append_event: Incoming, size: usize) -> Result<(), TransactionError> {
+        if let Err(e) = self.sender.send((append_event, size)).await {
+            error!(
+                "failed to write to transaction {:?} due to {:?}",
+                self.info.txn_id, e
+            );
+            return Err(TransactionError::TxnSegmentWriterError {
+                error_msg: format!("{:?}", e),
+            });
+        }
+        Ok(())
+    }
+
+    // numbers the events in the order they are written to the transaction.
+    fn push_event_handle(&mut self, event: EventHandle) {
+        self.event_handles.push((self.events_written, event));
+        self.events_written += 1;
+    }
+
+    // removes the acknowledged events from the front and fails if one of them failed.
+    fn check_acked_events(&mut self) -> Result<(), TransactionError> {
+        let mut ack_up_to = 0;
+        let mut failure = None;
+        for (index, event) in &mut self.event_handles {
+            let error = match event.try_recv() {
+                Ok(Ok(())) => {
+                    ack_up_to += 1;
+                    continue;
+                }
+                Ok(Err(e)) => format!("error when writing event: {:?}", e),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Closed) => "event handle closed, cannot get result for event".to_string(),
+            };
+            ack_up_to += 1;
+            self.failed_events.push((*index, error.clone()));
+            failure = Some(error);
+            break;
+        }
+        self.event_handles.drain(0..ack_up_to);
+        match failure {
+            Some(error_msg) => Err(TransactionError::TxnSegmentWriterError { error_msg }),
+            None => Ok(()),
+        }
+    }
+
+    // a transaction left in the journal is removed by the next list_open_transactions.
+    async fn remove_from_journal(&self) {
+        let journal = match &self.journal {
+            Some(journal) => journal,
+            None => return,
+        };
+        if let Err(e) = journal.remove(self.info.txn_id).await {
+            warn!(
+                "failed to remove transaction {:?} from the journal: {:?}",
+                self.info.txn_id, e
+            );
+        }
+    }
+
+    fn error_if_lease_lost(&mut self) -> Result<(), TransactionError> {
+        if self.lease_lost.is_none() {
+            if let Ok(msg) = self.lease.try_recv() {
+                self.lease_lost = Some(msg);
+            }
+        }
+        match &self.lease_lost {
+            Some(msg) => Err(TransactionError::LeaseExpired {
+                id: self.info.txn_id,
+                msg: msg.clone(),
+            }),
+            None => Ok(()),
+        }
+    }
 }
 
 impl Drop for Transaction {
     fn drop(&mut self) {
-        let _res = self.sender.send_without_bp(Incoming::Close());
+        let _res = self.sender.send_without_bp(Incoming::Close(None));
     }
 }
 
+// receives the reason why the lease of a transaction is lost from the Pinger.
+type LeaseReceiver = oneshot::Receiver<String>;
+
 #[derive(Debug)]
 enum PingerEvent {
-    Add(TxId),
+    Add(TxId, oneshot::Sender<String>),
     Remove(TxId),
 }
 
+// the lease of a transaction that is pinged by the Pinger.
+struct Lease {
+    // reports the lease loss to the Transaction.
+    sender: oneshot::Sender<String>,
+    // the last time the lease was renewed.
+    renewed: Instant,
+}
+
 /// Pinger is used to ping transactions periodically. It spawns a task that runs in the background
 /// to ping transactions without blocking the current thread. The spawned task uses a loop to run
-/// the ping logic and sleeps(does not block other tasks) for a given time before next iteration.
+/// the ping logic and sleeps(does not block other tasks) for the ping interval before next iteration.
+///
+/// The transactions are pinged concurrently and each ping is bounded by the ping interval. A failed
+/// ping is retried in the next iteration, so the Pinger keeps running through controller outages.
+/// The ping interval is at most a third of the lease time, so a failed ping is retried before the
+/// lease runs out. The lease of a transaction is lost if it cannot be renewed within the lease time,
+/// or if the controller rejects the ping or reports the transaction as committed or aborted. The
+/// reason is then sent to the Transaction, which fails its later writes and commit. The Pinger stops
+/// once the writer and all its transactions are dropped.
 pub(crate) struct Pinger {
     stream: ScopedStream,
     txn_lease_millis: u64,
     ping_interval_millis: u64,
     factory: ClientFactoryAsync,
     receiver: UnboundedReceiver<PingerEvent>,
-    shutdown: oneshot::Receiver<()>,
 }
 
 /// PingerHandle is just a wrapped channel sender which is used to communicate with the Pinger.
@@ -432,18 +792,20 @@ pub(crate) struct Pinger {
 pub(crate) struct PingerHandle(UnboundedSender<PingerEvent>);
 
 impl PingerHandle {
-    pub(crate) fn add(&mut self, txn_id: TxId) -> Result<(), TransactionalEventWriterError> {
-        if let Err(e) = self.0.send(PingerEvent::Add(txn_id)) {
+    /// Adds the transaction to the ping list and returns the receiver of its lease loss.
+    pub(crate) fn add(&self, txn_id: TxId) -> Result<LeaseReceiver, TransactionalEventWriterError> {
+        let (tx, rx) = oneshot::channel();
+        if let Err(e) = self.0.send(PingerEvent::Add(txn_id, tx)) {
             error!("pinger failed to add transaction: {:?}", e);
             Err(TransactionalEventWriterError::PingerError {
                 msg: format!("failed to add transaction due to: {:?}", e),
             })
         } else {
-            Ok(())
+            Ok(rx)
         }
     }
 
-    pub(crate) fn remove(&mut self, txn_id: TxId) -> Result<(), TransactionalEventWriterError> {
+    pub(crate) fn remove(&self, txn_id: TxId) -> Result<(), TransactionalEventWriterError> {
         if let Err(e) = self.0.send(PingerEvent::Remove(txn_id)) {
             error!("pinger failed to remove transaction: {:?}", e);
             Err(TransactionalEventWriterError::PingerError {
@@ -460,98 +822,123 @@ impl Pinger {
         stream: ScopedStream,
         txn_lease_millis: u64,
         factory: ClientFactoryAsync,
-    ) -> (Self, PingerHandle, oneshot::Sender<()>) {
+    ) -> (Self, PingerHandle) {
         let (tx, rx) = unbounded_channel();
-        let (oneshot_tx, oneshot_rx) = oneshot::channel();
         let pinger = Pinger {
             stream,
             txn_lease_millis,
             ping_interval_millis: Pinger::ping_interval(txn_lease_millis),
             factory,
             receiver: rx,
-            shutdown: oneshot_rx,
         };
         let handle = PingerHandle(tx);
-        (pinger, handle, oneshot_tx)
+        (pinger, handle)
     }
 
     pub(crate) async fn start_ping(&mut self) {
-        // this set is used to store the transactions that are alive and
-        // needs to be pinged periodically.
-        let mut txn_list: HashSet<TxId> = HashSet::new();
-
-        // this set is used to store the transaction that are aborted or committed
-        let mut completed_txns: HashSet<TxId> = HashSet::new();
+        // the leases of the transactions that are alive and need to be pinged periodically.
+        let mut leases: HashMap<TxId, Lease> = HashMap::new();
 
         loop {
-            // try receive any incoming events
-            if let Some(option) = self.receiver.recv().now_or_never() {
-                if let Some(event) = option {
-                    match event {
-                        PingerEvent::Add(id) => {
-                            txn_list.insert(id);
+            debug!("start sending pings to {} transactions.", leases.len());
+            // the transactions are pinged concurrently so that a slow ping does not delay the others.
+            let pinger = &*self;
+            let pings = leases
+                .iter_mut()
+                .map(|(txn_id, lease)| async move { (*txn_id, pinger.ping(*txn_id, lease).await) });
+            let lost_leases: Vec<(TxId, String)> = join_all(pings)
+                .await
+                .into_iter()
+                .filter_map(|(txn_id, result)| result.err().map(|msg| (txn_id, msg)))
+                .collect();
+            for (txn_id, msg) in lost_leases {
+                warn!("transaction {:?} lost its lease: {}", txn_id, msg);
+                if let Some(lease) = leases.remove(&txn_id) {
+                    let _res = lease.sender.send(msg);
+                }
+            }
+            debug!("sending transaction pings complete.");
+
+            // handle the incoming events until the next ping.
+            let next_ping = sleep(Duration::from_millis(self.ping_interval_millis));
+            tokio::pin!(next_ping);
+            loop {
+                tokio::select! {
+                    _ = &mut next_ping => {
+                        debug!("pinger wake up after {}ms", self.ping_interval_millis);
+                        break;
+                    }
+                    event = self.receiver.recv() => match event {
+                        Some(PingerEvent::Add(txn_id, sender)) => {
+                            let renewed = Instant::now();
+                            leases.insert(txn_id, Lease { sender, renewed });
                         }
-                        PingerEvent::Remove(id) => {
-                            txn_list.remove(&id);
+                        Some(PingerEvent::Remove(txn_id)) => {
+                            leases.remove(&txn_id);
+                        }
+                        None => {
+                            info!("shut down pinger");
+                            return;
                         }
                     }
-                } else {
-                    panic!("pinger sender gone");
                 }
             }
+        }
+    }
 
-            // remove completed transactions from the ping list
-            txn_list.retain(|i| !completed_txns.contains(i));
-            completed_txns.clear();
-
-            info!("start sending pings to {} transactions.", txn_list.len());
-            // for each transaction in the ping list, send ping to the server
-            for txn_id in txn_list.iter() {
-                debug!(
-                    "sending ping request for txn ID: {:?} with lease: {:?}",
-                    txn_id, self.txn_lease_millis
-                );
-                let status = self
-                    .factory
-                    .controller_client()
-                    .ping_transaction(
-                        &self.stream,
-                        txn_id.to_owned(),
-                        Duration::from_millis(self.txn_lease_millis),
-                    )
-                    .await
-                    .expect("ping transaction");
-
-                if let PingStatus::Ok = status {
-                    debug!("successfully pinged transaction {:?}", txn_id);
-                } else {
-                    debug!("transaction {:?} is committed/aborted", txn_id);
-                    completed_txns.insert(txn_id.to_owned());
-                }
+    // renews the lease of the transaction, returns the reason if the lease is lost. A ping which
+    // does not complete within the ping interval is given up and retried in the next iteration.
+    async fn ping(&self, txn_id: TxId, lease: &mut Lease) -> Result<(), String> {
+        debug!(
+            "sending ping request for txn ID: {:?} with lease: {:?}",
+            txn_id, self.txn_lease_millis
+        );
+        let lease_time = Duration::from_millis(self.txn_lease_millis);
+        let ping = self
+            .factory
+            .controller_client()
+            .ping_transaction(&self.stream, txn_id, lease_time);
+        match timeout(Duration::from_millis(self.ping_interval_millis), ping).await {
+            Ok(Ok(PingStatus::Ok)) => {
+                debug!("successfully pinged transaction {:?}", txn_id);
+                lease.renewed = Instant::now();
+                Ok(())
             }
-            info!("sending transaction pings complete.");
-
-            // delay for transaction lease milliseconds.
-            tokio::select! {
-                _ = sleep(Duration::from_millis(self.txn_lease_millis)) => {
-                    debug!("pinger wake up after {}ms", self.txn_lease_millis);
-                }
-                _ = &mut self.shutdown => {
-                    info!("shut down pinger");
-                    return;
+            Ok(Ok(status)) => Err(format!("ping returned unexpected status {:?}", status)),
+            Ok(Err(e)) => match e.error {
+                ControllerError::OperationError { can_retry: false, .. } => {
+                    Err(format!("ping rejected by controller: {:?}", e.error))
                 }
-            }
+                error => self.retry_ping(txn_id, lease, format!("{:?}", error)),
+            },
+            Err(_) => self.retry_ping(
+                txn_id,
+                lease,
+                format!("no reply within {}ms", self.ping_interval_millis),
+            ),
         }
     }
 
+    // keeps the transaction in the ping list after a failed ping unless its lease has run out.
+    fn retry_ping(&self, txn_id: TxId, lease: &Lease, error: String) -> Result<(), String> {
+        if lease.renewed.elapsed() >= Duration::from_millis(self.txn_lease_millis) {
+            return Err(format!(
+                "lease not renewed within {}ms: {}",
+                self.txn_lease_millis, error
+            ));
+        }
+        warn!(
+            "failed to ping transaction {:?}, retry in {}ms: {}",
+            txn_id, self.ping_interval_millis, error
+        );
+        Ok(())
+    }
+
     fn ping_interval(txn_lease_millis: u64) -> u64 {
-        //Provides a good number of attempts: 1 for <4s, 2 for <9s, 3 for <16s, 4 for <25s, ... 10 for <100s
+        //Provides a good number of attempts: 3 for <16s, 4 for <25s, ... 10 for <100s
         //while at the same time allowing the interval to grow as the timeout gets larger.
-        let target_num_pings = if txn_lease_millis > 1000u64 {
-            f64::sqrt(txn_lease_millis as f64 / 1000f64)
-        } else {
-            1f64
-        };
+        //At least 3 attempts leave room to retry a failed ping within a short lease.
+        let target_num_pings = f64::max(f64::sqrt(txn_lease_millis as f64 / 1000f64), 3f64);
         (txn_lease_millis as f64 / target_num_pings).round() as u64
     }
 }
@@ -563,6 +950,12 @@ pub enum TransactionalEventWriterError {
 
     #[snafu(display("Controller client failed with error {:?}", source))]
     TxnStreamControllerError { source: ControllerError },
+
+    #[snafu(display("Transaction journal failed with error {:?}", source))]
+    TxnJournalError { source: TableError },
+
+    #[snafu(display("Transaction journal is not enabled"))]
+    TxnJournalDisabled {},
 }
 
 #[derive(Debug, Snafu)]
@@ -584,6 +977,16 @@ pub enum TransactionError {
 
     #[snafu(display("Abort Transaction {:?} error due to Transaction {:?}", id, status))]
     TxnAbortError { id: TxId, status: TransactionStatus },
+
+    #[snafu(display("Transaction {:?} lease expired: {}", id, msg))]
+    LeaseExpired { id: TxId, msg: String },
+
+    #[snafu(display("Transaction {:?} has unacknowledged events: {:?}", id, failures))]
+    UnacknowledgedEvents {
+        id: TxId,
+        // the index of each failed event in the transaction along with its error.
+        failures: Vec<(usize, String)>,
+    },
 }
 
 #[cfg(test)]
@@ -594,17 +997,38 @@ pub(crate) mod test {
     use pravega_client_config::connection_type::{ConnectionType, MockType};
     use pravega_client_config::ClientConfigBuilder;
     use pravega_client_shared::{PravegaNodeUri, ScopedSegment};
+    use std::collections::HashSet;
     use tokio::runtime::Runtime;
 
     #[test]
     fn test_get_ping_interval() {
-        assert_eq!(Pinger::ping_interval(1000u64), 1000u64);
-        assert_eq!(Pinger::ping_interval(4000u64), 2000u64);
+        assert_eq!(Pinger::ping_interval(1000u64), 333u64);
+        assert_eq!(Pinger::ping_interval(4000u64), 1333u64);
         assert_eq!(Pinger::ping_interval(9000u64), 3000u64);
         assert_eq!(Pinger::ping_interval(16000u64), 4000u64);
         assert_eq!(Pinger::ping_interval(25000u64), 5000u64);
     }
 
+    #[test]
+    fn test_pinger_retry_within_lease() {
+        let rt = Runtime::new().unwrap();
+        let (_txn_stream_writer, factory) = rt.block_on(create_txn_stream_writer());
+        let (pinger, _handle) = Pinger::new(ScopedStream::from("scope/stream"), 1000, factory.to_async());
+        let lease = |renewed_millis_ago| Lease {
+            sender: oneshot::channel().0,
+            renewed: Instant::now() - Duration::from_millis(renewed_millis_ago),
+        };
+        let txn_id = TxId(0);
+
+        // two pings in a row fail within a short lease and the lease is kept.
+        for attempt in 1..=2 {
+            let lease = lease(attempt * pinger.ping_interval_millis);
+            assert!(pinger.retry_ping(txn_id, &lease, "timeout".to_string()).is_ok());
+        }
+        let lease = lease(1000);
+        assert!(pinger.retry_ping(txn_id, &lease, "timeout".to_string()).is_err());
+    }
+
     #[test]
     fn test_txn_stream_writer() {
         let rt = Runtime::new().unwrap();
@@ -668,6 +1092,231 @@ pub(crate) mod test {
         assert!(txn.event_handles.is_empty());
     }
 
+    #[test]
+    fn test_txn_write_events() {
+        let rt = Runtime::new().unwrap();
+        let (mut txn_stream_writer, _factory) = rt.block_on(create_txn_stream_writer());
+        let mut txn = rt
+            .block_on(txn_stream_writer.begin())
+            .expect("begin a transaction");
+        let events = vec![(Some("key".to_string()), vec![1; 1024]), (None, vec![2; 1024])];
+        rt.block_on(txn.write_events(events)).unwrap();
+        rt.block_on(txn.write_event(None, vec![3; 1024])).unwrap();
+
+        // a batch with an event that is too large is not written at all.
+        let events = vec![(None, vec![4; 1024]), (None, vec![5; 9 * 1024 * 1024])];
+        let result = rt.block_on(txn.write_events(events));
+        assert!(matches!(
+            result,
+            Err(TransactionError::TxnSegmentWriterError { .. })
+        ));
+
+        rt.block_on(txn.flush()).unwrap();
+        assert!(txn.event_handles.is_empty());
+        assert_eq!(txn.events_written, 3);
+        rt.block_on(txn.commit(Timestamp(0))).unwrap();
+        let status = rt.block_on(txn.check_status()).unwrap();
+        assert_eq!(status, TransactionStatus::Committed);
+    }
+
+    #[test]
+    fn test_txn_commit_unacknowledged_events() {
+        let rt = Runtime::new().unwrap();
+        let (mut txn_stream_writer, _factory) = rt.block_on(create_txn_stream_writer());
+        let mut txn = rt
+            .block_on(txn_stream_writer.begin())
+            .expect("begin a transaction");
+        rt.block_on(txn.write_event(None, vec![1; 1024])).unwrap();
+        rt.block_on(txn.flush()).unwrap();
+
+        // the second event of the transaction fails.
+        let (tx, rx) = oneshot::channel();
+        tx.send(Err(Error::InternalFailure {
+            msg: "append failed".to_string(),
+        }))
+        .expect("send error");
+        txn.push_event_handle(rx);
+        // the next write reports it.
+        let result = rt.block_on(txn.write_event(None, vec![1; 1024]));
+        assert!(matches!(
+            result,
+            Err(TransactionError::TxnSegmentWriterError { .. })
+        ));
+
+        match rt.block_on(txn.commit(Timestamp(0))) {
+            Err(TransactionError::UnacknowledgedEvents { id, failures }) => {
+                assert_eq!(id, txn.txn_id());
+                assert_eq!(failures.len(), 1);
+                assert_eq!(failures[0].0, 1);
+            }
+            result => panic!("unexpected commit result {:?}", result),
+        }
+        let status = rt.block_on(txn.check_status()).unwrap();
+        assert_eq!(status, TransactionStatus::Open);
+        rt.block_on(txn.abort()).unwrap();
+    }
+
+    #[test]
+    fn test_txn_lease_lost() {
+        let config = ClientConfigBuilder::default()
+            .connection_type(ConnectionType::Mock(MockType::Happy))
+            .mock(true)
+            .controller_uri(PravegaNodeUri::from("127.0.0.2:9091"))
+            .transaction_timeout_time(1000u64)
+            .build()
+            .unwrap();
+        // the time only advances when the runtime is idle, so the pinger runs without waiting.
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .start_paused(true)
+            .build()
+            .expect("create runtime");
+        let factory = ClientFactory::new_with_runtime(config, rt);
+        let rt = factory.runtime();
+        rt.block_on(create_stream(&factory, "scope", "stream", 1));
+        let stream = ScopedStream::from("scope/stream");
+        let mut txn_stream_writer =
+            rt.block_on(factory.create_transactional_event_writer(stream.clone(), WriterId(123)));
+        let mut txn = rt
+            .block_on(txn_stream_writer.begin())
+            .expect("begin a transaction");
+        let mut other_txn = rt
+            .block_on(txn_stream_writer.begin())
+            .expect("begin a transaction");
+        rt.block_on(txn.write_event(None, vec![1; 1024])).unwrap();
+
+        // the transaction is aborted behind the back of the writer and the next ping finds out.
+        rt.block_on(
+            factory
+                .controller_client()
+                .abort_transaction(&stream, txn.txn_id()),
+        )
+        .expect("abort transaction");
+        rt.block_on(async { sleep(Duration::from_millis(2000)).await });
+
+        let result = rt.block_on(txn.write_event(None, vec![1; 1024]));
+        assert!(matches!(result, Err(TransactionError::LeaseExpired { .. })));
+        let result = rt.block_on(txn.commit(Timestamp(0)));
+        assert!(matches!(result, Err(TransactionError::LeaseExpired { .. })));
+
+        // the other transactions keep their lease.
+        rt.block_on(other_txn.write_event(None, vec![1; 1024])).unwrap();
+        rt.block_on(other_txn.commit(Timestamp(0))).unwrap();
+    }
+
+    #[test]
+    fn test_recover_transactions() {
+        let rt = Runtime::new().unwrap();
+        let (txn_stream_writer, factory) = rt.block_on(create_txn_stream_writer());
+        let stream = ScopedStream::from("scope/stream");
+        // the transactions are only journaled once the journal is enabled.
+        let result = rt.block_on(txn_stream_writer.list_open_transactions());
+        assert!(matches!(
+            result,
+            Err(TransactionalEventWriterError::TxnJournalDisabled {})
+        ));
+        let mut txn_stream_writer = rt
+            .block_on(txn_stream_writer.with_journal())
+            .expect("enable journal");
+        let mut txns = vec![];
+        for _ in 0..3 {
+            let mut txn = rt
+                .block_on(txn_stream_writer.begin())
+                .expect("begin a transaction");
+            rt.block_on(txn.write_event(None, vec![1; 1024])).unwrap();
+            txns.push(txn);
+        }
+        let mut committed_txn = txns.pop().expect("get transaction");
+        rt.block_on(committed_txn.commit(Timestamp(0))).unwrap();
+        let txn_ids: Vec<TxId> = txns.iter().map(|txn| txn.txn_id()).collect();
+        // the writer crashes with two transactions open.
+        drop(txns);
+        drop(txn_stream_writer);
+
+        // a writer with the same writer id finds them after the restart.
+        let mut txn_stream_writer = rt
+            .block_on(async {
+                factory
+                    .create_transactional_event_writer(stream.clone(), WriterId(123))
+                    .await
+                    .with_journal()
+                    .await
+            })
+            .expect("enable journal");
+        let open_txns: HashSet<TxId> = rt
+            .block_on(txn_stream_writer.list_open_transactions())
+            .expect("list open transactions")
+            .into_iter()
+            .collect();
+        assert_eq!(open_txns, txn_ids.iter().cloned().collect());
+        let other_writer = rt
+            .block_on(async {
+                factory
+                    .create_transactional_event_writer(stream.clone(), WriterId(456))
+                    .await
+                    .with_journal()
+                    .await
+            })
+            .expect("enable journal");
+        let other_txns = rt
+            .block_on(other_writer.list_open_transactions())
+            .expect("list open transactions");
+        assert!(other_txns.is_empty());
+
+        let results = rt
+            .block_on(txn_stream_writer.recover_transactions(|txn_id| {
+                if txn_id == txn_ids[0] {
+                    TransactionRecovery::Commit(Timestamp(0))
+                } else {
+                    TransactionRecovery::Abort
+                }
+            }))
+            .expect("recover transactions");
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().all(|(_txn_id, result)| result.is_ok()));
+        let txn = rt
+            .block_on(txn_stream_writer.get_txn(txn_ids[0]))
+            .expect("get transaction");
+        let status = rt.block_on(txn.check_status()).expect("check status");
+        assert_eq!(status, TransactionStatus::Committed);
+        let txn = rt
+            .block_on(txn_stream_writer.get_txn(txn_ids[1]))
+            .expect("get transaction");
+        let status = rt.block_on(txn.check_status()).expect("check status");
+        assert_eq!(status, TransactionStatus::Aborted);
+
+        let open_txns = rt
+            .block_on(txn_stream_writer.list_open_transactions())
+            .expect("list open transactions");
+        assert!(open_txns.is_empty());
+
+        // the journal is deleted once the writer id is retired.
+        let mut txn = rt
+            .block_on(txn_stream_writer.begin())
+            .expect("begin a transaction");
+        rt.block_on(txn_stream_writer.delete_journal())
+            .expect("delete journal");
+        let result = rt.block_on(txn_stream_writer.list_open_transactions());
+        assert!(matches!(
+            result,
+            Err(TransactionalEventWriterError::TxnJournalDisabled {})
+        ));
+        let txn_stream_writer = rt
+            .block_on(async {
+                factory
+                    .create_transactional_event_writer(stream, WriterId(123))
+                    .await
+                    .with_journal()
+                    .await
+            })
+            .expect("enable journal");
+        let open_txns = rt
+            .block_on(txn_stream_writer.list_open_transactions())
+            .expect("list open transactions");
+        assert!(open_txns.is_empty());
+        rt.block_on(txn.abort()).expect("abort transaction");
+    }
+
     // helper function
     pub(crate) async fn create_txn_stream_writer() -> (TransactionalEventWriter, ClientFactory) {
         let txn_segment = ScopedSegment::from("scope/stream/0");
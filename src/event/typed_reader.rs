@@ -0,0 +1,264 @@
+//
+// Copyright (c) Dell Inc., or its subsidiaries. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+
+use crate::event::reader::{EventReader, EventReaderError, SegmentSlice};
+use crate::event::serializer::{CborCodec, CodecError, Deserializer};
+
+use core::fmt;
+use snafu::Snafu;
+use std::marker::PhantomData;
+
+/// Read typed events from Stream.
+///
+/// TypedEventReader wraps an [`EventReader`] and uses a [`Deserializer`] to decode every event
+/// of the acquired segment slices. A failure to decode an event is reported for that event only,
+/// so the application can skip it and keep reading the rest of the slice.
+///
+/// # Examples
+///
+/// ```no_run
+/// use pravega_client_config::ClientConfigBuilder;
+/// use pravega_client::client_factory::ClientFactory;
+/// use pravega_client::event::serializer::JsonCodec;
+/// use pravega_client_shared::ScopedStream;
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize, Debug)]
+/// struct Reading {
+///     sensor: String,
+///     value: f64,
+/// }
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let config = ClientConfigBuilder::default()
+///         .controller_uri("localhost:8000")
+///         .build()
+///         .expect("creating config");
+///     let client_factory = ClientFactory::new(config);
+///     let stream = ScopedStream::from("myscope/mystream");
+///     let rg = client_factory.create_reader_group("rg".to_string(), stream).await;
+///     let mut reader = rg.create_typed_reader::<Reading, _>("r1".to_string(), JsonCodec).await;
+///     if let Some(slice) = reader.acquire_segment().await.expect("acquire segment") {
+///         for event in slice {
+///             match event {
+///                 Ok(event) => println!("Event read is {:?}", event.value),
+///                 Err(e) => println!("Failed to decode event: {}", e),
+///             }
+///         }
+///     }
+/// }
+/// ```
+///
+/// [`EventReader`]: crate::event::reader::EventReader
+/// [`Deserializer`]: crate::event::serializer::Deserializer
+pub struct TypedEventReader<T, D = CborCodec> {
+    reader: EventReader,
+    deserializer: D,
+    _event_type: PhantomData<fn() -> T>,
+}
+
+impl<T, D: Deserializer<T> + Clone> TypedEventReader<T, D> {
+    pub(crate) fn new(reader: EventReader, deserializer: D) -> Self {
+        TypedEventReader {
+            reader,
+            deserializer,
+            _event_type: PhantomData,
+        }
+    }
+
+    /// Acquire a segment slice whose events are decoded with the deserializer of this reader.
+    ///
+    /// See [`EventReader::acquire_segment`] for details.
+    ///
+    /// [`EventReader::acquire_segment`]: crate::event::reader::EventReader::acquire_segment
+    pub async fn acquire_segment(&mut self) -> Result<Option<TypedSegmentSlice<T, D>>, EventReaderError> {
+        let slice = self.reader.acquire_segment().await?;
+        Ok(slice.map(|slice| TypedSegmentSlice::new(slice, self.deserializer.clone())))
+    }
+
+    /// Release a partially read segment slice back to event reader.
+    pub async fn release_segment(&mut self, slice: TypedSegmentSlice<T, D>) -> Result<(), EventReaderError> {
+        self.reader.release_segment(slice.slice).await
+    }
+
+    /// Release a segment back to the reader and also indicate the offset up to which the segment slice is consumed.
+    pub async fn release_segment_at(
+        &mut self,
+        slice: TypedSegmentSlice<T, D>,
+        offset: i64,
+    ) -> Result<(), EventReaderError> {
+        self.reader.release_segment_at(slice.slice, offset).await
+    }
+
+    /// Mark the reader as offline.
+    pub async fn reader_offline(&mut self) -> Result<(), EventReaderError> {
+        self.reader.reader_offline().await
+    }
+
+    /// Return the underlying untyped EventReader.
+    pub fn into_inner(self) -> EventReader {
+        self.reader
+    }
+}
+
+/// This represents a decoded event that was read from a Pravega Segment and the offset at which
+/// the event was read from.
+#[derive(Debug)]
+pub struct TypedEvent<T> {
+    pub offset_in_segment: i64,
+    pub value: T,
+}
+
+/// A segment slice that decodes its events while iterating.
+pub struct TypedSegmentSlice<T, D = CborCodec> {
+    slice: SegmentSlice,
+    deserializer: D,
+    _event_type: PhantomData<fn() -> T>,
+}
+
+impl<T, D: Deserializer<T>> TypedSegmentSlice<T, D> {
+    fn new(slice: SegmentSlice, deserializer: D) -> Self {
+        TypedSegmentSlice {
+            slice,
+            deserializer,
+            _event_type: PhantomData,
+        }
+    }
+
+    /// The name of the segment this slice reads from.
+    pub fn segment(&self) -> &str {
+        &self.slice.meta.scoped_segment
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.slice.is_empty()
+    }
+
+    /// Returns true if this slice is a checkpoint marker rather than segment data.
+    pub fn is_checkpoint(&self) -> bool {
+        self.slice.is_checkpoint()
+    }
+
+    /// Returns the name of the checkpoint if this slice is a checkpoint marker.
+    pub fn get_checkpoint_name(&self) -> Option<&str> {
+        self.slice.get_checkpoint_name()
+    }
+}
+
+impl<T, D: Deserializer<T>> Iterator for TypedSegmentSlice<T, D> {
+    type Item = Result<TypedEvent<T>, TypedEventReaderError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let event = self.slice.next()?;
+        let decoded = match self.deserializer.deserialize(&event.value) {
+            Ok(value) => Ok(TypedEvent {
+                offset_in_segment: event.offset_in_segment,
+                value,
+            }),
+            Err(source) => Err(TypedEventReaderError::Decode {
+                segment: self.slice.meta.scoped_segment.clone(),
+                offset_in_segment: event.offset_in_segment,
+                value: event.value,
+                source,
+            }),
+        };
+        Some(decoded)
+    }
+}
+
+impl<T, D> fmt::Debug for TypedSegmentSlice<T, D> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TypedSegmentSlice")
+            .field("meta", &self.slice.meta)
+            .finish()
+    }
+}
+
+#[derive(Debug, Snafu)]
+pub enum TypedEventReaderError {
+    /// The event could not be decoded. The raw bytes of the event are kept so that
+    /// the application can inspect or dead-letter them.
+    #[snafu(display(
+        "Failed to decode event at offset {} of segment {}: {}",
+        offset_in_segment,
+        segment,
+        source
+    ))]
+    Decode {
+        segment: String,
+        offset_in_segment: i64,
+        value: Vec<u8>,
+        source: CodecError,
+    },
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::event::reader::SliceMetadata;
+    use crate::event::serializer::{JsonCodec, Serializer};
+    use bytes::{BufMut, BytesMut};
+    use pravega_wire_protocol::commands::{Command, EventCommand};
+    use serde::{Deserialize, Serialize};
+    use std::sync::Arc;
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    struct Reading {
+        value: u32,
+    }
+
+    fn slice_with_events(payloads: Vec<Vec<u8>>) -> SegmentSlice {
+        let mut buf = BytesMut::new();
+        for data in payloads {
+            buf.put_i32(EventCommand::TYPE_CODE);
+            buf.put_i32(data.len() as i32);
+            buf.put(data.as_slice());
+        }
+        let mut meta = SliceMetadata::default();
+        meta.scoped_segment = "scope/stream/0.#epoch.0".to_string();
+        meta.set_segment_data(0, buf);
+        let slice = SegmentSlice {
+            meta,
+            slice_return_tx: None,
+            checkpoint: None,
+            config: Arc::default(),
+        };
+        slice
+    }
+
+    #[test]
+    fn test_typed_slice_reports_decode_error_per_event() {
+        let good = JsonCodec.serialize(&Reading { value: 1 }).expect("serialize");
+        let bad = b"not json".to_vec();
+        let slice = slice_with_events(vec![good.clone(), bad.clone(), good]);
+        let mut typed: TypedSegmentSlice<Reading, JsonCodec> = TypedSegmentSlice::new(slice, JsonCodec);
+
+        let first = typed.next().expect("event").expect("decode");
+        assert_eq!(first.value, Reading { value: 1 });
+        assert_eq!(first.offset_in_segment, 0);
+
+        match typed.next().expect("event") {
+            Err(TypedEventReaderError::Decode {
+                offset_in_segment,
+                value,
+                ..
+            }) => {
+                assert_eq!(value, bad);
+                assert!(offset_in_segment > 0);
+            }
+            Ok(_) => panic!("expected decode failure"),
+        }
+
+        let third = typed.next().expect("event").expect("decode");
+        assert_eq!(third.value, Reading { value: 1 });
+        assert!(typed.next().is_none());
+    }
+}
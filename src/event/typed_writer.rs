@@ -0,0 +1,117 @@
+//
+// Copyright (c) Dell Inc., or its subsidiaries. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+
+use crate::error::Error;
+use crate::event::serializer::{CborCodec, Serializer};
+use crate::event::writer::EventWriter;
+
+use std::marker::PhantomData;
+use tokio::sync::oneshot;
+
+/// Write typed events exactly once to a given stream.
+///
+/// TypedEventWriter wraps an [`EventWriter`] and uses a [`Serializer`] to convert every event
+/// into bytes before handing it to the underlying writer. By default events are encoded with CBOR.
+///
+/// # Examples
+///
+/// ```no_run
+/// use pravega_client_config::ClientConfigBuilder;
+/// use pravega_client::client_factory::ClientFactory;
+/// use pravega_client::event::serializer::JsonCodec;
+/// use pravega_client_shared::ScopedStream;
+/// use serde::Serialize;
+///
+/// #[derive(Serialize)]
+/// struct Reading {
+///     sensor: String,
+///     value: f64,
+/// }
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let config = ClientConfigBuilder::default()
+///         .controller_uri("localhost:8000")
+///         .build()
+///         .expect("creating config");
+///     let client_factory = ClientFactory::new(config);
+///     let stream = ScopedStream::from("myscope/mystream");
+///     let mut writer = client_factory.create_typed_event_writer(stream, JsonCodec);
+///     let reading = Reading { sensor: "temperature".to_string(), value: 21.5 };
+///     let result = writer.write_event(&reading).await;
+///     assert!(result.await.is_ok())
+/// }
+/// ```
+///
+/// [`EventWriter`]: crate::event::writer::EventWriter
+/// [`Serializer`]: crate::event::serializer::Serializer
+pub struct TypedEventWriter<T, S = CborCodec> {
+    writer: EventWriter,
+    serializer: S,
+    _event_type: PhantomData<fn(&T)>,
+}
+
+impl<T, S: Serializer<T>> TypedEventWriter<T, S> {
+    pub(crate) fn new(writer: EventWriter, serializer: S) -> Self {
+        TypedEventWriter {
+            writer,
+            serializer,
+            _event_type: PhantomData,
+        }
+    }
+
+    /// Serialize and write an event without routing key.
+    ///
+    /// If the event cannot be serialized, the returned oneshot contains an `InvalidInput` error
+    /// and nothing is sent to the stream. See [`EventWriter::write_event`] for the rest of the semantics.
+    ///
+    /// [`EventWriter::write_event`]: crate::event::writer::EventWriter::write_event
+    pub async fn write_event(&mut self, event: &T) -> oneshot::Receiver<Result<(), Error>> {
+        match self.serializer.serialize(event) {
+            Ok(payload) => self.writer.write_event(payload).await,
+            Err(e) => Self::serialize_failure(e),
+        }
+    }
+
+    /// Serialize and write an event with a routing key.
+    ///
+    /// Same as the write_event.
+    pub async fn write_event_by_routing_key(
+        &mut self,
+        routing_key: String,
+        event: &T,
+    ) -> oneshot::Receiver<Result<(), Error>> {
+        match self.serializer.serialize(event) {
+            Ok(payload) => self.writer.write_event_by_routing_key(routing_key, payload).await,
+            Err(e) => Self::serialize_failure(e),
+        }
+    }
+
+    /// Flush data.
+    ///
+    /// It will wait until all pending appends have acknowledgment.
+    pub async fn flush(&mut self) -> Result<(), Error> {
+        self.writer.flush().await
+    }
+
+    /// Return the underlying untyped EventWriter.
+    pub fn into_inner(self) -> EventWriter {
+        self.writer
+    }
+
+    fn serialize_failure(e: impl std::fmt::Display) -> oneshot::Receiver<Result<(), Error>> {
+        let (tx, rx) = oneshot::channel();
+        tx.send(Err(Error::InvalidInput {
+            msg: format!("failed to serialize event: {}", e),
+        }))
+        .expect("send error");
+        rx
+    }
+}
@@ -10,17 +10,31 @@
 
 use crate::client_factory::ClientFactoryAsync;
 use crate::error::Error;
+use crate::event::compression::{self, Compression};
+use crate::event::headers::{self, EventHeaders};
+use crate::event::large_event;
+use crate::event::routing::{HashRoutingStrategy, RoutingStrategy};
+use crate::event::transactional_writer::TransactionalEventWriter;
 use crate::segment::event::{Incoming, PendingEvent, RoutingInfo};
 use crate::segment::reactor::Reactor;
+use crate::segment::writer::SegmentWriterConfig;
 use crate::util::get_random_u128;
 
+use derive_builder::*;
+use getset::{CopyGetters, Getters};
 use pravega_client_channel::{create_channel, ChannelSender};
-use pravega_client_shared::{ScopedStream, WriterId};
+use pravega_client_shared::{ScopedSegment, ScopedStream, Timestamp, TransactionStatus, WriterId};
+use pravega_wire_protocol::commands::TYPE_PLUS_LENGTH_SIZE;
+use snafu::Snafu;
 
 use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use tokio::sync::oneshot;
 use tokio::sync::oneshot::error::TryRecvError;
-use tracing::info_span;
+use tokio::task::JoinHandle;
+use tokio::time::{sleep, timeout};
+use tracing::{info, info_span, warn};
 use tracing_futures::Instrument;
 
 /// Write events exactly once to a given stream.
@@ -28,7 +42,27 @@ use tracing_futures::Instrument;
 /// EventWriter spawns a `Reactor` that runs in the background for processing incoming events.
 /// The `write` method sends the event to the `Reactor` asynchronously and returns a `tokio::oneshot::Receiver`
 /// which contains the result of the write to the caller. The maximum size of the serialized event
-/// supported is 8MB, writing size larger than that will return an error.
+/// supported is 8MB, writing size larger than that will return an error unless large events are
+/// enabled.
+///
+/// ## Large events
+/// With [`with_large_events`], an event larger than 8MB is split into chunks that are written
+/// atomically in a transaction on the routing key of the event, so the chunks are stored next to each
+/// other in the same segment. The write waits for the previous events to be acknowledged and for the
+/// transaction to be committed before returning, which keeps the order of the events.
+/// The readers need [`ReaderConfig::large_events`] to reassemble such events.
+///
+/// [`with_large_events`]: EventWriter::with_large_events
+/// [`ReaderConfig::large_events`]: crate::event::reader::ReaderConfig::large_events
+///
+/// ## Compression
+/// With [`with_compression`], the events are compressed before being written and wrapped in an
+/// envelope naming the codec. Readers with [`ReaderConfig::decompress_events`] detect the envelope and
+/// decompress the events, so compressed and uncompressed writers can share a stream. Events that do
+/// not shrink are written uncompressed.
+///
+/// [`with_compression`]: EventWriter::with_compression
+/// [`ReaderConfig::decompress_events`]: crate::event::reader::ReaderConfig::decompress_events
 ///
 /// ## Backpressure
 /// Write has a backpressure mechanism. Internally, it uses [`Channel`] to send event to
@@ -36,7 +70,23 @@ use tracing_futures::Instrument;
 /// is reached, any further write will not be accepted until enough space has been freed in the [`Channel`].
 ///
 /// [`channel`]: pravega_client_channel
-/// [`capacity`]: EventWriter::CHANNEL_CAPACITY
+/// [`capacity`]: EventWriterConfig::channel_capacity
+///
+/// ## Rate limit
+/// The bytes and events written per second by all the writers created from the same client factory can
+/// be limited with [`ClientConfig::max_write_bytes_per_sec`] and [`ClientConfig::max_write_events_per_sec`].
+/// A write waits until the limit allows it before it is sent to the Reactor, each chunk of a large event
+/// waits on its own. The total time spent waiting is published as the
+/// `pravega.client.writer.throttled_time_ms` counter.
+///
+/// [`ClientConfig::max_write_bytes_per_sec`]: pravega_client_config::ClientConfig::max_write_bytes_per_sec
+/// [`ClientConfig::max_write_events_per_sec`]: pravega_client_config::ClientConfig::max_write_events_per_sec
+///
+/// ## Batching
+/// Events are sent to a segment in append blocks. By default a single append block is inflight per
+/// segment and the pending events are sent as soon as it is acknowledged. [`EventWriterConfig`] allows
+/// more inflight append blocks and a linger time to wait for more events to fill up a block, trading
+/// latency for throughput.
 ///
 /// ## Retry
 ///
@@ -46,6 +96,46 @@ use tracing_futures::Instrument;
 ///
 /// [`retry`]: pravega_client_retry
 ///
+/// ## Routing
+/// The events with the same routing key are written to the same segment, so they are read in the
+/// order they are written. The [`RoutingStrategy`] of the [`EventWriterConfig`] maps the routing keys
+/// to the segments, by default the routing keys are hashed like the Java client does.
+/// [`write_event_to_segment`] writes an event to a given segment instead, for applications which
+/// place their events by key range. The write fails if the segment is not an open segment of the
+/// stream.
+///
+/// [`RoutingStrategy`]: crate::event::routing::RoutingStrategy
+/// [`write_event_to_segment`]: EventWriter::write_event_to_segment
+///
+/// ## Exactly once across restarts
+/// A writer created with a stable [`writer_id`] and writing its events with
+/// [`write_event_with_sequence_number`] can be restarted after a crash and write again the events
+/// which may not have been persisted. The segment writers recover the last event number the server
+/// has for the writer id and skip the events whose sequence number is not above it, so that no event
+/// is duplicated. Such a writer should write all of its events with sequence numbers. The events
+/// take a routing key, so that an event written again after a restart goes to the same segment.
+///
+/// The last event number is kept per segment, so the guarantee does not hold across a merge of
+/// segments. The unacknowledged events of the merged segments are resent to their successor one
+/// segment after the other, and the successor gets event numbers that no longer follow the sequence
+/// numbers. An event written again after a restart may then be skipped although it was never
+/// persisted.
+///
+/// [`writer_id`]: EventWriterConfig::writer_id
+/// [`write_event_with_sequence_number`]: EventWriter::write_event_with_sequence_number
+///
+/// ## Failures
+/// If the controller cannot be reached, the stream is sealed or deleted, or the writer is not
+/// authorized to write to the stream, the pending writes and any later write fail with an
+/// [`EventWriterError`].
+///
+/// ## Close
+/// Dropping the EventWriter stops the reactor in the background and the events which are not
+/// persisted yet are lost. [`close`] waits for the events to be persisted, stops the reactor and
+/// hands back the events which could not be persisted so that they can be stored or retried.
+///
+/// [`close`]: EventWriter::close
+///
 /// # Examples
 ///
 /// ```no_run
@@ -76,30 +166,190 @@ use tracing_futures::Instrument;
 /// ```
 pub struct EventWriter {
     writer_id: WriterId,
+    stream: ScopedStream,
+    factory: ClientFactoryAsync,
     sender: ChannelSender<Incoming>,
     event_handles: VecDeque<oneshot::Receiver<Result<(), Error>>>,
+    large_events: bool,
+    large_event_writer: Option<TransactionalEventWriter>,
+    compression: Compression,
+    reactor_handle: Option<JoinHandle<()>>,
+    last_sequence_number: i64,
+}
+
+/// An event which was not persisted when the [`EventWriter`] was closed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnackedEvent {
+    /// The routing key of the event, None if it was written without routing key.
+    pub routing_key: Option<String>,
+    /// The segment the event was written to by [`EventWriter::write_event_to_segment`].
+    pub segment: Option<ScopedSegment>,
+    /// The event as it was passed to the write method.
+    pub payload: Vec<u8>,
+    /// The headers passed to [`EventWriter::write_event_with_headers`], empty for other events.
+    pub headers: EventHeaders,
+}
+
+/// The failures after which an [`EventWriter`] can no longer write events.
+///
+/// The error is returned to every write which is not acknowledged yet and to every later write,
+/// wrapped in [`Error::WriterFailure`].
+#[derive(Debug, Snafu, Clone, PartialEq, Eq)]
+pub enum EventWriterError {
+    #[snafu(display("Controller is unavailable: {}", error_msg))]
+    ControllerUnavailable { error_msg: String },
+
+    #[snafu(display("Stream {} is sealed", stream))]
+    StreamSealed { stream: ScopedStream },
+
+    #[snafu(display("Stream {} is deleted", stream))]
+    StreamDeleted { stream: ScopedStream },
+
+    #[snafu(display("Authentication failed: {}", error_msg))]
+    AuthFailed { error_msg: String },
+}
+
+/// The configuration of an [`EventWriter`].
+///
+/// # Examples
+///
+/// ```no_run
+/// use pravega_client::event::writer::EventWriterConfigBuilder;
+/// use std::time::Duration;
+///
+/// // a throughput-oriented writer which batches events for up to 10ms.
+/// let config = EventWriterConfigBuilder::default()
+///     .max_inflight_appends(4usize)
+///     .linger(Duration::from_millis(10))
+///     .build()
+///     .expect("creating writer config");
+/// ```
+#[derive(Builder, Debug, CopyGetters, Getters, Clone)]
+#[builder(setter(into), build_fn(validate = "Self::validate"))]
+pub struct EventWriterConfig {
+    /// The total size of the events held in memory before a write waits for space to be freed.
+    #[get_copy = "pub"]
+    #[builder(default = "16 * 1024 * 1024")]
+    pub channel_capacity: usize,
+
+    /// The maximum number of append blocks sent to a segment but not acknowledged yet.
+    #[get_copy = "pub"]
+    #[builder(default = "1")]
+    pub max_inflight_appends: usize,
+
+    /// The size an append block is filled up to before being sent, capped at the maximum write size.
+    #[get_copy = "pub"]
+    #[builder(default = "8 * 1024 * 1024 + 8")]
+    pub block_size: usize,
+
+    /// The time an event waits for more events to fill up an append block before being sent.
+    #[get_copy = "pub"]
+    #[builder(default = "Duration::from_millis(0)")]
+    pub linger: Duration,
+
+    /// A stable id for the writer, which lets a writer restarted with the same id skip the events
+    /// the server already has. A random id is picked if None.
+    ///
+    /// See the [exactly once](EventWriter#exactly-once-across-restarts) section for details.
+    #[get_copy = "pub"]
+    #[builder(default, setter(strip_option))]
+    pub writer_id: Option<WriterId>,
+
+    /// Maps the routing keys of the events to the segments of the stream.
+    ///
+    /// See the [routing](EventWriter#routing) section for details.
+    #[get = "pub"]
+    #[builder(default = "Arc::new(HashRoutingStrategy)", setter(into = false))]
+    pub routing_strategy: Arc<dyn RoutingStrategy>,
+}
+
+impl EventWriterConfigBuilder {
+    fn validate(&self) -> Result<(), String> {
+        if matches!(self.channel_capacity, Some(0)) {
+            return Err("channel capacity should be positive".to_string());
+        }
+        if matches!(self.max_inflight_appends, Some(0)) {
+            return Err("at least 1 append should be allowed in flight".to_string());
+        }
+        if matches!(self.block_size, Some(0)) {
+            return Err("block size should be positive".to_string());
+        }
+        Ok(())
+    }
+}
+
+impl Default for EventWriterConfig {
+    fn default() -> Self {
+        EventWriterConfigBuilder::default()
+            .build()
+            .expect("build default writer config")
+    }
+}
+
+impl EventWriterConfig {
+    fn segment_writer_config(&self) -> SegmentWriterConfig {
+        SegmentWriterConfig {
+            max_inflight_blocks: self.max_inflight_appends,
+            block_size: self.block_size,
+            linger: self.linger,
+            writer_id: self.writer_id,
+        }
+    }
 }
 
 impl EventWriter {
     pub(crate) const MAX_EVENT_SIZE: usize = 8 * 1024 * 1024;
-    // maximum 16 MB total size of events could be held in memory
-    const CHANNEL_CAPACITY: usize = 16 * 1024 * 1024;
+    const COMMIT_POLL_INTERVAL: Duration = Duration::from_millis(100);
 
-    pub(crate) fn new(stream: ScopedStream, factory: ClientFactoryAsync) -> Self {
-        let (tx, rx) = create_channel(Self::CHANNEL_CAPACITY);
-        let writer_id = WriterId::from(get_random_u128());
+    pub(crate) fn new(stream: ScopedStream, factory: ClientFactoryAsync, config: EventWriterConfig) -> Self {
+        let (tx, rx) = create_channel(config.channel_capacity);
+        let writer_id = config
+            .writer_id
+            .unwrap_or_else(|| WriterId::from(get_random_u128()));
         let span = info_span!("Reactor", event_stream_writer = %writer_id);
         // spawn is tied to the factory runtime.
-        factory
-            .runtime_handle()
-            .spawn(Reactor::run(stream, tx.clone(), rx, factory, None).instrument(span));
+        let reactor_handle = factory.runtime_handle().spawn(
+            Reactor::run(
+                stream.clone(),
+                tx.clone(),
+                rx,
+                factory.clone(),
+                None,
+                config.segment_writer_config(),
+                config.routing_strategy.clone(),
+            )
+            .instrument(span),
+        );
         EventWriter {
             writer_id,
+            stream,
+            factory,
             sender: tx,
             event_handles: VecDeque::new(),
+            large_events: false,
+            large_event_writer: None,
+            compression: Compression::None,
+            reactor_handle: Some(reactor_handle),
+            last_sequence_number: 0,
         }
     }
 
+    /// Allow events larger than the maximum event size by writing them in chunks.
+    ///
+    /// See the [large events](EventWriter#large-events) section for details.
+    pub fn with_large_events(mut self) -> Self {
+        self.large_events = true;
+        self
+    }
+
+    /// Compress the events with the given codec.
+    ///
+    /// See the [compression](EventWriter#compression) section for details.
+    pub fn with_compression(mut self, compression: Compression) -> Self {
+        self.compression = compression;
+        self
+    }
+
     /// Write an event without routing key.
     ///
     /// A random routing key will be generated in this case.
@@ -120,6 +370,15 @@ impl EventWriter {
     /// result.await.expect("flush to server");
     /// ```
     pub async fn write_event(&mut self, event: Vec<u8>) -> oneshot::Receiver<Result<(), Error>> {
+        let event = match self.compress(event) {
+            Ok(event) => event,
+            Err(e) => return Self::failed(e),
+        };
+        if self.is_large_event(&event) {
+            // the chunks must be routed to the same segment.
+            let routing_key = get_random_u128().to_string();
+            return self.write_large_event(routing_key, event).await;
+        }
         let size = event.len();
         let (tx, rx) = oneshot::channel();
         let (tx_flush, rx_flush) = oneshot::channel();
@@ -142,6 +401,13 @@ impl EventWriter {
         routing_key: String,
         event: Vec<u8>,
     ) -> oneshot::Receiver<Result<(), Error>> {
+        let event = match self.compress(event) {
+            Ok(event) => event,
+            Err(e) => return Self::failed(e),
+        };
+        if self.is_large_event(&event) {
+            return self.write_large_event(routing_key, event).await;
+        }
         let size = event.len();
         let (tx, rx) = oneshot::channel();
         let (tx_flush, rx_flush) = oneshot::channel();
@@ -156,6 +422,221 @@ impl EventWriter {
         }
     }
 
+    /// Writes an event along with its headers, such as a tracing id, a content type or a schema version.
+    ///
+    /// The headers are written in an envelope in front of the payload, readers with
+    /// [`ReaderConfig::event_headers`] hand them out in [`Event::headers()`]. A random routing key
+    /// is generated if the routing key is None.
+    ///
+    /// Same as the write_event.
+    ///
+    /// [`ReaderConfig::event_headers`]: crate::event::reader::ReaderConfig::event_headers
+    /// [`Event::headers()`]: crate::event::reader::Event::headers
+    pub async fn write_event_with_headers(
+        &mut self,
+        routing_key: Option<String>,
+        headers: EventHeaders,
+        payload: Vec<u8>,
+    ) -> oneshot::Receiver<Result<(), Error>> {
+        let event = match headers::encode(&headers, &payload) {
+            Ok(event) => event,
+            Err(e) => return Self::failed(Error::InvalidInput { msg: e.to_string() }),
+        };
+        match routing_key {
+            Some(routing_key) => self.write_event_by_routing_key(routing_key, event).await,
+            None => self.write_event(event).await,
+        }
+    }
+
+    /// Writes an event to the given segment, which must be an open segment of the stream.
+    ///
+    /// The current segments of the stream and their key ranges are given by
+    /// [`ControllerClient::get_current_segments`].
+    ///
+    /// Same as the write_event.
+    ///
+    /// [`ControllerClient::get_current_segments`]: pravega_controller_client::ControllerClient::get_current_segments
+    pub async fn write_event_to_segment(
+        &mut self,
+        segment: ScopedSegment,
+        event: Vec<u8>,
+    ) -> oneshot::Receiver<Result<(), Error>> {
+        if segment.scope != self.stream.scope || segment.stream != self.stream.stream {
+            return Self::failed(Error::InvalidInput {
+                msg: format!("segment {} does not belong to stream {}", segment, self.stream),
+            });
+        }
+        let event = match self.compress(event) {
+            Ok(event) => event,
+            Err(e) => return Self::failed(e),
+        };
+        let size = event.len();
+        let (tx, rx) = oneshot::channel();
+        let (tx_flush, rx_flush) = oneshot::channel();
+        let routing_info = RoutingInfo::Segment(segment);
+        if let Some(pending_event) =
+            PendingEvent::with_header_flush(routing_info, event, None, tx, Some(tx_flush))
+        {
+            let append_event = Incoming::AppendEvent(pending_event);
+            self.writer_event_internal(append_event, size, rx, rx_flush).await
+        } else {
+            rx
+        }
+    }
+
+    /// Writes an event numbered by the application with the given routing key.
+    ///
+    /// The sequence numbers must be positive and increasing. An event whose sequence number is not
+    /// above the last event number the server has for the writer id is acknowledged without being
+    /// written again. See the [exactly once](EventWriter#exactly-once-across-restarts) section for
+    /// details. Events larger than the maximum event size are rejected.
+    ///
+    /// The routing key is required since the server only recognizes an event written again if it is
+    /// written to the same segment.
+    pub async fn write_event_with_sequence_number(
+        &mut self,
+        routing_key: String,
+        sequence_number: i64,
+        event: Vec<u8>,
+    ) -> oneshot::Receiver<Result<(), Error>> {
+        if sequence_number <= self.last_sequence_number {
+            return Self::failed(Error::InvalidInput {
+                msg: format!(
+                    "sequence number {} is not above the previous sequence number {}",
+                    sequence_number, self.last_sequence_number
+                ),
+            });
+        }
+        let event = match self.compress(event) {
+            Ok(event) => event,
+            Err(e) => return Self::failed(e),
+        };
+        let size = event.len();
+        let (tx, rx) = oneshot::channel();
+        let (tx_flush, rx_flush) = oneshot::channel();
+        let routing_info = RoutingInfo::RoutingKey(Some(routing_key));
+        if let Some(mut pending_event) =
+            PendingEvent::with_header_flush(routing_info, event, None, tx, Some(tx_flush))
+        {
+            pending_event.sequence_number = Some(sequence_number);
+            self.last_sequence_number = sequence_number;
+            let append_event = Incoming::AppendEvent(pending_event);
+            self.writer_event_internal(append_event, size, rx, rx_flush).await
+        } else {
+            rx
+        }
+    }
+
+    /// Writes a batch of events with their optional routing keys.
+    ///
+    /// The capacity of the whole batch is reserved at once and a single oneshot is returned, which
+    /// resolves once every event of the batch is acknowledged. If some of the events fail, the error
+    /// lists the index of each failed event in the batch along with its error. A random routing key is
+    /// generated for the events without routing key.
+    ///
+    /// Large events cannot be written in a batch. With [`with_large_events`], a batch containing an
+    /// event larger than 8MB is rejected as a whole with an `InvalidInput` error and none of its events
+    /// is written, such events should be written with [`write_event_by_routing_key`] instead.
+    ///
+    /// [`with_large_events`]: EventWriter::with_large_events
+    /// [`write_event_by_routing_key`]: EventWriter::write_event_by_routing_key
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let mut event_writer = client_factory.create_event_writer(stream);
+    /// let events = vec![(Some("key".to_string()), b"a".to_vec()), (None, b"b".to_vec())];
+    /// let result = event_writer.write_events(events).await;
+    /// result.await.expect("flush batch to server");
+    /// ```
+    pub async fn write_events(
+        &mut self,
+        events: Vec<(Option<String>, Vec<u8>)>,
+    ) -> oneshot::Receiver<Result<(), Error>> {
+        if let Err(err) = self.clear_initial_complete_events() {
+            // fail fast upon checking previous write events
+            return Self::failed(err);
+        }
+        let total = events.len();
+        let events: Vec<_> = events
+            .into_iter()
+            .map(|(routing_key, event)| (routing_key, self.compress(event)))
+            .collect();
+        let large_event = events
+            .iter()
+            .position(|(_, event)| matches!(event, Ok(event) if self.is_large_event(event)));
+        if let Some(index) = large_event {
+            return Self::failed(Error::InvalidInput {
+                msg: format!(
+                    "event {} of the batch exceeds {} bytes, large events cannot be written in a batch",
+                    index,
+                    Self::MAX_EVENT_SIZE
+                ),
+            });
+        }
+        let mut pending_events = Vec::with_capacity(total);
+        let mut receivers = Vec::with_capacity(total);
+        let mut size = 0;
+        for (routing_key, event) in events {
+            let (tx, rx) = oneshot::channel();
+            receivers.push(rx);
+            let event = match event {
+                Ok(event) => event,
+                Err(e) => {
+                    tx.send(Err(e)).expect("send error");
+                    continue;
+                }
+            };
+            let routing_info = RoutingInfo::RoutingKey(routing_key);
+            // an event which is too large fails on its own.
+            if let Some(pending_event) = PendingEvent::with_header(routing_info, event, None, tx) {
+                size += pending_event.data.len();
+                pending_events.push(pending_event);
+            }
+        }
+        if !pending_events.is_empty() {
+            self.throttle(size, pending_events.len()).await;
+            if let Err(_e) = self
+                .sender
+                .send((Incoming::AppendEvents(pending_events), size))
+                .await
+            {
+                return Self::failed(Error::InternalFailure {
+                    msg: "failed to send request to reactor".to_string(),
+                });
+            }
+        }
+
+        let (tx, rx) = oneshot::channel();
+        let (tx_flush, rx_flush) = oneshot::channel();
+        self.event_handles.push_back(rx_flush);
+        self.factory.runtime_handle().spawn(async move {
+            let mut failures = vec![];
+            for (index, receiver) in receivers.into_iter().enumerate() {
+                let result = receiver.await.unwrap_or_else(|e| {
+                    Err(Error::InternalFailure {
+                        msg: format!("oneshot error {:?}", e),
+                    })
+                });
+                if let Err(e) = result {
+                    failures.push((index, e));
+                }
+            }
+            let result = if failures.is_empty() {
+                Ok(())
+            } else {
+                Err(Error::BatchFailure { total, failures })
+            };
+            let flush_result = match &result {
+                Ok(()) => Ok(()),
+                Err(e) => Err(Error::InternalFailure { msg: e.to_string() }),
+            };
+            let _ = tx_flush.send(flush_result);
+            let _ = tx.send(result);
+        });
+        rx
+    }
+
     async fn writer_event_internal(
         &mut self,
         append_event: Incoming,
@@ -165,10 +646,10 @@ impl EventWriter {
     ) -> oneshot::Receiver<Result<(), Error>> {
         if let Err(err) = self.clear_initial_complete_events() {
             // fail fast upon checking previous write events
-            let (tx_error, rx_error) = oneshot::channel();
-            tx_error.send(Err(err)).expect("send error");
-            rx_error
-        } else if let Err(_e) = self.sender.send((append_event, size)).await {
+            return Self::failed(err);
+        }
+        self.throttle(size, 1).await;
+        if let Err(_e) = self.sender.send((append_event, size)).await {
             let (tx_error, rx_error) = oneshot::channel();
             tx_error
                 .send(Err(Error::InternalFailure {
@@ -182,6 +663,84 @@ impl EventWriter {
         }
     }
 
+    // waits for the write rate limit of the client factory, if any.
+    async fn throttle(&self, bytes: usize, events: usize) {
+        if let Some(limiter) = self.factory.write_rate_limiter() {
+            limiter.acquire(&self.stream, bytes, events).await;
+        }
+    }
+
+    fn compress(&self, event: Vec<u8>) -> Result<Vec<u8>, Error> {
+        compression::compress(self.compression, event)
+            .map_err(|e| Error::InternalFailure { msg: e.to_string() })
+    }
+
+    fn failed(err: Error) -> oneshot::Receiver<Result<(), Error>> {
+        let (tx_error, rx_error) = oneshot::channel();
+        tx_error.send(Err(err)).expect("send error");
+        rx_error
+    }
+
+    fn is_large_event(&self, event: &[u8]) -> bool {
+        self.large_events && event.len() > Self::MAX_EVENT_SIZE
+    }
+
+    async fn write_large_event(
+        &mut self,
+        routing_key: String,
+        event: Vec<u8>,
+    ) -> oneshot::Receiver<Result<(), Error>> {
+        let (tx, rx) = oneshot::channel();
+        let result = self.write_chunks(routing_key, event).await;
+        tx.send(result).expect("send result");
+        rx
+    }
+
+    // Write the chunks of a large event in a transaction once the previous events are persisted.
+    async fn write_chunks(&mut self, routing_key: String, event: Vec<u8>) -> Result<(), Error> {
+        self.flush().await?;
+        if self.large_event_writer.is_none() {
+            let writer =
+                TransactionalEventWriter::new(self.stream.clone(), self.writer_id, self.factory.clone())
+                    .await;
+            self.large_event_writer = Some(writer);
+        }
+        let writer = self.large_event_writer.as_mut().expect("large event writer");
+        let to_error = |e: String| Error::InternalFailure {
+            msg: format!("failed to write large event: {}", e),
+        };
+        let mut txn = writer.begin().await.map_err(|e| to_error(e.to_string()))?;
+        let chunks = large_event::split_into_chunks(get_random_u128(), &event, Self::MAX_EVENT_SIZE);
+        info!(
+            "writing large event of {} bytes in {} chunks to transaction {}",
+            event.len(),
+            chunks.len(),
+            txn.txn_id()
+        );
+        for chunk in chunks {
+            self.throttle(chunk.len(), 1).await;
+            if let Err(e) = txn.write_event(Some(routing_key.clone()), chunk).await {
+                let _ = txn.abort().await;
+                return Err(to_error(e.to_string()));
+            }
+        }
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("system time after unix epoch");
+        txn.commit(Timestamp(now.as_millis() as u64))
+            .await
+            .map_err(|e| to_error(e.to_string()))?;
+        // the chunks are only appended to the segment once the transaction is committed, the events
+        // written after this one have to wait for it to keep the order.
+        loop {
+            match txn.check_status().await.map_err(|e| to_error(e.to_string()))? {
+                TransactionStatus::Committed => return Ok(()),
+                TransactionStatus::Committing => sleep(Self::COMMIT_POLL_INTERVAL).await,
+                status => return Err(to_error(format!("transaction {} is {:?}", txn.txn_id(), status))),
+            }
+        }
+    }
+
     /// Flush data.
     ///
     /// It will wait until all pending appends have acknowledgment.
@@ -196,6 +755,94 @@ impl EventWriter {
         Ok(())
     }
 
+    /// Close the writer and return the events which could not be persisted.
+    ///
+    /// It waits up to `flush_timeout` for the pending events to be acknowledged, then stops the
+    /// reactor and waits for it to exit. The events which are still not acknowledged are handed back
+    /// with their routing key, their write methods return a `WriterClosed` error.
+    ///
+    /// Large events are not reported. They are written in a transaction by the write method itself,
+    /// which completes once the event is committed, so none of them is pending when the writer closes.
+    /// A large event whose write was dropped before it completed is neither written nor handed back.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let mut event_writer = client_factory.create_event_writer(stream);
+    /// event_writer.write_event(payload).await;
+    /// let unacked = event_writer.close(Duration::from_secs(10)).await.expect("close writer");
+    /// for event in unacked {
+    ///     // persist or retry the event
+    /// }
+    /// ```
+    pub async fn close(mut self, flush_timeout: Duration) -> Result<Vec<UnackedEvent>, Error> {
+        match timeout(flush_timeout, self.flush()).await {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => warn!("failed to flush events before closing writer: {}", e),
+            Err(_) => warn!(
+                "events are not persisted within {:?} before closing writer",
+                flush_timeout
+            ),
+        }
+        let (tx, rx) = oneshot::channel();
+        if self.sender.send_without_bp(Incoming::Close(Some(tx))).is_err() {
+            return Err(Error::InternalFailure {
+                msg: "reactor is already closed".to_string(),
+            });
+        }
+        let unacked = rx.await.map_err(|e| Error::InternalFailure {
+            msg: format!("reactor exited before handing back unacked events {:?}", e),
+        })?;
+        if let Some(handle) = self.reactor_handle.take() {
+            handle.await.map_err(|e| Error::InternalFailure {
+                msg: format!("reactor failed {:?}", e),
+            })?;
+        }
+        info!(
+            "writer {} is closed with {} unacked events",
+            self.writer_id,
+            unacked.len()
+        );
+        Ok(unacked
+            .into_iter()
+            .map(|pending_event| self.to_unacked_event(pending_event))
+            .collect())
+    }
+
+    // Fail the write of the pending event and recover the event as it was passed to the writer.
+    fn to_unacked_event(&self, pending_event: PendingEvent) -> UnackedEvent {
+        let closed = || Error::WriterClosed {
+            msg: "event is not acknowledged".to_string(),
+        };
+        let _res = pending_event.oneshot_sender.send(Err(closed()));
+        if let Some(flush_sender) = pending_event.flush_oneshot_sender {
+            let _res = flush_sender.send(Err(closed()));
+        }
+        let (routing_key, segment) = match pending_event.routing_info {
+            RoutingInfo::RoutingKey(key) => (key, None),
+            RoutingInfo::Segment(segment) => (None, Some(segment)),
+        };
+        let data = &pending_event.data[TYPE_PLUS_LENGTH_SIZE as usize..];
+        let event = if self.compression == Compression::None {
+            data.to_vec()
+        } else {
+            // the writer compressed the event itself, its size needs no bound.
+            compression::decompress(data, usize::MAX).unwrap_or_else(|_| data.to_vec())
+        };
+        // the headers are wrapped around the payload before it is compressed.
+        let (headers, payload) = if headers::has_headers(&event) {
+            headers::decode(&event).unwrap_or_else(|_| (EventHeaders::new(), event))
+        } else {
+            (EventHeaders::new(), event)
+        };
+        UnackedEvent {
+            routing_key,
+            segment,
+            payload,
+            headers,
+        }
+    }
+
     /// Clear initial completed events from flush queue.
     fn clear_initial_complete_events(&mut self) -> Result<(), Error> {
         while let Some(mut receiver) = self.event_handles.pop_front() {
@@ -223,7 +870,7 @@ impl EventWriter {
 
 impl Drop for EventWriter {
     fn drop(&mut self) {
-        let _res = self.sender.send_without_bp(Incoming::Close());
+        let _res = self.sender.send_without_bp(Incoming::Close(None));
     }
 }
 
@@ -232,7 +879,44 @@ mod tests {
     use tokio::runtime::Runtime;
 
     use super::*;
+    use crate::client_factory::ClientFactory;
+    use crate::event::reader_group::StreamCutVersioned;
     use crate::segment::event::{PendingEvent, RoutingInfo};
+    use crate::util::create_stream;
+    use pravega_client_config::connection_type::{ConnectionType, MockType};
+    use pravega_client_config::ClientConfigBuilder;
+    use pravega_client_shared::PravegaNodeUri;
+
+    #[test]
+    fn test_event_writer_config() {
+        let config = EventWriterConfig::default();
+        assert_eq!(config.channel_capacity(), 16 * 1024 * 1024);
+        assert_eq!(config.max_inflight_appends(), 1);
+        assert_eq!(config.linger(), Duration::from_millis(0));
+
+        let config = EventWriterConfigBuilder::default()
+            .max_inflight_appends(4usize)
+            .block_size(1024usize)
+            .linger(Duration::from_millis(5))
+            .writer_id(42u128)
+            .build()
+            .expect("build config");
+        assert_eq!(config.writer_id(), Some(WriterId(42)));
+        let segment_writer_config = config.segment_writer_config();
+        assert_eq!(segment_writer_config.writer_id, Some(WriterId(42)));
+        assert_eq!(segment_writer_config.max_inflight_blocks, 4);
+        assert_eq!(segment_writer_config.block_size, 1024);
+        assert_eq!(segment_writer_config.linger, Duration::from_millis(5));
+
+        assert!(EventWriterConfigBuilder::default()
+            .max_inflight_appends(0usize)
+            .build()
+            .is_err());
+        assert!(EventWriterConfigBuilder::default()
+            .block_size(0usize)
+            .build()
+            .is_err());
+    }
 
     #[test]
     fn test_pending_event() {
@@ -256,4 +940,320 @@ mod tests {
         let reply = rt.block_on(rx).expect("get reply");
         assert!(reply.is_err());
     }
+
+    #[test]
+    fn test_write_events_batch() {
+        let config = ClientConfigBuilder::default()
+            .connection_type(ConnectionType::Mock(MockType::Happy))
+            .mock(true)
+            .controller_uri(PravegaNodeUri::from("127.0.0.2:9091".to_string()))
+            .build()
+            .unwrap();
+        let factory = ClientFactory::new(config);
+        factory
+            .runtime()
+            .block_on(create_stream(&factory, "testScopeBatch", "testStreamBatch", 2));
+        let stream = ScopedStream::from("testScopeBatch/testStreamBatch");
+        let mut writer = factory.create_event_writer(stream);
+
+        let events = vec![
+            (Some("key".to_string()), vec![1; 128]),
+            (None, vec![2; 256]),
+            (Some("key".to_string()), vec![3; 512]),
+        ];
+        let result = factory.runtime().block_on(async {
+            let rx = writer.write_events(events).await;
+            rx.await.expect("get batch result")
+        });
+        assert!(result.is_ok());
+        factory.runtime().block_on(writer.flush()).expect("flush");
+
+        // the oversized event fails on its own.
+        let events = vec![
+            (None, vec![1; 128]),
+            (None, vec![2; EventWriter::MAX_EVENT_SIZE + 1]),
+        ];
+        let result = factory.runtime().block_on(async {
+            let rx = writer.write_events(events).await;
+            rx.await.expect("get batch result")
+        });
+        match result {
+            Err(Error::BatchFailure { total, failures }) => {
+                assert_eq!(total, 2);
+                assert_eq!(failures.len(), 1);
+                assert_eq!(failures[0].0, 1);
+                assert!(matches!(failures[0].1, Error::InvalidInput { .. }));
+            }
+            _ => panic!("expected the batch to fail partially"),
+        }
+
+        // a batch with a large event is rejected as a whole.
+        let mut writer = writer.with_large_events();
+        let events = vec![
+            (None, vec![1; 128]),
+            (None, vec![2; EventWriter::MAX_EVENT_SIZE + 1]),
+        ];
+        let result = factory.runtime().block_on(async {
+            let rx = writer.write_events(events).await;
+            rx.await.expect("get batch result")
+        });
+        assert!(matches!(result, Err(Error::InvalidInput { .. })));
+    }
+
+    #[test]
+    fn test_write_event_with_sequence_number() {
+        let config = ClientConfigBuilder::default()
+            .connection_type(ConnectionType::Mock(MockType::Happy))
+            .mock(true)
+            .controller_uri(PravegaNodeUri::from("127.0.0.2:9091".to_string()))
+            .build()
+            .unwrap();
+        let factory = ClientFactory::new(config);
+        factory.runtime().block_on(create_stream(
+            &factory,
+            "testScopeSequence",
+            "testStreamSequence",
+            1,
+        ));
+        let stream = ScopedStream::from("testScopeSequence/testStreamSequence");
+        let writer_config = EventWriterConfigBuilder::default()
+            .writer_id(7u128)
+            .build()
+            .expect("build config");
+        let mut writer = factory.create_event_writer_with_config(stream, writer_config);
+        assert_eq!(writer.writer_id, WriterId(7));
+
+        factory.runtime().block_on(async {
+            let rx = writer
+                .write_event_with_sequence_number("key".to_string(), 2, vec![1; 128])
+                .await;
+            assert!(rx.await.expect("get reply").is_ok());
+            // sequence numbers must increase.
+            let rx = writer
+                .write_event_with_sequence_number("key".to_string(), 2, vec![1; 128])
+                .await;
+            assert!(matches!(
+                rx.await.expect("get reply"),
+                Err(Error::InvalidInput { .. })
+            ));
+            let rx = writer
+                .write_event_with_sequence_number("key".to_string(), 5, vec![1; 128])
+                .await;
+            assert!(rx.await.expect("get reply").is_ok());
+        });
+    }
+
+    #[test]
+    fn test_write_event_with_sequence_number_after_restart() {
+        let config = ClientConfigBuilder::default()
+            .connection_type(ConnectionType::Mock(MockType::Happy))
+            .mock(true)
+            .controller_uri(PravegaNodeUri::from("127.0.0.2:9091".to_string()))
+            .build()
+            .unwrap();
+        let factory = ClientFactory::new(config);
+        factory.runtime().block_on(create_stream(
+            &factory,
+            "testScopeRestart",
+            "testStreamRestart",
+            1,
+        ));
+        let stream = ScopedStream::from("testScopeRestart/testStreamRestart");
+        let writer_config = EventWriterConfigBuilder::default()
+            .writer_id(8u128)
+            .build()
+            .expect("build config");
+        let segment_length = || async {
+            let meta = factory.to_async().create_stream_meta_client(stream.clone()).await;
+            match meta.fetch_current_tail_segments().await.expect("fetch tail") {
+                StreamCutVersioned::V1(cut) => cut.get_positions().values().sum::<i64>(),
+                _ => panic!("expected the offsets of the tail segments"),
+            }
+        };
+
+        let mut writer = factory.create_event_writer_with_config(stream.clone(), writer_config.clone());
+        let written = factory.runtime().block_on(async {
+            for sequence_number in 1..=3 {
+                let rx = writer
+                    .write_event_with_sequence_number("key".to_string(), sequence_number, vec![1; 128])
+                    .await;
+                assert!(rx.await.expect("get reply").is_ok());
+            }
+            segment_length().await
+        });
+        drop(writer);
+
+        // the restarted writer resumes after the last event number the server has, events which
+        // were already written are acknowledged without being written again.
+        let mut writer = factory.create_event_writer_with_config(stream.clone(), writer_config);
+        factory.runtime().block_on(async {
+            let rx = writer
+                .write_event_with_sequence_number("key".to_string(), 2, vec![1; 128])
+                .await;
+            assert!(rx.await.expect("get reply").is_ok());
+            assert_eq!(segment_length().await, written);
+            let rx = writer
+                .write_event_with_sequence_number("key".to_string(), 4, vec![1; 128])
+                .await;
+            assert!(rx.await.expect("get reply").is_ok());
+            assert!(segment_length().await > written);
+        });
+    }
+
+    #[test]
+    fn test_write_event_to_segment() {
+        let config = ClientConfigBuilder::default()
+            .connection_type(ConnectionType::Mock(MockType::Happy))
+            .mock(true)
+            .controller_uri(PravegaNodeUri::from("127.0.0.2:9091".to_string()))
+            .build()
+            .unwrap();
+        let factory = ClientFactory::new(config);
+        factory.runtime().block_on(create_stream(
+            &factory,
+            "testScopeRouting",
+            "testStreamRouting",
+            2,
+        ));
+        let stream = ScopedStream::from("testScopeRouting/testStreamRouting");
+        let mut writer = factory.create_event_writer(stream);
+
+        factory.runtime().block_on(async {
+            let segment = ScopedSegment::from("testScopeRouting/testStreamRouting/1");
+            let rx = writer.write_event_to_segment(segment, vec![1; 128]).await;
+            assert!(rx.await.expect("get reply").is_ok());
+            // a segment which is not part of the stream.
+            let segment = ScopedSegment::from("testScopeRouting/testStreamRouting/5");
+            let rx = writer.write_event_to_segment(segment, vec![1; 128]).await;
+            assert!(matches!(
+                rx.await.expect("get reply"),
+                Err(Error::InvalidInput { .. })
+            ));
+            // a segment of another stream.
+            let segment = ScopedSegment::from("testScopeRouting/otherStream/0");
+            let rx = writer.write_event_to_segment(segment, vec![1; 128]).await;
+            assert!(matches!(
+                rx.await.expect("get reply"),
+                Err(Error::InvalidInput { .. })
+            ));
+        });
+    }
+
+    #[test]
+    fn test_close_event_writer() {
+        let config = ClientConfigBuilder::default()
+            .connection_type(ConnectionType::Mock(MockType::Happy))
+            .mock(true)
+            .controller_uri(PravegaNodeUri::from("127.0.0.2:9091".to_string()))
+            .build()
+            .unwrap();
+        let factory = ClientFactory::new(config);
+        factory
+            .runtime()
+            .block_on(create_stream(&factory, "testScopeClose", "testStreamClose", 2));
+        let stream = ScopedStream::from("testScopeClose/testStreamClose");
+
+        // the events persisted before closing are not handed back.
+        let mut writer = factory.create_event_writer(stream.clone());
+        let unacked = factory.runtime().block_on(async {
+            let rx = writer
+                .write_event_by_routing_key("key".to_string(), vec![1; 128])
+                .await;
+            let unacked = writer.close(Duration::from_secs(10)).await.expect("close writer");
+            assert!(rx.await.expect("get reply").is_ok());
+            unacked
+        });
+        assert!(unacked.is_empty());
+
+        // an unacked event is recovered as it was passed to the writer.
+        let writer = factory
+            .create_event_writer(stream)
+            .with_compression(Compression::Zstd);
+        let payload = b"unacked event ".repeat(16);
+        let data = writer.compress(payload.clone()).expect("compress");
+        let (tx, rx) = oneshot::channel();
+        let routing_info = RoutingInfo::RoutingKey(Some("key".to_string()));
+        let event = PendingEvent::with_header(routing_info, data, None, tx).expect("create pending event");
+        let unacked = writer.to_unacked_event(event);
+        assert_eq!(unacked.routing_key, Some("key".to_string()));
+        assert_eq!(unacked.payload, payload);
+        assert!(unacked.headers.is_empty());
+        let reply = factory.runtime().block_on(rx).expect("get reply");
+        assert!(matches!(reply, Err(Error::WriterClosed { .. })));
+
+        // the headers of an unacked event are handed back apart from its payload.
+        let mut event_headers = EventHeaders::new();
+        event_headers.insert("content-type".to_string(), "text/plain".to_string());
+        let with_headers = headers::encode(&event_headers, &payload).expect("encode");
+        let data = writer.compress(with_headers).expect("compress");
+        let (tx, _rx) = oneshot::channel();
+        let routing_info = RoutingInfo::RoutingKey(None);
+        let event = PendingEvent::with_header(routing_info, data, None, tx).expect("create pending event");
+        let unacked = writer.to_unacked_event(event);
+        assert_eq!(unacked.payload, payload);
+        assert_eq!(unacked.headers, event_headers);
+    }
+
+    #[test]
+    fn test_event_writer_rate_limit() {
+        let config = ClientConfigBuilder::default()
+            .connection_type(ConnectionType::Mock(MockType::Happy))
+            .mock(true)
+            .controller_uri(PravegaNodeUri::from("127.0.0.2:9091".to_string()))
+            .max_write_events_per_sec(4)
+            .build()
+            .unwrap();
+        let factory = ClientFactory::new(config);
+        factory
+            .runtime()
+            .block_on(create_stream(&factory, "testScopeLimit", "testStreamLimit", 1));
+        let stream = ScopedStream::from("testScopeLimit/testStreamLimit");
+
+        // the writers of a factory share the limit.
+        let mut writer1 = factory.create_event_writer(stream.clone());
+        let mut writer2 = factory.create_event_writer(stream);
+        let start = std::time::Instant::now();
+        factory.runtime().block_on(async {
+            for _ in 0..3 {
+                let rx1 = writer1.write_event(vec![1; 16]).await;
+                let rx2 = writer2.write_event(vec![2; 16]).await;
+                assert!(rx1.await.expect("get reply").is_ok());
+                assert!(rx2.await.expect("get reply").is_ok());
+            }
+        });
+        // the first 4 events are written right away and the other 2 wait for half a second.
+        assert!(start.elapsed() >= Duration::from_millis(450));
+    }
+
+    #[test]
+    fn test_event_writer_rate_limit_large_event() {
+        let config = ClientConfigBuilder::default()
+            .connection_type(ConnectionType::Mock(MockType::Happy))
+            .mock(true)
+            .controller_uri(PravegaNodeUri::from("127.0.0.2:9091".to_string()))
+            .max_write_events_per_sec(2)
+            .build()
+            .unwrap();
+        let factory = ClientFactory::new(config);
+        factory.runtime().block_on(create_stream(
+            &factory,
+            "testScopeLimitLarge",
+            "testStreamLimitLarge",
+            1,
+        ));
+        let stream = ScopedStream::from("testScopeLimitLarge/testStreamLimitLarge");
+
+        // each chunk of a large event waits for the limit on its own.
+        let mut writer = factory.create_event_writer(stream).with_large_events();
+        let start = std::time::Instant::now();
+        factory.runtime().block_on(async {
+            let rx = writer
+                .write_event_by_routing_key("key".to_string(), vec![1; EventWriter::MAX_EVENT_SIZE * 2])
+                .await;
+            assert!(rx.await.expect("get reply").is_ok());
+        });
+        // the event is split in 3 chunks, the first 2 are written right away and the last one waits
+        // for half a second.
+        assert!(start.elapsed() >= Duration::from_millis(450));
+    }
 }
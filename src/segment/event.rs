@@ -8,6 +8,7 @@
 // http://www.apache.org/licenses/LICENSE-2.0
 //
 use crate::error::Error;
+use crate::event::writer::EventWriterError;
 use pravega_client_shared::*;
 use pravega_wire_protocol::commands::{Command, EventCommand};
 use pravega_wire_protocol::wire_commands::Replies;
@@ -19,10 +20,15 @@ use uuid::Uuid;
 #[derive(Debug)]
 pub(crate) enum Incoming {
     AppendEvent(PendingEvent),
+    AppendEvents(Vec<PendingEvent>),
     ServerReply(ServerReply),
     Reconnect(WriterInfo),
     Reset(ScopedSegment),
-    Close(),
+    Flush(ScopedSegment),
+    // A segment writer hit a failure after which no event can be written.
+    Fail(EventWriterError),
+    // Stops the reactor, the events which are not acknowledged yet are handed back if requested.
+    Close(Option<oneshot::Sender<Vec<PendingEvent>>>),
 }
 
 #[derive(new, Debug)]
@@ -51,10 +57,35 @@ pub(crate) struct PendingEvent {
     pub(crate) conditional_offset: Option<i64>,
     pub(crate) oneshot_sender: oneshot::Sender<Result<(), Error>>,
     pub(crate) flush_oneshot_sender: Option<oneshot::Sender<Result<(), Error>>>,
+    // The event number assigned by the application, the segment writer numbers the event otherwise.
+    pub(crate) sequence_number: Option<i64>,
 }
 
 impl PendingEvent {
     pub(crate) const MAX_WRITE_SIZE: usize = 8 * 1024 * 1024 + 8;
+
+    /// Fails the write of this event, along with the flush waiting for it.
+    pub(crate) fn fail(self, error: &EventWriterError) {
+        // ignore the send result since error means the receiver is dropped
+        let _res = self.oneshot_sender.send(Err(Error::WriterFailure {
+            source: error.clone(),
+        }));
+        if let Some(flush_sender) = self.flush_oneshot_sender {
+            let _res = flush_sender.send(Err(Error::WriterFailure {
+                source: error.clone(),
+            }));
+        }
+    }
+
+    /// Rejects the write of this event, along with the flush waiting for it.
+    pub(crate) fn reject(self, error: Error) {
+        if let Some(flush_sender) = self.flush_oneshot_sender {
+            // ignore the send result since error means the receiver is dropped
+            let _res = flush_sender.send(Err(error.clone()));
+        }
+        let _res = self.oneshot_sender.send(Err(error));
+    }
+
     pub(crate) fn new(
         routing_info: RoutingInfo,
         data: Vec<u8>,
@@ -85,6 +116,7 @@ impl PendingEvent {
                 conditional_offset,
                 oneshot_sender,
                 flush_oneshot_sender,
+                sequence_number: None,
             })
         }
     }
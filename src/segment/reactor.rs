@@ -8,7 +8,7 @@
 // http://www.apache.org/licenses/LICENSE-2.0
 //
 
-use pravega_client_channel::{ChannelReceiver, ChannelSender};
+use pravega_client_channel::{CapacityGuard, ChannelReceiver, ChannelSender};
 use tracing::{debug, error, info, warn};
 
 use pravega_client_shared::*;
@@ -16,8 +16,12 @@ use pravega_wire_protocol::wire_commands::Replies;
 
 use crate::client_factory::ClientFactoryAsync;
 use crate::error::Error;
-use crate::segment::event::{Incoming, RoutingInfo, ServerReply};
+use crate::event::routing::RoutingStrategy;
+use crate::event::writer::EventWriterError;
+use crate::segment::event::{Incoming, PendingEvent, RoutingInfo, ServerReply};
 use crate::segment::selector::SegmentSelector;
+use crate::segment::writer::SegmentWriterConfig;
+use std::sync::Arc;
 
 #[derive(new)]
 pub(crate) struct Reactor {}
@@ -29,10 +33,15 @@ impl Reactor {
         mut receiver: ChannelReceiver<Incoming>,
         factory: ClientFactoryAsync,
         stream_segments: Option<StreamSegments>,
+        config: SegmentWriterConfig,
+        routing_strategy: Arc<dyn RoutingStrategy>,
     ) {
-        let mut selector = SegmentSelector::new(stream, sender, factory.clone()).await;
+        let mut selector =
+            SegmentSelector::new(stream, sender, factory.clone(), config, routing_strategy).await;
         // get the current segments and create corresponding event segment writers
-        selector.initialize(stream_segments).await;
+        if let Err(e) = selector.initialize(stream_segments).await {
+            Reactor::fail(&mut selector, e);
+        }
         info!("starting reactor");
         while Reactor::run_once(&mut selector, &mut receiver, &factory)
             .await
@@ -46,25 +55,32 @@ impl Reactor {
         receiver: &mut ChannelReceiver<Incoming>,
         factory: &ClientFactoryAsync,
     ) -> Result<(), &'static str> {
-        let (event, cap_guard) = receiver.recv().await.expect("sender closed, processor exit");
+        let (event, cap_guard) = match receiver.recv().await {
+            Some(message) => message,
+            None => {
+                info!("all senders are dropped, closing reactor");
+                return Err("sender closed");
+            }
+        };
+        if let Some(failure) = &selector.failure {
+            return Reactor::reject(event, failure);
+        }
         match event {
             Incoming::AppendEvent(pending_event) => {
-                let event_segment_writer = match &pending_event.routing_info {
-                    RoutingInfo::RoutingKey(key) => selector.get_segment_writer(key),
-                    RoutingInfo::Segment(segment) => selector.get_segment_writer_by_key(segment),
-                };
-
-                if event_segment_writer.need_reset {
-                    // ignore the send result since error means the receiver is dropped
-                    let _res = pending_event.oneshot_sender.send(Result::Err(Error::ConditionalCheckFailure {
-                        msg:
-                        "conditional check failed in previous appends, need to reset before processing new appends".to_string(),
-                    }));
-                    return Ok(());
-                }
-                if let Err(e) = event_segment_writer.write(pending_event, cap_guard).await {
-                    warn!("failed to write append to segment due to {:?}, reconnecting", e);
-                    event_segment_writer.reconnect(factory).await;
+                Reactor::append(selector, pending_event, cap_guard, factory).await;
+                Ok(())
+            }
+            Incoming::AppendEvents(pending_events) => {
+                // the capacity of the batch is reserved at once and released event by event.
+                let mut cap_guard = cap_guard;
+                let last = pending_events.len().saturating_sub(1);
+                for (index, pending_event) in pending_events.into_iter().enumerate() {
+                    let event_guard = if index == last {
+                        cap_guard.split(usize::MAX)
+                    } else {
+                        cap_guard.split(pending_event.data.len())
+                    };
+                    Reactor::append(selector, pending_event, event_guard, factory).await;
                 }
                 Ok(())
             }
@@ -101,13 +117,109 @@ impl Reactor {
                 writer.need_reset = false;
                 Ok(())
             }
-            Incoming::Close() => {
+            Incoming::Flush(segment) => {
+                if let Some(writer) = selector.writers.get_mut(&segment) {
+                    if let Err(e) = writer.linger_elapsed().await {
+                        warn!(
+                            "failed to write pending events to segment due to {:?}, reconnecting",
+                            e
+                        );
+                        writer.reconnect(factory).await;
+                    }
+                }
+                Ok(())
+            }
+            Incoming::Fail(error) => {
+                Reactor::fail(selector, error);
+                Ok(())
+            }
+            Incoming::Close(unacked_sender) => {
                 info!("receive signal to close reactor");
+                if let Some(unacked_sender) = unacked_sender {
+                    let unacked = selector
+                        .writers
+                        .values_mut()
+                        .flat_map(|writer| writer.get_unacked_events())
+                        .map(|append| append.event)
+                        .collect();
+                    // ignore the send result since error means the receiver is dropped
+                    let _res = unacked_sender.send(unacked);
+                }
                 Err("close")
             }
         }
     }
 
+    // Fails all the unacked events, the reactor rejects any further event until it is closed.
+    fn fail(selector: &mut SegmentSelector, error: EventWriterError) {
+        error!("writer failed due to {}, rejecting any further event", error);
+        for writer in selector.writers.values_mut() {
+            for append in writer.get_unacked_events() {
+                append.event.fail(&error);
+            }
+        }
+        selector.failure = Some(error);
+    }
+
+    // Handles the incoming requests once the writer failed.
+    fn reject(event: Incoming, failure: &EventWriterError) -> Result<(), &'static str> {
+        match event {
+            Incoming::AppendEvent(pending_event) => pending_event.fail(failure),
+            Incoming::AppendEvents(pending_events) => {
+                for pending_event in pending_events {
+                    pending_event.fail(failure);
+                }
+            }
+            Incoming::Close(unacked_sender) => {
+                info!("receive signal to close failed reactor");
+                // the unacked events have been failed already
+                if let Some(unacked_sender) = unacked_sender {
+                    let _res = unacked_sender.send(vec![]);
+                }
+                return Err("close");
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    async fn append(
+        selector: &mut SegmentSelector,
+        pending_event: PendingEvent,
+        cap_guard: CapacityGuard,
+        factory: &ClientFactoryAsync,
+    ) {
+        let event_segment_writer = match &pending_event.routing_info {
+            RoutingInfo::RoutingKey(key) => match selector.get_segment_writer(key) {
+                Ok(writer) => writer,
+                Err(e) => {
+                    pending_event.reject(e);
+                    return;
+                }
+            },
+            RoutingInfo::Segment(segment) => {
+                if !selector.writers.contains_key(segment) {
+                    let msg = format!("segment {} is not an open segment of the stream", segment);
+                    pending_event.reject(Error::InvalidInput { msg });
+                    return;
+                }
+                selector.get_segment_writer_by_key(segment)
+            }
+        };
+
+        if event_segment_writer.need_reset {
+            pending_event.reject(Error::ConditionalCheckFailure {
+                msg:
+                "conditional check failed in previous appends, need to reset before processing new appends".to_string(),
+            });
+            return;
+        }
+        if let Err(e) = event_segment_writer.write(pending_event, cap_guard).await {
+            warn!("failed to write append to segment due to {:?}, reconnecting", e);
+            event_segment_writer.reconnect(factory).await;
+        }
+    }
+
     async fn process_server_reply(
         server_reply: ServerReply,
         selector: &mut SegmentSelector,
@@ -142,13 +254,14 @@ impl Reactor {
                     cmd.segment, cmd.server_stack_trace
                 );
                 let segment = ScopedSegment::from(&*cmd.segment);
-                if let Some(inflight) = selector.refresh_segment_event_writers_upon_sealed(&segment).await {
-                    selector.resend(inflight).await;
-                    selector.remove_segment_writer(&segment);
-                    Ok(())
-                } else {
-                    Err("Stream is sealed")
+                match selector.refresh_segment_event_writers_upon_sealed(&segment).await {
+                    Ok(inflight) => {
+                        selector.resend(inflight).await;
+                        selector.remove_segment_writer(&segment);
+                    }
+                    Err(e) => Reactor::fail(selector, e),
                 }
+                Ok(())
             }
 
             Replies::NoSuchSegment(cmd) => {
@@ -157,13 +270,19 @@ impl Reactor {
                     cmd.segment, cmd.server_stack_trace
                 );
                 let segment = ScopedSegment::from(&*cmd.segment);
-                if let Some(inflight) = selector.refresh_segment_event_writers_upon_sealed(&segment).await {
-                    selector.resend(inflight).await;
-                    selector.remove_segment_writer(&segment);
-                    Ok(())
-                } else {
-                    Err("Stream is sealed")
+                match selector.refresh_segment_event_writers_upon_sealed(&segment).await {
+                    Ok(inflight) => {
+                        selector.resend(inflight).await;
+                        selector.remove_segment_writer(&segment);
+                    }
+                    // the successors of a truncated segment can no longer be found once the stream is deleted.
+                    Err(EventWriterError::ControllerUnavailable { .. }) => {
+                        let stream = selector.stream.clone();
+                        Reactor::fail(selector, EventWriterError::StreamDeleted { stream })
+                    }
+                    Err(e) => Reactor::fail(selector, e),
                 }
+                Ok(())
             }
 
             Replies::WrongHost(cmd) => {
@@ -176,6 +295,25 @@ impl Reactor {
                 Ok(())
             }
 
+            Replies::AuthTokenCheckFailed(cmd) => {
+                if cmd.is_token_expired() {
+                    info!(
+                        "delegation token expired for writer {:?}, reconnecting",
+                        writer.id
+                    );
+                    writer.signal_delegation_token_expiry();
+                    writer.reconnect(factory).await;
+                } else {
+                    Reactor::fail(
+                        selector,
+                        EventWriterError::AuthFailed {
+                            error_msg: cmd.server_stack_trace,
+                        },
+                    );
+                }
+                Ok(())
+            }
+
             Replies::ConditionalCheckFailed(cmd) => {
                 if writer.id.0 == cmd.writer_id {
                     // Conditional check failed caused by interleaved data.
@@ -237,6 +375,68 @@ pub(crate) mod test {
         assert_eq!(sender.remain(), 1024);
     }
 
+    #[test]
+    fn test_reactor_append_batch() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let (mut selector, mut sender, mut receiver, factory) =
+            rt.block_on(create_segment_selector(MockType::Happy));
+
+        // write a batch of events at once and reactor should ack each of them
+        let receivers = rt.block_on(write_batch_for_selector(&mut sender, &[128, 256, 128]));
+        let result = rt.block_on(Reactor::run_once(
+            &mut selector,
+            &mut receiver,
+            &factory.to_async(),
+        ));
+        assert!(result.is_ok());
+        assert_eq!(sender.remain(), 512);
+
+        // process the server responses, the capacity is released as events are acked
+        while sender.remain() < 1024 {
+            let result = rt.block_on(Reactor::run_once(
+                &mut selector,
+                &mut receiver,
+                &factory.to_async(),
+            ));
+            assert!(result.is_ok());
+        }
+        for receiver in receivers {
+            assert!(rt.block_on(receiver).expect("get reply").is_ok());
+        }
+    }
+
+    #[test]
+    fn test_reactor_close_returns_unacked_events() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let (mut selector, mut sender, mut receiver, factory) =
+            rt.block_on(create_segment_selector(MockType::Happy));
+
+        // the close signal is queued before the server acks the event
+        let event_handle = rt.block_on(write_once_for_selector(&mut sender, 512));
+        let (unacked_sender, unacked_receiver) = oneshot::channel();
+        sender
+            .send_without_bp(Incoming::Close(Some(unacked_sender)))
+            .expect("send close");
+        let result = rt.block_on(Reactor::run_once(
+            &mut selector,
+            &mut receiver,
+            &factory.to_async(),
+        ));
+        assert!(result.is_ok());
+        let result = rt.block_on(Reactor::run_once(
+            &mut selector,
+            &mut receiver,
+            &factory.to_async(),
+        ));
+        assert!(result.is_err());
+
+        let unacked = rt.block_on(unacked_receiver).expect("get unacked events");
+        assert_eq!(unacked.len(), 1);
+        assert_eq!(unacked[0].data, vec![1; 512]);
+        drop(unacked);
+        assert!(rt.block_on(event_handle).is_err());
+    }
+
     #[test]
     fn test_reactor_wrong_host() {
         let rt = tokio::runtime::Runtime::new().unwrap();
@@ -272,7 +472,7 @@ pub(crate) mod test {
         assert_eq!(selector.writers.len(), 2);
 
         // write data once
-        rt.block_on(write_once_for_selector(&mut sender, 512));
+        let event_handle = rt.block_on(write_once_for_selector(&mut sender, 512));
         let result = rt.block_on(Reactor::run_once(
             &mut selector,
             &mut receiver,
@@ -287,8 +487,30 @@ pub(crate) mod test {
             &mut receiver,
             &factory.to_async(),
         ));
-        // returns empty successors meaning stream is sealed
-        assert!(result.is_err());
+        // returns empty successors meaning stream is sealed, the pending event fails
+        assert!(result.is_ok());
+        let sealed = EventWriterError::StreamSealed {
+            stream: ScopedStream::from("testScope/testStream"),
+        };
+        assert_eq!(selector.failure, Some(sealed.clone()));
+        assert_eq!(sender.remain(), 1024);
+        match rt.block_on(event_handle).expect("get reply") {
+            Err(Error::WriterFailure { source }) => assert_eq!(source, sealed),
+            _ => panic!("expected the write to fail"),
+        }
+
+        // the later writes fail as well
+        let event_handle = rt.block_on(write_once_for_selector(&mut sender, 512));
+        let result = rt.block_on(Reactor::run_once(
+            &mut selector,
+            &mut receiver,
+            &factory.to_async(),
+        ));
+        assert!(result.is_ok());
+        match rt.block_on(event_handle).expect("get reply") {
+            Err(Error::WriterFailure { source }) => assert_eq!(source, sealed),
+            _ => panic!("expected the write to fail"),
+        }
     }
 
     // helper function section
@@ -303,4 +525,25 @@ pub(crate) mod test {
         sender.send((Incoming::AppendEvent(event), size)).await.unwrap();
         oneshot_receiver
     }
+
+    async fn write_batch_for_selector(
+        sender: &mut ChannelSender<Incoming>,
+        sizes: &[usize],
+    ) -> Vec<EventHandle> {
+        let mut events = vec![];
+        let mut receivers = vec![];
+        for size in sizes {
+            let (oneshot_sender, oneshot_receiver) = tokio::sync::oneshot::channel();
+            let routing_info = RoutingInfo::RoutingKey(Some("routing_key".to_string()));
+            let event = PendingEvent::new(routing_info, vec![1; *size], None, oneshot_sender, None)
+                .expect("create pending event");
+            events.push(event);
+            receivers.push(oneshot_receiver);
+        }
+        sender
+            .send((Incoming::AppendEvents(events), sizes.iter().sum()))
+            .await
+            .unwrap();
+        receivers
+    }
 }
@@ -21,11 +21,16 @@ use pravega_wire_protocol::commands::{ReadSegmentCommand, SegmentReadCommand};
 use pravega_wire_protocol::wire_commands::{Replies, Requests};
 
 use async_trait::async_trait;
+use futures::future::poll_fn;
+use futures::ready;
 use snafu::Snafu;
 use std::cmp;
 use std::collections::VecDeque;
+use std::future::Future;
+use std::pin::Pin;
 use std::result::Result as StdResult;
 use std::sync::Arc;
+use std::task::{Context, Poll};
 use tokio::runtime::Handle;
 use tokio::sync::oneshot::error::TryRecvError;
 use tokio::sync::{oneshot, Mutex};
@@ -389,15 +394,25 @@ impl PrefetchingAsyncSegmentReader {
     // Reads from buffered data.
     // Note: bytes returned could be less than requested.
     pub(crate) async fn read(&mut self, buf: &mut [u8]) -> StdResult<usize, ReaderError> {
+        poll_fn(|cx| self.poll_read(cx, buf)).await
+    }
+
+    // Polls for buffered data, registers the waker if the buffer is empty and the prefetch is in flight.
+    // Note: bytes returned could be less than requested.
+    pub(crate) fn poll_read(
+        &mut self,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<StdResult<usize, ReaderError>> {
         self.fill_buffer_if_available()?;
         self.issue_request_if_needed();
 
         while self.buffer.is_empty() {
             if self.end_of_segment {
                 // no more bytes could be read from the current offset
-                return Ok(0);
+                return Poll::Ready(Ok(0));
             }
-            self.fill_buffer().await?;
+            ready!(self.poll_fill_buffer(cx))?;
             self.issue_request_if_needed();
         }
 
@@ -425,7 +440,7 @@ impl PrefetchingAsyncSegmentReader {
             }
         }
 
-        Ok(buf.len() - need_to_read)
+        Poll::Ready(Ok(buf.len() - need_to_read))
     }
 
     // Returns the underlying reader and drops the prefetching reader.
@@ -507,21 +522,23 @@ impl PrefetchingAsyncSegmentReader {
         Ok(())
     }
 
-    async fn fill_buffer(&mut self) -> Result<(), ReaderError> {
-        if let Some(receiver) = self.receiver.take() {
-            match receiver.await {
+    fn poll_fill_buffer(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), ReaderError>> {
+        if let Some(receiver) = self.receiver.as_mut() {
+            let reply = ready!(Pin::new(receiver).poll(cx));
+            self.receiver = None;
+            match reply {
                 Ok(res) => match res {
                     Ok(cmd) => {
                         self.buffer.push_back(cmd);
                     }
-                    Err(e) => return Err(e),
+                    Err(e) => return Poll::Ready(Err(e)),
                 },
                 Err(e) => {
                     warn!("failed to receive reply from background read: {}", e);
                 }
             }
         }
-        Ok(())
+        Poll::Ready(Ok(()))
     }
 }
 
@@ -9,8 +9,11 @@
 //
 
 use crate::client_factory::ClientFactoryAsync;
+use crate::error::Error;
+use crate::event::routing::RoutingStrategy;
+use crate::event::writer::EventWriterError;
 use crate::segment::event::{Incoming, RoutingInfo};
-use crate::segment::writer::{Append, SegmentWriter};
+use crate::segment::writer::{Append, SegmentWriter, SegmentWriterConfig};
 use crate::util::get_random_f64;
 
 use pravega_client_auth::DelegationTokenProvider;
@@ -41,6 +44,15 @@ pub(crate) struct SegmentSelector {
 
     /// Delegation token for authentication.
     pub(crate) delegation_token_provider: Arc<DelegationTokenProvider>,
+
+    /// The config of the segment writers.
+    pub(crate) config: SegmentWriterConfig,
+
+    /// The failure after which no event can be written.
+    pub(crate) failure: Option<EventWriterError>,
+
+    /// Maps the routing keys to the segments.
+    pub(crate) routing_strategy: Arc<dyn RoutingStrategy>,
 }
 
 impl SegmentSelector {
@@ -48,6 +60,8 @@ impl SegmentSelector {
         stream: ScopedStream,
         sender: ChannelSender<Incoming>,
         factory: ClientFactoryAsync,
+        config: SegmentWriterConfig,
+        routing_strategy: Arc<dyn RoutingStrategy>,
     ) -> Self {
         let delegation_token_provider = factory.create_delegation_token_provider(stream.clone()).await;
         SegmentSelector {
@@ -57,12 +71,18 @@ impl SegmentSelector {
             sender,
             factory,
             delegation_token_provider: Arc::new(delegation_token_provider),
+            config,
+            failure: None,
+            routing_strategy,
         }
     }
 
     /// Initialize segment writers by setting up connections so that segment
     /// writers are ready to use after initialization.
-    pub(crate) async fn initialize(&mut self, stream_segments: Option<StreamSegments>) {
+    pub(crate) async fn initialize(
+        &mut self,
+        stream_segments: Option<StreamSegments>,
+    ) -> Result<(), EventWriterError> {
         if let Some(ss) = stream_segments {
             self.current_segments = ss;
         } else {
@@ -71,20 +91,55 @@ impl SegmentSelector {
                 .controller_client()
                 .get_current_segments(&self.stream)
                 .await
-                .expect("retry failed");
+                .map_err(|e| EventWriterError::ControllerUnavailable {
+                    error_msg: e.to_string(),
+                })?;
+        }
+        if self.current_segments.get_segments().is_empty() {
+            return Err(EventWriterError::StreamSealed {
+                stream: self.stream.clone(),
+            });
         }
         self.create_missing_writers().await;
+        Ok(())
     }
 
     /// Get a segment writer by providing an optional routing key. The stream at least owns one
-    /// segment so this method should always has writer to return.
-    pub(crate) fn get_segment_writer(&mut self, routing_key: &Option<String>) -> &mut SegmentWriter {
-        let segment = self
-            .current_segments
-            .get_segment_for_routing_key(routing_key, get_random_f64);
-        self.writers
+    /// segment so this method should always has writer to return, unless the routing strategy maps
+    /// the routing key out of the key space.
+    pub(crate) fn get_segment_writer(
+        &mut self,
+        routing_key: &Option<String>,
+    ) -> Result<&mut SegmentWriter, Error> {
+        let segment = SegmentSelector::route(&self.current_segments, &*self.routing_strategy, routing_key)?;
+        Ok(self
+            .writers
             .get_mut(segment)
-            .expect("must have corresponding writer")
+            .expect("must have corresponding writer"))
+    }
+
+    // Selects the segment of the routing key, a random segment if there is no routing key.
+    fn route<'a>(
+        segments: &'a StreamSegments,
+        routing_strategy: &dyn RoutingStrategy,
+        routing_key: &Option<String>,
+    ) -> Result<&'a ScopedSegment, Error> {
+        match routing_key {
+            Some(routing_key) => {
+                let key = routing_strategy.key(routing_key);
+                // NaN is not contained in any range.
+                if !(0.0..=1.0).contains(&key) {
+                    return Err(Error::InvalidInput {
+                        msg: format!(
+                            "routing key {} is mapped to {} which is outside of [0, 1]",
+                            routing_key, key
+                        ),
+                    });
+                }
+                Ok(segments.get_segment(key))
+            }
+            None => Ok(segments.get_segment(get_random_f64())),
+        }
     }
 
     /// Get a segment writer by providing the segment name.
@@ -100,21 +155,24 @@ impl SegmentSelector {
     pub(crate) async fn refresh_segment_event_writers_upon_sealed(
         &mut self,
         sealed_segment: &ScopedSegment,
-    ) -> Option<Vec<Append>> {
+    ) -> Result<Vec<Append>, EventWriterError> {
         let stream_segments_with_predecessors = self
             .factory
             .controller_client()
             .get_successors(sealed_segment)
             .await
-            .expect("get successors for sealed segment");
+            .map_err(|e| EventWriterError::ControllerUnavailable {
+                error_msg: e.to_string(),
+            })?;
 
         if stream_segments_with_predecessors.is_stream_sealed() {
-            None
+            Err(EventWriterError::StreamSealed {
+                stream: self.stream.clone(),
+            })
         } else {
-            Some(
-                self.update_segments_upon_sealed(stream_segments_with_predecessors, sealed_segment)
-                    .await,
-            )
+            Ok(self
+                .update_segments_upon_sealed(stream_segments_with_predecessors, sealed_segment)
+                .await)
         }
     }
 
@@ -146,6 +204,7 @@ impl SegmentSelector {
                     self.sender.clone(),
                     self.factory.config().retry_policy,
                     self.delegation_token_provider.clone(),
+                    self.config,
                 );
 
                 debug!(
@@ -165,10 +224,24 @@ impl SegmentSelector {
     pub(crate) async fn resend(&mut self, to_resend: Vec<Append>) {
         for append in to_resend {
             let segment = match &append.event.routing_info {
-                RoutingInfo::RoutingKey(key) => self
-                    .current_segments
-                    .get_segment_for_routing_key(key, get_random_f64),
-                RoutingInfo::Segment(segment) => segment,
+                RoutingInfo::RoutingKey(key) => {
+                    match SegmentSelector::route(&self.current_segments, &*self.routing_strategy, key) {
+                        Ok(segment) => segment,
+                        Err(e) => {
+                            append.event.reject(e);
+                            continue;
+                        }
+                    }
+                }
+                RoutingInfo::Segment(segment) => {
+                    if !self.current_segments.get_segments().contains(segment) {
+                        // the event was written to this very segment, which is sealed.
+                        let msg = format!("segment {} is sealed", segment);
+                        append.event.reject(Error::InvalidInput { msg });
+                        continue;
+                    }
+                    segment
+                }
             };
             let segment_writer = self.writers.get_mut(segment).expect("must have writer");
             segment_writer.add_pending(append.event, append.cap_guard);
@@ -192,6 +265,8 @@ impl SegmentSelector {
 pub(crate) mod test {
     use super::*;
     use crate::client_factory::ClientFactory;
+    use crate::event::routing::HashRoutingStrategy;
+    use crate::segment::writer::test::create_event;
     use im::HashMap as ImHashMap;
     use ordered_float::OrderedFloat;
     use pravega_client_channel::{create_channel, ChannelReceiver};
@@ -241,6 +316,156 @@ pub(crate) mod test {
         assert_eq!(selector.writers.len(), 4);
     }
 
+    // Routes every routing key to the upper half of the key space.
+    #[derive(Debug)]
+    struct UpperHalfStrategy;
+
+    impl RoutingStrategy for UpperHalfStrategy {
+        fn key(&self, _routing_key: &str) -> f64 {
+            0.75
+        }
+    }
+
+    // Routes every routing key to the given key, which may be out of the key space.
+    #[derive(Debug)]
+    struct FixedKeyStrategy(f64);
+
+    impl RoutingStrategy for FixedKeyStrategy {
+        fn key(&self, _routing_key: &str) -> f64 {
+            self.0
+        }
+    }
+
+    #[test]
+    fn test_segment_selector_routing_strategy() {
+        let rt = Runtime::new().unwrap();
+        let (mut selector, _sender, _receiver, _factory) =
+            rt.block_on(create_segment_selector(MockType::Happy));
+        selector.routing_strategy = Arc::new(UpperHalfStrategy);
+        for key in ["a", "b", "c"].iter() {
+            let writer = selector
+                .get_segment_writer(&Some(key.to_string()))
+                .expect("get writer");
+            assert_eq!(writer.segment, ScopedSegment::from("testScope/testStream/1"));
+        }
+
+        // the keys out of the key space fail the event without panicking.
+        for key in [f64::NAN, -0.5, 1.5].iter() {
+            selector.routing_strategy = Arc::new(FixedKeyStrategy(*key));
+            let result = selector.get_segment_writer(&Some("a".to_string()));
+            assert!(matches!(result, Err(Error::InvalidInput { .. })));
+        }
+        selector.routing_strategy = Arc::new(FixedKeyStrategy(1.0));
+        let writer = selector
+            .get_segment_writer(&Some("a".to_string()))
+            .expect("get writer");
+        assert_eq!(writer.segment, ScopedSegment::from("testScope/testStream/1"));
+    }
+
+    #[test]
+    fn test_segment_selector_resend_out_of_key_space() {
+        let rt = Runtime::new().unwrap();
+        let (mut selector, mut sender, mut receiver, _factory) =
+            rt.block_on(create_segment_selector(MockType::Happy));
+        let (mut event, cap_guard, event_handle) =
+            rt.block_on(create_event(128, &mut sender, &mut receiver, None));
+        let (flush_sender, flush_handle) = tokio::sync::oneshot::channel();
+        event.flush_oneshot_sender = Some(flush_sender);
+
+        // both the write and the flush waiting for it fail.
+        selector.routing_strategy = Arc::new(FixedKeyStrategy(1.5));
+        rt.block_on(selector.resend(vec![Append {
+            event_id: 1,
+            event,
+            cap_guard,
+            block_end: false,
+        }]));
+        let result = rt.block_on(event_handle).expect("get reply");
+        assert!(matches!(result, Err(Error::InvalidInput { .. })));
+        let result = rt.block_on(flush_handle).expect("get flush reply");
+        assert!(matches!(result, Err(Error::InvalidInput { .. })));
+    }
+
+    #[test]
+    fn test_segment_selector_resend_merged_segments() {
+        let rt = Runtime::new().unwrap();
+        let (mut selector, mut sender, mut receiver, _factory) =
+            rt.block_on(create_segment_selector(MockType::Happy));
+        // the segments 0 and 1 have unacknowledged events with interleaved sequence numbers.
+        for (segment, sequence_numbers) in [(0, [5, 7]), (1, [4, 6])].iter() {
+            for sequence_number in sequence_numbers.iter() {
+                let (mut event, guard, _event_handle) =
+                    rt.block_on(create_event(128, &mut sender, &mut receiver, None));
+                event.sequence_number = Some(*sequence_number);
+                selector
+                    .writers
+                    .get_mut(&ScopedSegment::from(
+                        format!("testScope/testStream/{}", segment).as_str(),
+                    ))
+                    .expect("get writer")
+                    .add_pending(event, guard);
+            }
+        }
+
+        // the segments 0 and 1 merge into segment 2 and are sealed one after the other.
+        let successor = SegmentWithRange {
+            scoped_segment: ScopedSegment::from("testScope/testStream/2"),
+            min_key: OrderedFloat::from(0.0),
+            max_key: OrderedFloat::from(1.0),
+        };
+        for (segment, key) in [(0, 0.25), (1, 0.75)].iter() {
+            let mut sp = ImHashMap::new();
+            sp.insert(successor.clone(), vec![Segment::from(0), Segment::from(1)]);
+            let mut rs = ImHashMap::new();
+            rs.insert(Segment::from(*segment), vec![successor.clone()]);
+            let ssp = StreamSegmentsWithPredecessors {
+                segment_with_predecessors: sp,
+                replacement_segments: rs,
+            };
+            let sealed_segment = ScopedSegment::from(format!("testScope/testStream/{}", segment).as_str());
+            selector.routing_strategy = Arc::new(FixedKeyStrategy(*key));
+            let to_resend = rt.block_on(selector.update_segments_upon_sealed(ssp, &sealed_segment));
+            rt.block_on(selector.resend(to_resend));
+            selector.remove_segment_writer(&sealed_segment);
+        }
+
+        // all the events are resent to segment 2 with growing event ids, none of them is skipped.
+        let event_ids: Vec<i64> = selector
+            .writers
+            .get_mut(&ScopedSegment::from("testScope/testStream/2"))
+            .expect("get writer")
+            .get_unacked_events()
+            .iter()
+            .map(|append| append.event_id)
+            .collect();
+        assert_eq!(event_ids, vec![5, 7, 8, 9]);
+    }
+
+    #[test]
+    fn test_segment_selector_controller_failure() {
+        let rt = Runtime::new().unwrap();
+        let (selector, _sender, _receiver, factory) = rt.block_on(create_segment_selector(MockType::Happy));
+
+        // the stream is unknown to the controller
+        let mut selector = rt.block_on(SegmentSelector::new(
+            ScopedStream::from("testScope/unknownStream"),
+            selector.sender.clone(),
+            factory.to_async(),
+            SegmentWriterConfig::default(),
+            Arc::new(HashRoutingStrategy),
+        ));
+        let result = rt.block_on(selector.initialize(None));
+        assert!(matches!(
+            result,
+            Err(EventWriterError::ControllerUnavailable { .. })
+        ));
+        assert!(selector.writers.is_empty());
+
+        // the stream has no segments left
+        let result = rt.block_on(selector.initialize(Some(StreamSegments::new(BTreeMap::new()))));
+        assert!(matches!(result, Err(EventWriterError::StreamSealed { .. })));
+    }
+
     // helper function section
     pub(crate) async fn create_segment_selector(
         mock: MockType,
@@ -284,13 +509,23 @@ pub(crate) mod test {
             .await
             .unwrap();
         let (sender, receiver) = create_channel(1024);
-        let mut selector = SegmentSelector::new(stream.clone(), sender.clone(), factory.to_async()).await;
+        let mut selector = SegmentSelector::new(
+            stream.clone(),
+            sender.clone(),
+            factory.to_async(),
+            SegmentWriterConfig::default(),
+            Arc::new(HashRoutingStrategy),
+        )
+        .await;
         let stream_segments = factory
             .controller_client()
             .get_current_segments(&stream)
             .await
             .unwrap();
-        selector.initialize(Some(stream_segments)).await;
+        selector
+            .initialize(Some(stream_segments))
+            .await
+            .expect("initialize selector");
         (selector, sender, receiver, factory)
     }
 }
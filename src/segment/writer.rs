@@ -10,6 +10,7 @@
 
 use crate::client_factory::ClientFactoryAsync;
 use crate::error::Error;
+use crate::event::writer::EventWriterError;
 use crate::segment::event::{Incoming, PendingEvent, ServerReply, WriterInfo};
 use crate::segment::raw_client::{RawClient, RawClientError};
 use crate::update;
@@ -32,11 +33,14 @@ use pravega_wire_protocol::error::ClientConnectionError;
 use pravega_wire_protocol::wire_commands::{Replies, Requests};
 
 use snafu::{ResultExt, Snafu};
+use std::cmp;
 use std::collections::VecDeque;
 use std::fmt;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::select;
 use tokio::sync::oneshot;
+use tokio::time::sleep;
 use tracing::{debug, error, field, info, info_span, trace};
 use tracing_futures::Instrument;
 
@@ -59,9 +63,16 @@ pub(crate) struct SegmentWriter {
     // Events that are waiting to be sent.
     pending: VecDeque<Append>,
 
+    // Total size in bytes of the events in the pending list.
+    pending_size: usize,
+
     // Incremental event id.
     event_num: i64,
 
+    // The last event number the server had for the writer id when the writer first connected, the new
+    // events with a sequence number up to it are already persisted.
+    recovered_event_num: Option<i64>,
+
     // The sender that sends back reply to reactor for processing.
     sender: ChannelSender<Incoming>,
 
@@ -78,6 +89,36 @@ pub(crate) struct SegmentWriter {
     // that the caller is fully aware of the ConditionalCheckFailure so any subsequent appends can be processed.
     // Before receiving such reset signal, reactor will reject any append.
     pub(crate) need_reset: bool,
+
+    // Controls how the pending events are batched into append blocks.
+    config: SegmentWriterConfig,
+
+    // Whether a flush of the pending events is scheduled after the linger time.
+    linger_scheduled: bool,
+}
+
+/// Controls how a SegmentWriter batches the pending events into append blocks.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct SegmentWriterConfig {
+    /// The maximum number of append blocks sent but not acknowledged.
+    pub(crate) max_inflight_blocks: usize,
+    /// The size an append block is filled up to before being sent.
+    pub(crate) block_size: usize,
+    /// The time pending events wait for more events to fill up an append block.
+    pub(crate) linger: Duration,
+    /// The id of the segment writers, a random id is picked for each segment writer if None.
+    pub(crate) writer_id: Option<WriterId>,
+}
+
+impl Default for SegmentWriterConfig {
+    fn default() -> Self {
+        SegmentWriterConfig {
+            max_inflight_blocks: 1,
+            block_size: SegmentWriter::MAX_WRITE_SIZE as usize,
+            linger: Duration::from_millis(0),
+            writer_id: None,
+        }
+    }
 }
 
 impl SegmentWriter {
@@ -91,19 +132,26 @@ impl SegmentWriter {
         sender: ChannelSender<Incoming>,
         retry_policy: RetryWithBackoff,
         delegation_token_provider: Arc<DelegationTokenProvider>,
+        config: SegmentWriterConfig,
     ) -> Self {
         SegmentWriter {
-            id: WriterId::from(get_random_u128()),
+            id: config
+                .writer_id
+                .unwrap_or_else(|| WriterId::from(get_random_u128())),
             connection: None,
             segment,
             inflight: VecDeque::new(),
             pending: VecDeque::new(),
+            pending_size: 0,
             event_num: 0,
+            recovered_event_num: None,
             sender,
             retry_policy,
             delegation_token_provider,
             connection_listener_handle: None,
             need_reset: false,
+            config,
+            linger_scheduled: false,
         }
     }
 
@@ -167,8 +215,19 @@ impl SegmentWriter {
                             self.id, self.segment, cmd.last_event_number
                         );
                         self.ack(cmd.last_event_number);
+                        // a writer with a stable id resumes after the events the server already has.
+                        self.event_num = cmp::max(self.event_num, cmd.last_event_number);
+                        if self.recovered_event_num.is_none() {
+                            self.recovered_event_num = Some(cmd.last_event_number);
+                        }
                         connection
                     }
+                    Replies::AuthTokenCheckFailed(cmd) if !cmd.is_token_expired() => {
+                        info!("append setup is not authorized: {}", cmd.server_stack_trace);
+                        return Err(SegmentWriterError::AuthFailed {
+                            error_msg: cmd.server_stack_trace,
+                        });
+                    }
                     _ => {
                         info!("append setup failed due to {:?}", reply);
                         return Err(SegmentWriterError::WrongReply {
@@ -240,32 +299,104 @@ impl SegmentWriter {
     }
 
     /// Adds the event to the pending list
-    /// then writes the pending list if the inflight list is empty.
+    /// then writes the pending list if fewer append blocks than the limit are inflight.
+    ///
+    /// If a linger time is configured, the pending list is only written once it fills up an append
+    /// block or once the linger time has elapsed.
+    ///
+    /// An event with a sequence number that is not above the last event number the server had when
+    /// the writer connected is skipped and acknowledged right away, since the server already has it.
     pub(crate) async fn write(
         &mut self,
         event: PendingEvent,
         cap_guard: CapacityGuard,
     ) -> Result<(), SegmentWriterError> {
+        if let (Some(sequence_number), Some(recovered_event_num)) =
+            (event.sequence_number, self.recovered_event_num)
+        {
+            if sequence_number <= recovered_event_num {
+                debug!(
+                    "skip event with sequence number {} already written to segment {:?}",
+                    sequence_number, self.segment
+                );
+                let _res = event.oneshot_sender.send(Ok(()));
+                if let Some(flush_sender) = event.flush_oneshot_sender {
+                    let _res = flush_sender.send(Ok(()));
+                }
+                return Ok(());
+            }
+        }
         self.add_pending(event, cap_guard);
+        if self.config.linger > Duration::from_millis(0) && self.pending_size < self.block_size() {
+            self.schedule_linger();
+            return Ok(());
+        }
         self.write_pending_events().await
     }
 
-    /// Adds the event to the pending list
+    /// Writes the pending events once the linger time has elapsed.
+    pub(crate) async fn linger_elapsed(&mut self) -> Result<(), SegmentWriterError> {
+        self.linger_scheduled = false;
+        self.write_pending_events().await
+    }
+
+    // Asks the reactor to write the pending events after the linger time.
+    fn schedule_linger(&mut self) {
+        if self.linger_scheduled {
+            return;
+        }
+        self.linger_scheduled = true;
+        let sender = self.sender.clone();
+        let segment = self.segment.clone();
+        let linger = self.config.linger;
+        tokio::spawn(async move {
+            sleep(linger).await;
+            if let Err(e) = sender.send((Incoming::Flush(segment), 0)).await {
+                debug!("failed to send flush signal to reactor {:?}", e);
+            }
+        });
+    }
+
+    fn block_size(&self) -> usize {
+        cmp::min(self.config.block_size, SegmentWriter::MAX_WRITE_SIZE as usize)
+    }
+
+    fn inflight_blocks(&self) -> usize {
+        self.inflight.iter().filter(|append| append.block_end).count()
+    }
+
+    /// Adds the event to the pending list.
+    ///
+    /// The event id is the sequence number of the event if it has one and it is above the last event
+    /// id. Event ids only ever grow since the server rejects the appends that go back, which happens
+    /// to the events resent from two merged segments.
     pub(crate) fn add_pending(&mut self, event: PendingEvent, cap_guard: CapacityGuard) {
-        self.event_num += 1;
+        self.event_num = match event.sequence_number {
+            Some(sequence_number) => cmp::max(sequence_number, self.event_num + 1),
+            None => self.event_num + 1,
+        };
+        self.pending_size += event.data.len();
         self.pending.push_back(Append {
             event_id: self.event_num,
             event,
             cap_guard,
+            block_end: false,
         });
     }
 
-    /// Writes the pending events to the server. It will grab at most MAX_WRITE_SIZE of data
-    /// from the pending list and send them to the server. Those events will be moved to inflight list waiting to be acked.
+    /// Writes the pending events to the server in append blocks until the inflight limit is reached.
     pub(crate) async fn write_pending_events(&mut self) -> Result<(), SegmentWriterError> {
-        if !self.inflight.is_empty() || self.pending.is_empty() {
-            return Ok(());
+        while self.inflight_blocks() < self.config.max_inflight_blocks && !self.pending.is_empty() {
+            self.write_append_block().await?;
         }
+        Ok(())
+    }
+
+    // Grabs at most the block size of data from the pending list and sends them to the server.
+    // Those events will be moved to inflight list waiting to be acked.
+    async fn write_append_block(&mut self) -> Result<(), SegmentWriterError> {
+        let block_size = self.block_size();
+        let block_start = self.inflight.len();
 
         let mut total_size = 0;
         let mut to_send = vec![];
@@ -281,7 +412,7 @@ impl SegmentWriter {
                 append.event.data.len(),
                 SegmentWriter::MAX_WRITE_SIZE
             );
-            if append.event.data.len() + to_send.len() <= SegmentWriter::MAX_WRITE_SIZE as usize
+            if (to_send.is_empty() || append.event.data.len() + to_send.len() <= block_size)
                 && event_count < SegmentWriter::MAX_EVENTS as usize
                 && conditional == append.event.conditional_offset.is_some()
             {
@@ -302,6 +433,8 @@ impl SegmentWriter {
                 event_count += 1;
                 total_size += append.event.data.len();
                 to_send.extend(&append.event.data);
+                let mut append = append;
+                append.block_end = false;
                 self.inflight.push_back(append);
             } else {
                 self.pending.push_front(append);
@@ -309,17 +442,19 @@ impl SegmentWriter {
             }
         }
 
+        self.pending_size -= total_size;
+        self.inflight.back_mut().expect("last event").block_end = true;
         debug!(
             "flushing {} events of total size {} to segment {:?} based on offset {:?}; event segment writer id {:?}/connection id: {:?}",
             event_count,
             to_send.len(),
             self.segment.to_string(),
-            self.inflight.front().as_ref().unwrap().event.conditional_offset,
+            self.inflight[block_start].event.conditional_offset,
             self.id,
             self.connection.as_ref().expect("must have connection").get_id(),
         );
 
-        let request = if let Some(offset) = self.inflight.front().unwrap().event.conditional_offset {
+        let request = if let Some(offset) = self.inflight[block_start].event.conditional_offset {
             Requests::ConditionalBlockEnd(ConditionalBlockEndCommand {
                 writer_id: self.id.0,
                 event_number: self.inflight.back().expect("last event").event_id,
@@ -332,7 +467,7 @@ impl SegmentWriter {
                 writer_id: self.id.0,
                 size_of_whole_events: total_size as i32,
                 data: to_send,
-                num_event: self.inflight.len() as i32,
+                num_event: event_count as i32,
                 last_event_number: self.inflight.back().expect("last event").event_id,
                 request_id: get_request_id(),
             })
@@ -403,6 +538,7 @@ impl SegmentWriter {
         while let Some(append) = self.pending.pop_front() {
             ret.push(append);
         }
+        self.pending_size = 0;
         ret
     }
 
@@ -420,6 +556,14 @@ impl SegmentWriter {
 
         // setup the connection
         let setup_res = self.setup_connection(factory).await;
+        if let Err(SegmentWriterError::AuthFailed { error_msg }) = setup_res {
+            // retrying will not help, stop writing events.
+            self.sender
+                .send((Incoming::Fail(EventWriterError::AuthFailed { error_msg }), 0))
+                .await
+                .expect("send failure signal to reactor");
+            return;
+        }
         if setup_res.is_err() {
             self.sender
                 .send((
@@ -435,9 +579,9 @@ impl SegmentWriter {
             return;
         }
 
-        while self.inflight.back().is_some() {
-            self.pending
-                .push_front(self.inflight.pop_back().expect("must have event"));
+        while let Some(append) = self.inflight.pop_back() {
+            self.pending_size += append.event.data.len();
+            self.pending.push_front(append);
         }
 
         // flush any pending events
@@ -480,6 +624,7 @@ impl SegmentWriter {
         }
 
         // clear pending list
+        self.pending_size = 0;
         while let Some(append) = self.pending.pop_back() {
             let _res = append
                 .event
@@ -523,6 +668,8 @@ pub(crate) struct Append {
     pub(crate) event_id: i64,
     pub(crate) event: PendingEvent,
     pub(crate) cap_guard: CapacityGuard,
+    // Whether this is the last event of the append block it was sent in.
+    pub(crate) block_end: bool,
 }
 
 #[derive(Debug, Snafu)]
@@ -550,6 +697,9 @@ pub enum SegmentWriterError {
 
     #[snafu(display("Conditional check failed: {}", msg))]
     ConditionalCheckFailure { msg: String },
+
+    #[snafu(display("Not authorized to write to the segment: {}", error_msg))]
+    AuthFailed { error_msg: String },
 }
 
 #[cfg(test)]
@@ -663,8 +813,11 @@ pub(crate) mod test {
         segment_writer.add_pending(event0, guard0);
         segment_writer.add_pending(event1, guard1);
 
+        assert_eq!(segment_writer.pending_size, 128 + 128);
+
         rt.block_on(segment_writer.write_pending_events()).expect("write");
         assert_eq!(segment_writer.pending.len(), 1);
+        assert_eq!(segment_writer.pending_size, 128);
         assert_eq!(segment_writer.inflight.len(), 1);
 
         segment_writer.inflight.clear();
@@ -748,6 +901,115 @@ pub(crate) mod test {
         assert_eq!(segment_writer.inflight.len(), 0);
     }
 
+    #[test]
+    fn test_segment_writer_multiple_inflight_blocks() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let (mut segment_writer, mut sender, mut receiver, factory) = create_segment_writer(MockType::Happy);
+        segment_writer.config = SegmentWriterConfig {
+            max_inflight_blocks: 2,
+            block_size: 256,
+            linger: Duration::from_millis(0),
+            ..SegmentWriterConfig::default()
+        };
+        let result = rt.block_on(segment_writer.setup_connection(&factory.to_async()));
+        assert!(result.is_ok());
+
+        for _ in 0..5 {
+            let (event, guard, _event_handle) =
+                rt.block_on(create_event(128, &mut sender, &mut receiver, None));
+            segment_writer.add_pending(event, guard);
+        }
+        // two blocks of two events are sent without waiting for the first one to be acked.
+        rt.block_on(segment_writer.write_pending_events()).expect("write");
+        assert_eq!(segment_writer.inflight_blocks(), 2);
+        assert_eq!(segment_writer.inflight.len(), 4);
+        assert_eq!(segment_writer.pending.len(), 1);
+
+        // acking the first block frees room for the remaining event.
+        let first_block_end = segment_writer.inflight[1].event_id;
+        segment_writer.ack(first_block_end);
+        rt.block_on(segment_writer.write_pending_events()).expect("write");
+        assert_eq!(segment_writer.inflight_blocks(), 2);
+        assert_eq!(segment_writer.inflight.len(), 3);
+        assert!(segment_writer.pending.is_empty());
+    }
+
+    #[test]
+    fn test_segment_writer_linger() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let (mut segment_writer, mut sender, mut receiver, factory) = create_segment_writer(MockType::Happy);
+        segment_writer.config = SegmentWriterConfig {
+            max_inflight_blocks: 1,
+            block_size: 256,
+            linger: Duration::from_millis(10),
+            ..SegmentWriterConfig::default()
+        };
+        let result = rt.block_on(segment_writer.setup_connection(&factory.to_async()));
+        assert!(result.is_ok());
+
+        // the event waits for the block to fill up.
+        let (event, guard, _event_handle) = rt.block_on(create_event(128, &mut sender, &mut receiver, None));
+        rt.block_on(segment_writer.write(event, guard)).expect("write");
+        assert!(segment_writer.inflight.is_empty());
+        assert_eq!(segment_writer.pending.len(), 1);
+
+        // the pending events are written once the linger time has elapsed.
+        rt.block_on(async {
+            loop {
+                let (incoming, _guard) = receiver.recv().await.unwrap();
+                if let Incoming::Flush(segment) = incoming {
+                    assert_eq!(segment, segment_writer.segment);
+                    break;
+                }
+            }
+        });
+        rt.block_on(segment_writer.linger_elapsed()).expect("write");
+        assert_eq!(segment_writer.inflight.len(), 1);
+        assert!(segment_writer.pending.is_empty());
+
+        // a full block is written right away.
+        let (event, guard, _event_handle) = rt.block_on(create_event(256, &mut sender, &mut receiver, None));
+        segment_writer.ack(segment_writer.inflight[0].event_id);
+        rt.block_on(segment_writer.write(event, guard)).expect("write");
+        assert_eq!(segment_writer.inflight.len(), 1);
+        assert!(segment_writer.pending.is_empty());
+    }
+
+    #[test]
+    fn test_segment_writer_sequence_numbers() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let (mut segment_writer, mut sender, mut receiver, _factory) = create_segment_writer(MockType::Happy);
+        // the server already has the events up to 5 for this writer id.
+        segment_writer.event_num = 5;
+        segment_writer.recovered_event_num = Some(5);
+
+        let (mut event, guard, event_handle) =
+            rt.block_on(create_event(128, &mut sender, &mut receiver, None));
+        event.sequence_number = Some(3);
+        rt.block_on(segment_writer.write(event, guard)).expect("write");
+        assert!(segment_writer.pending.is_empty());
+        assert!(segment_writer.inflight.is_empty());
+        assert!(rt.block_on(event_handle).expect("get reply").is_ok());
+
+        let (mut event, guard, _event_handle) =
+            rt.block_on(create_event(128, &mut sender, &mut receiver, None));
+        event.sequence_number = Some(7);
+        segment_writer.add_pending(event, guard);
+        let (event, guard, _event_handle) = rt.block_on(create_event(128, &mut sender, &mut receiver, None));
+        segment_writer.add_pending(event, guard);
+        // an event resent from a merged segment is added even though its sequence number is lower.
+        let (mut event, guard, _event_handle) =
+            rt.block_on(create_event(128, &mut sender, &mut receiver, None));
+        event.sequence_number = Some(4);
+        segment_writer.add_pending(event, guard);
+        let event_ids: Vec<i64> = segment_writer
+            .pending
+            .iter()
+            .map(|append| append.event_id)
+            .collect();
+        assert_eq!(event_ids, vec![7, 8, 9]);
+    }
+
     // helper function section
     pub(crate) fn create_segment_writer(
         mock: MockType,
@@ -773,6 +1035,7 @@ pub(crate) mod test {
                 sender.clone(),
                 factory.config().retry_policy,
                 Arc::new(delegation_token_provider),
+                SegmentWriterConfig::default(),
             ),
             sender,
             receiver,
@@ -780,7 +1043,7 @@ pub(crate) mod test {
         )
     }
 
-    async fn create_event(
+    pub(crate) async fn create_event(
         size: usize,
         sender: &mut ChannelSender<Incoming>,
         receiver: &mut ChannelReceiver<Incoming>,
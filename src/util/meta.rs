@@ -153,6 +153,43 @@ impl MetaClient {
         }
     }
 
+    ///
+    /// Fetch the current length of a Segment of the Stream.
+    ///
+    pub(crate) async fn fetch_segment_length(
+        &self,
+        scoped_segment: &ScopedSegment,
+    ) -> Result<i64, MetaClientError> {
+        self.fetch_segment_info(scoped_segment)
+            .await
+            .map(|info| info.write_offset)
+    }
+
+    ///
+    /// Fetch the successors of a sealed Segment of the Stream.
+    ///
+    pub(crate) async fn fetch_successors(
+        &self,
+        scoped_segment: &ScopedSegment,
+    ) -> Result<Vec<ScopedSegment>, MetaClientError> {
+        let res = self
+            .factory
+            .controller_client()
+            .get_successors(scoped_segment)
+            .await
+            .map_err(|e| e.error);
+        let successors = res.context(ControllerConnectionError {
+            stream: self.scoped_stream.to_string(),
+            can_retry: false, // Controller client internally retries
+            error_msg: "Failed to fetch the successors of a segment from controller".to_string(),
+        })?;
+        Ok(successors
+            .segment_with_predecessors
+            .keys()
+            .map(|segment| segment.scoped_segment.clone())
+            .collect())
+    }
+
     // Helper method to fetch Segment information for a given Segment.
     // This ensures we retry with the provided retry configuration incase of errors.
     async fn fetch_segment_info(
@@ -9,7 +9,7 @@
 //
 
 use enum_iterator::IntoEnumIterator;
-use metrics::register_gauge;
+use metrics::{register_counter, register_gauge};
 use metrics_exporter_prometheus::PrometheusBuilder;
 use std::net::SocketAddr;
 
@@ -20,6 +20,8 @@ pub(crate) enum ClientMetrics {
     AppendLatency,
     AppendBlockSize,
     OutstandingAppendCount,
+    ReaderGroupUnreadBytes,
+    WriteThrottledTime,
 }
 
 impl ClientMetrics {
@@ -43,6 +45,18 @@ impl ClientMetrics {
                     "The current outstanding appends from caller."
                 );
             }
+            ClientMetrics::ReaderGroupUnreadBytes => {
+                register_gauge!(
+                    "pravega.client.reader_group.unread_bytes",
+                    "The number of bytes the reader group has not read yet."
+                );
+            }
+            ClientMetrics::WriteThrottledTime => {
+                register_counter!(
+                    "pravega.client.writer.throttled_time_ms",
+                    "The total time the writes waited for the write rate limit."
+                );
+            }
         }
     }
 }
@@ -72,6 +86,12 @@ macro_rules! update {
             ClientMetrics::OutstandingAppendCount => {
                 metrics::gauge!("pravega.client.segment.outstanding_append_count", $value as f64, $($tags)*);
             }
+            ClientMetrics::ReaderGroupUnreadBytes => {
+                metrics::gauge!("pravega.client.reader_group.unread_bytes", $value as f64, $($tags)*);
+            }
+            ClientMetrics::WriteThrottledTime => {
+                metrics::counter!("pravega.client.writer.throttled_time_ms", $value as u64, $($tags)*);
+            }
         }
     };
 }
@@ -25,6 +25,7 @@ use crate::client_factory::ClientFactory;
 pub(crate) mod metric;
 pub mod meta;
 pub mod oneshot_holder;
+pub(crate) mod rate_limiter;
 
 thread_local! {
     pub(crate) static RNG: RefCell<Pcg32> = RefCell::new(Pcg32::from_entropy());
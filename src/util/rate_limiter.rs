@@ -0,0 +1,176 @@
+//
+// Copyright (c) Dell Inc., or its subsidiaries. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+
+use crate::util::metric::ClientMetrics;
+
+use pravega_client_config::ClientConfig;
+use pravega_client_shared::ScopedStream;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tokio::time::sleep;
+use tracing::debug;
+
+/// Limits the rate of the writes of all the writers created from the same client factory.
+///
+/// It holds a token bucket for the bytes and one for the events. A write takes its tokens from the
+/// buckets and waits until they are refilled if they do not have enough tokens. A write larger than
+/// the capacity of a bucket is still allowed, it just waits longer.
+#[derive(Debug)]
+pub(crate) struct WriteRateLimiter {
+    bytes: Option<Mutex<TokenBucket>>,
+    events: Option<Mutex<TokenBucket>>,
+}
+
+impl WriteRateLimiter {
+    /// Creates a limiter from the client config, returns None if no write rate limit is configured.
+    pub(crate) fn from_config(config: &ClientConfig) -> Option<Self> {
+        let bytes = config.max_write_bytes_per_sec();
+        let events = config.max_write_events_per_sec();
+        if bytes.is_none() && events.is_none() {
+            return None;
+        }
+        Some(WriteRateLimiter {
+            bytes: bytes.map(|rate| Mutex::new(TokenBucket::new(rate))),
+            events: events.map(|rate| Mutex::new(TokenBucket::new(rate))),
+        })
+    }
+
+    /// Waits until the given number of bytes and events are allowed to be written to the stream.
+    pub(crate) async fn acquire(&self, stream: &ScopedStream, bytes: usize, events: usize) {
+        let wait = self.reserve_write(bytes, events, Instant::now());
+        if wait > Duration::from_millis(0) {
+            debug!("write to stream {} is throttled for {:?}", stream, wait);
+            update!(
+                ClientMetrics::WriteThrottledTime,
+                wait.as_millis() as u64,
+                "Stream" => stream.to_string()
+            );
+            sleep(wait).await;
+        }
+    }
+
+    // takes the tokens of a write from both buckets and returns how long the write should wait.
+    fn reserve_write(&self, bytes: usize, events: usize, now: Instant) -> Duration {
+        std::cmp::max(
+            Self::reserve(&self.bytes, bytes as u64, now),
+            Self::reserve(&self.events, events as u64, now),
+        )
+    }
+
+    fn reserve(bucket: &Option<Mutex<TokenBucket>>, amount: u64, now: Instant) -> Duration {
+        bucket.as_ref().map_or(Duration::from_millis(0), |bucket| {
+            bucket
+                .lock()
+                .expect("acquire rate limiter lock")
+                .reserve(amount, now)
+        })
+    }
+}
+
+/// A token bucket that holds up to one second worth of tokens.
+///
+/// The tokens can go below zero so that the writes are served in the order they reserve their
+/// tokens and a write larger than the capacity does not wait forever.
+#[derive(Debug)]
+struct TokenBucket {
+    rate: f64,
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    fn new(rate: u64) -> Self {
+        TokenBucket {
+            rate: rate as f64,
+            tokens: rate as f64,
+            last_refill: Instant::now(),
+        }
+    }
+
+    /// Takes the tokens from the bucket and returns how long the caller should wait before writing.
+    fn reserve(&mut self, amount: u64, now: Instant) -> Duration {
+        if now > self.last_refill {
+            let elapsed = now.duration_since(self.last_refill).as_secs_f64();
+            self.tokens = (self.tokens + elapsed * self.rate).min(self.rate);
+            self.last_refill = now;
+        }
+        self.tokens -= amount as f64;
+        if self.tokens >= 0.0 {
+            Duration::from_millis(0)
+        } else {
+            Duration::from_secs_f64(-self.tokens / self.rate)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use pravega_client_config::{ClientConfigBuilder, MOCK_CONTROLLER_URI};
+
+    #[test]
+    fn test_token_bucket() {
+        let mut bucket = TokenBucket::new(100);
+        let start = bucket.last_refill;
+
+        // the bucket starts full
+        assert_eq!(bucket.reserve(60, start), Duration::from_millis(0));
+        assert_eq!(bucket.reserve(40, start), Duration::from_millis(0));
+        // an empty bucket waits for the refill
+        assert_eq!(bucket.reserve(50, start), Duration::from_millis(500));
+        // a later write waits behind the earlier one
+        assert_eq!(bucket.reserve(50, start), Duration::from_secs(1));
+        // the refill pays the debt back
+        assert_eq!(
+            bucket.reserve(10, start + Duration::from_secs(2)),
+            Duration::from_millis(0)
+        );
+        // the bucket holds at most one second worth of tokens
+        let later = start + Duration::from_secs(10);
+        assert_eq!(bucket.reserve(100, later), Duration::from_millis(0));
+        assert_eq!(bucket.reserve(200, later), Duration::from_secs(2));
+    }
+
+    #[test]
+    fn test_write_rate_limiter() {
+        let config = ClientConfigBuilder::default()
+            .controller_uri(MOCK_CONTROLLER_URI)
+            .build()
+            .expect("creating config");
+        assert!(WriteRateLimiter::from_config(&config).is_none());
+
+        let config = ClientConfigBuilder::default()
+            .controller_uri(MOCK_CONTROLLER_URI)
+            .max_write_events_per_sec(10)
+            .build()
+            .expect("creating config");
+        let limiter = WriteRateLimiter::from_config(&config).expect("create rate limiter");
+        let start = Instant::now();
+
+        // the bytes are not limited and the first 10 events are written right away
+        assert_eq!(
+            limiter.reserve_write(usize::MAX, 10, start),
+            Duration::from_millis(0)
+        );
+        assert_eq!(limiter.reserve_write(0, 2, start), Duration::from_millis(200));
+
+        // a write waits for the bucket that is refilled last
+        let config = ClientConfigBuilder::default()
+            .controller_uri(MOCK_CONTROLLER_URI)
+            .max_write_bytes_per_sec(1000)
+            .max_write_events_per_sec(10)
+            .build()
+            .expect("creating config");
+        let limiter = WriteRateLimiter::from_config(&config).expect("create rate limiter");
+        let start = Instant::now();
+        assert_eq!(limiter.reserve_write(1500, 10, start), Duration::from_millis(500));
+        assert_eq!(limiter.reserve_write(0, 20, start), Duration::from_secs(2));
+    }
+}
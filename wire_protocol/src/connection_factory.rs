@@ -168,10 +168,12 @@ impl ConnectionFactory for TokioConnectionFactory {
 
 type TableSegmentIndex = HashMap<String, HashMap<TableKey, TableValue>>;
 type TableSegment = HashMap<String, Vec<(TableKey, TableValue)>>;
+type EventNumbers = HashMap<(u128, String), i64>;
 
 struct MockConnectionFactory {
     segments: Arc<Mutex<HashMap<String, SegmentInfo>>>,
     writers: Arc<Mutex<HashMap<u128, String>>>,
+    event_numbers: Arc<Mutex<EventNumbers>>,
     table_segment_index: Arc<Mutex<TableSegmentIndex>>,
     table_segment: Arc<Mutex<TableSegment>>,
     mock_type: MockType,
@@ -182,6 +184,7 @@ impl MockConnectionFactory {
         MockConnectionFactory {
             segments: Arc::new(Mutex::new(HashMap::new())),
             writers: Arc::new(Mutex::new(HashMap::new())),
+            event_numbers: Arc::new(Mutex::new(HashMap::new())),
             table_segment_index: Arc::new(Mutex::new(HashMap::new())),
             table_segment: Arc::new(Mutex::new(HashMap::new())),
             mock_type,
@@ -199,6 +202,7 @@ impl ConnectionFactory for MockConnectionFactory {
             endpoint,
             self.segments.clone(),
             self.writers.clone(),
+            self.event_numbers.clone(),
             self.table_segment_index.clone(),
             self.table_segment.clone(),
             self.mock_type,
@@ -11,10 +11,11 @@
 extern crate byteorder;
 use crate::commands::{
     AppendSetupCommand, ConditionalCheckFailedCommand, DataAppendedCommand, SegmentAlreadyExistsCommand,
-    SegmentCreatedCommand, SegmentIsSealedCommand, SegmentIsTruncatedCommand, SegmentReadCommand,
-    SegmentSealedCommand, SegmentTruncatedCommand, StreamSegmentInfoCommand, TableEntries,
-    TableEntriesDeltaReadCommand, TableEntriesUpdatedCommand, TableKey, TableKeyBadVersionCommand,
-    TableKeyDoesNotExistCommand, TableKeysRemovedCommand, TableReadCommand, TableValue, WrongHostCommand,
+    SegmentCreatedCommand, SegmentDeletedCommand, SegmentIsSealedCommand, SegmentIsTruncatedCommand,
+    SegmentReadCommand, SegmentSealedCommand, SegmentTruncatedCommand, StreamSegmentInfoCommand,
+    TableEntries, TableEntriesDeltaReadCommand, TableEntriesUpdatedCommand, TableKey,
+    TableKeyBadVersionCommand, TableKeyDoesNotExistCommand, TableKeysRemovedCommand, TableReadCommand,
+    TableValue, WrongHostCommand,
 };
 use crate::connection::{Connection, ConnectionReadHalf, ConnectionWriteHalf};
 use crate::error::*;
@@ -33,6 +34,7 @@ use uuid::Uuid;
 
 type TableSegmentIndex = HashMap<String, HashMap<TableKey, TableValue>>;
 type TableSegment = HashMap<String, Vec<(TableKey, TableValue)>>;
+type EventNumbers = HashMap<(u128, String), i64>;
 
 pub struct MockConnection {
     id: Uuid,
@@ -46,6 +48,8 @@ pub struct MockConnection {
     segments: Arc<Mutex<HashMap<String, SegmentInfo>>>,
     // maps from writerId to segment
     writers: Arc<Mutex<HashMap<u128, String>>>,
+    // maps from writerId and segment to the last event number appended
+    event_numbers: Arc<Mutex<EventNumbers>>,
     // table segment index
     table_segment_index: Arc<Mutex<TableSegmentIndex>>,
     // table segment
@@ -57,6 +61,7 @@ impl MockConnection {
         endpoint: PravegaNodeUri,
         segments: Arc<Mutex<HashMap<String, SegmentInfo>>>,
         writers: Arc<Mutex<HashMap<u128, String>>>,
+        event_numbers: Arc<Mutex<EventNumbers>>,
         table_segment_index: Arc<Mutex<TableSegmentIndex>>,
         table_segment: Arc<Mutex<TableSegment>>,
         mock_type: MockType,
@@ -72,6 +77,7 @@ impl MockConnection {
             buffer_offset: 0,
             segments,
             writers,
+            event_numbers,
             table_segment_index,
             table_segment,
         }
@@ -83,6 +89,7 @@ impl Connection for MockConnection {
     async fn send_async(&mut self, payload: &[u8]) -> Result<(), ConnectionError> {
         let mut segments_guard = self.segments.lock().await;
         let mut writers_guard = self.writers.lock().await;
+        let mut event_numbers_guard = self.event_numbers.lock().await;
         let mut table_segment_index_guard = self.table_segment_index.lock().await;
         let mut table_segment_guard = self.table_segment.lock().await;
         match self.mock_type {
@@ -92,6 +99,7 @@ impl Connection for MockConnection {
                     payload,
                     &mut *segments_guard,
                     &mut *writers_guard,
+                    &mut *event_numbers_guard,
                     &mut *table_segment_index_guard,
                     &mut *table_segment_guard,
                 )
@@ -141,6 +149,7 @@ impl Connection for MockConnection {
             sender: self.sender.take().expect("split mock connection and get sender"),
             segments: self.segments.clone(),
             writers: self.writers.clone(),
+            event_numbers: self.event_numbers.clone(),
             table_segment_index: self.table_segment_index.clone(),
             table_segment: self.table_segment.clone(),
         }) as Box<dyn ConnectionWriteHalf>;
@@ -187,6 +196,8 @@ pub struct MockWritingConnection {
     segments: Arc<Mutex<HashMap<String, SegmentInfo>>>,
     // maps from writerId to segment
     writers: Arc<Mutex<HashMap<u128, String>>>,
+    // maps from writerId and segment to the last event number appended
+    event_numbers: Arc<Mutex<EventNumbers>>,
     // table segment index
     table_segment_index: Arc<Mutex<TableSegmentIndex>>,
     // table segment
@@ -217,6 +228,7 @@ impl ConnectionWriteHalf for MockWritingConnection {
     async fn send_async(&mut self, payload: &[u8]) -> Result<(), ConnectionError> {
         let mut segments_guard = self.segments.lock().await;
         let mut writers_guard = self.writers.lock().await;
+        let mut event_numbers_guard = self.event_numbers.lock().await;
         let mut table_segment_index_guard = self.table_segment_index.lock().await;
         let mut table_segment_guard = self.table_segment.lock().await;
         match self.mock_type {
@@ -226,6 +238,7 @@ impl ConnectionWriteHalf for MockWritingConnection {
                     payload,
                     &mut *segments_guard,
                     &mut *writers_guard,
+                    &mut *event_numbers_guard,
                     &mut *table_segment_index_guard,
                     &mut *table_segment_guard,
                 )
@@ -247,6 +260,7 @@ async fn send_happy(
     payload: &[u8],
     segments: &mut HashMap<String, SegmentInfo>,
     writers: &mut HashMap<u128, String>,
+    event_numbers: &mut EventNumbers,
     table_segment_index: &mut HashMap<String, HashMap<TableKey, TableValue>>,
     table_segment: &mut HashMap<String, Vec<(TableKey, TableValue)>>,
 ) -> Result<(), ConnectionError> {
@@ -266,11 +280,16 @@ async fn send_happy(
                 last_modified_time: 0,
             });
             writers.insert(cmd.writer_id, cmd.segment.to_string());
+            // i64::MIN when there is no previous event of this writer in this segment
+            let last_event_number = event_numbers
+                .get(&(cmd.writer_id, cmd.segment.to_string()))
+                .copied()
+                .unwrap_or(i64::MIN);
             let reply = Replies::AppendSetup(AppendSetupCommand {
                 request_id: cmd.request_id,
                 segment: cmd.segment,
                 writer_id: cmd.writer_id,
-                last_event_number: i64::MIN, // when there is no previous event in this segment
+                last_event_number,
             });
             sender.send(reply).expect("send reply");
         }
@@ -288,6 +307,7 @@ async fn send_happy(
                 return Ok(());
             }
             segment_info.write_offset += cmd.data.len() as i64;
+            event_numbers.insert((cmd.writer_id, segment.to_string()), cmd.last_event_number);
 
             let reply = Replies::DataAppended(DataAppendedCommand {
                 writer_id: cmd.writer_id,
@@ -381,6 +401,15 @@ async fn send_happy(
             };
             sender.send(reply).expect("send reply");
         }
+        Requests::DeleteTableSegment(cmd) => {
+            table_segment_index.remove(&cmd.segment);
+            table_segment.remove(&cmd.segment);
+            let reply = Replies::SegmentDeleted(SegmentDeletedCommand {
+                request_id: cmd.request_id,
+                segment: cmd.segment,
+            });
+            sender.send(reply).expect("send reply");
+        }
         Requests::UpdateTableEntries(cmd) => {
             let index = table_segment_index
                 .get_mut(&cmd.segment)
@@ -527,6 +556,7 @@ async fn send_happy(
             });
             sender.send(reply).expect("send reply");
             segment_info.write_offset += cmd.data.len() as i64;
+            event_numbers.insert((cmd.writer_id, segment.to_string()), cmd.event_number);
         }
         _ => {
             panic!("unsupported request {:?}", request);
@@ -654,6 +684,7 @@ mod test {
             Arc::new(Mutex::new(HashMap::new())),
             Arc::new(Mutex::new(HashMap::new())),
             Arc::new(Mutex::new(HashMap::new())),
+            Arc::new(Mutex::new(HashMap::new())),
             MockType::Happy,
         );
         let request = Requests::Hello(HelloCommand {